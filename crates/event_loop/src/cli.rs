@@ -0,0 +1,171 @@
+//! Command-line options.
+
+use std::{env, ffi::OsString, fmt, path::PathBuf};
+
+/// Usage message printed by `--help`.
+pub const USAGE: &str = "\
+Usage: event_loop [OPTIONS] [FEED-FILE]...
+
+Display feeds from FEED-FILEs. Without them, display the feeds listed in the urls
+file, or example feeds if there's no urls file.
+
+Options:
+  -C, --config-file <PATH> read the configuration from PATH
+  -c, --cache-file <PATH>  use PATH as the cache file
+  -u, --url-file <PATH>    read the subscriptions from PATH
+  -i, --import-from-opml <PATH>
+                           add feeds from OPML file PATH to the urls file and exit
+  -e, --export-to-opml     print the subscriptions as OPML and exit
+  -h, --help               print this help and exit";
+
+/// Options the program was started with.
+#[derive(Debug, Default)]
+pub struct Options {
+    /// Was `--help` given?
+    pub show_help: bool,
+
+    /// Config file, if given with `--config-file`.
+    pub config_file: Option<PathBuf>,
+
+    /// File in which feeds and their items are cached, if given with `--cache-file`.
+    pub cache_file: Option<PathBuf>,
+
+    /// File listing the subscriptions, if given with `--url-file`.
+    pub url_file: Option<PathBuf>,
+
+    /// OPML file to import subscriptions from, if given with `--import-from-opml`.
+    pub import_opml: Option<PathBuf>,
+
+    /// Was `--export-to-opml` given?
+    pub export_opml: bool,
+
+    /// Files containing feeds to display.
+    pub feed_files: Vec<PathBuf>,
+}
+
+/// A problem with the command line.
+#[derive(Debug)]
+pub enum CliError {
+    /// An option that we don't know of.
+    UnknownOption(String),
+
+    /// An option that needs a value was given none.
+    MissingValue(&'static str),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => write!(f, "unknown option `{}`", option),
+            CliError::MissingValue(option) => write!(f, "option `{}` needs a value", option),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl Options {
+    /// Parse the options the program was started with.
+    pub fn from_env() -> Result<Options, CliError> {
+        Options::parse(env::args_os().skip(1))
+    }
+
+    /// Parse `args`, which shouldn't include the program name.
+    ///
+    /// Anything after `--` is treated as a feed file, even if it starts with a dash.
+    pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Result<Options, CliError> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        let mut only_files = false;
+
+        while let Some(arg) = args.next() {
+            if only_files {
+                options.feed_files.push(PathBuf::from(arg));
+                continue;
+            }
+
+            match arg.to_str() {
+                Some("-h") | Some("--help") => options.show_help = true,
+
+                Some("-C") | Some("--config-file") => {
+                    let value = args.next().ok_or(CliError::MissingValue("--config-file"))?;
+                    options.config_file = Some(PathBuf::from(value));
+                }
+
+                Some("-c") | Some("--cache-file") => {
+                    let value = args.next().ok_or(CliError::MissingValue("--cache-file"))?;
+                    options.cache_file = Some(PathBuf::from(value));
+                }
+
+                Some("-u") | Some("--url-file") => {
+                    let value = args.next().ok_or(CliError::MissingValue("--url-file"))?;
+                    options.url_file = Some(PathBuf::from(value));
+                }
+
+                Some("-i") | Some("--import-from-opml") => {
+                    let value = args
+                        .next()
+                        .ok_or(CliError::MissingValue("--import-from-opml"))?;
+                    options.import_opml = Some(PathBuf::from(value));
+                }
+
+                Some("-e") | Some("--export-to-opml") => options.export_opml = true,
+
+                Some("--") => only_files = true,
+
+                Some(option) if option.starts_with('-') && option != "-" => {
+                    return Err(CliError::UnknownOption(option.to_string()))
+                }
+
+                _ => options.feed_files.push(PathBuf::from(arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Where the cache lives unless `--cache-file` says otherwise:
+/// `$XDG_DATA_HOME/newsboat-tui-rs/cache.db`, or `~/.local/share/newsboat-tui-rs/cache.db` if the
+/// variable isn't set.
+///
+/// Returns `None` if neither `$XDG_DATA_HOME` nor `$HOME` is set.
+pub fn default_cache_file() -> Option<PathBuf> {
+    Some(xdg_dir("XDG_DATA_HOME", ".local/share")?.join("cache.db"))
+}
+
+/// Where `history.cmdline` and `history.search` live: `$XDG_DATA_HOME/newsboat-tui-rs`, or
+/// `~/.local/share/newsboat-tui-rs` if the variable isn't set.
+///
+/// Returns `None` if neither `$XDG_DATA_HOME` nor `$HOME` is set.
+pub fn default_history_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// Where the urls file lives unless `--url-file` says otherwise:
+/// `$XDG_CONFIG_HOME/newsboat-tui-rs/urls`, or `~/.config/newsboat-tui-rs/urls` if the variable
+/// isn't set.
+///
+/// Returns `None` if neither `$XDG_CONFIG_HOME` nor `$HOME` is set.
+pub fn default_url_file() -> Option<PathBuf> {
+    Some(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("urls"))
+}
+
+/// Where the config file lives unless `--config-file` says otherwise:
+/// `$XDG_CONFIG_HOME/newsboat-tui-rs/config`, or `~/.config/newsboat-tui-rs/config` if the
+/// variable isn't set.
+///
+/// Returns `None` if neither `$XDG_CONFIG_HOME` nor `$HOME` is set.
+pub fn default_config_file() -> Option<PathBuf> {
+    Some(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("config"))
+}
+
+/// Our subdirectory of the XDG base directory named by `variable`, or of `fallback` in the home
+/// directory if the variable isn't set.
+fn xdg_dir(variable: &str, fallback: &str) -> Option<PathBuf> {
+    let base = env::var_os(variable)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))?;
+    Some(base.join("newsboat-tui-rs"))
+}
@@ -1,6 +1,10 @@
 //! Watcher for keypresses and signals.
 
-use std::{sync::mpsc, thread};
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
 
 #[cfg(feature = "termion")]
 use termion::input::TermRead;
@@ -10,6 +14,14 @@ use crossterm::event;
 
 use ui::event::Event;
 
+/// How long `try_next()` gives the input thread to read a key that's already waiting in stdin.
+///
+/// The input thread reads one key at a time and then waits for it to be processed, so the next key
+/// only reaches the channel after `try_next()` resumes it. If the thread isn't scheduled within
+/// this time, the key is left for the next `next()`, i.e. for after a redraw. Coalescing keys is
+/// thus best-effort: it saves frames when keys come in quickly, but nothing depends on it.
+const INPUT_GRACE: Duration = Duration::from_millis(1);
+
 #[cfg(feature = "termion")]
 fn try_termion_key_to_ours(key: termion::event::Key) -> Option<ui::event::Key> {
     use ui::event::Key;
@@ -23,7 +35,7 @@ fn try_termion_key_to_ours(key: termion::event::Key) -> Option<ui::event::Key> {
         termion::event::Key::End => Some(Key::End),
         termion::event::Key::PageUp => Some(Key::PageUp),
         termion::event::Key::PageDown => Some(Key::PageDown),
-        termion::event::Key::BackTab => None, // what is this key?
+        termion::event::Key::BackTab => Some(Key::BackTab),
         termion::event::Key::Delete => Some(Key::Delete),
         termion::event::Key::Insert => Some(Key::Insert),
         termion::event::Key::F(f) => Some(Key::F(f)),
@@ -50,9 +62,9 @@ fn try_crossterm_keycode_to_ours(keycode: event::KeyCode) -> Option<ui::event::K
         event::KeyCode::End => Some(Key::End),
         event::KeyCode::PageUp => Some(Key::PageUp),
         event::KeyCode::PageDown => Some(Key::PageDown),
-        // TODO: test how Termion represents this and emulate it
-        event::KeyCode::Tab => None,
-        event::KeyCode::BackTab => None, // what is this key?
+        // Termion reports Tab as a character, and so should we.
+        event::KeyCode::Tab => Some(Key::Char('\t')),
+        event::KeyCode::BackTab => Some(Key::BackTab),
         event::KeyCode::Delete => Some(Key::Delete),
         event::KeyCode::Insert => Some(Key::Insert),
         event::KeyCode::F(f) => Some(Key::F(f)),
@@ -88,20 +100,41 @@ fn try_crossterm_key_to_ours(key: event::KeyEvent) -> Option<ui::event::Key> {
                 );
             }
         }
+        // Termion reports Ctrl and Alt combinations with the base key, and so should we.
+        if key.modifiers.contains(event::KeyModifiers::CONTROL) {
+            result = Key::Ctrl(Box::new(result));
+        }
+        if key.modifiers.contains(event::KeyModifiers::ALT) {
+            result = Key::Alt(Box::new(result));
+        }
         result
     })
 }
 
 /// Watcher for keypresses and signals.
+///
+/// The thread that reads user input stops after each keypress and waits until the key is
+/// processed, i.e. until the next call to `next()`. While it waits, stdin is free, so whoever
+/// processes the key can hand the terminal over to another program.
 pub struct EventsSource {
     /// Receive channel from which events can be read.
     rx: mpsc::Receiver<Event>,
+
+    /// Send side of the same channel, for other sources of events, e.g. background reloads.
+    tx: mpsc::Sender<Event>,
+
+    /// Channel through which the input thread is told to resume reading stdin.
+    resume_input_tx: mpsc::Sender<()>,
+
+    /// Is the input thread waiting for us to process a keypress?
+    input_paused: bool,
 }
 
 impl EventsSource {
     /// Create a new events source.
     pub fn new() -> EventsSource {
         let (tx, rx) = mpsc::channel();
+        let (resume_input_tx, resume_input_rx) = mpsc::channel();
 
         // A thread that reads user input
         #[cfg(feature = "termion")]
@@ -111,17 +144,14 @@ impl EventsSource {
             let tx = tx.clone();
             thread::spawn(move || {
                 let stdin = io::stdin();
-                for event in stdin.keys() {
-                    match event {
-                        Ok(key) => {
-                            if let Some(key) = try_termion_key_to_ours(key) {
-                                if let Err(_) = tx.send(Event::Key(key)) {
-                                    return;
-                                }
-                            }
+                for key in stdin.keys().flatten() {
+                    if let Some(key) = try_termion_key_to_ours(key) {
+                        if tx.send(Event::Key(key)).is_err() {
+                            return;
+                        }
+                        if resume_input_rx.recv().is_err() {
+                            return;
                         }
-
-                        Err(_) => {}
                     }
                 }
             });
@@ -134,14 +164,17 @@ impl EventsSource {
                 match event::read() {
                     Ok(event::Event::Key(key)) => {
                         if let Some(key) = try_crossterm_key_to_ours(key) {
-                            if let Err(_) = tx.send(Event::Key(key)) {
+                            if tx.send(Event::Key(key)).is_err() {
+                                return;
+                            }
+                            if resume_input_rx.recv().is_err() {
                                 return;
                             }
                         }
                     }
 
                     Ok(event::Event::Resize(_, _)) => {
-                        if let Err(_) = tx.send(Event::TerminalResized) {
+                        if tx.send(Event::TerminalResized).is_err() {
                             return;
                         }
                     }
@@ -157,35 +190,115 @@ impl EventsSource {
         }
 
         // A thread that watches out for signals
-        #[cfg(feature = "termion")]
         {
             use signal_hook::iterator::Signals;
 
+            // Crossterm reports terminal resizes along with the input, so we only need SIGWINCH
+            // for termion.
+            #[cfg(feature = "termion")]
+            let watched_signals = [
+                libc::SIGWINCH,
+                libc::SIGTSTP,
+                libc::SIGCONT,
+                libc::SIGINT,
+                libc::SIGTERM,
+                libc::SIGHUP,
+            ];
+            #[cfg(feature = "crossterm")]
+            let watched_signals = [
+                libc::SIGTSTP,
+                libc::SIGCONT,
+                libc::SIGINT,
+                libc::SIGTERM,
+                libc::SIGHUP,
+            ];
+
             let tx = tx.clone();
-            let mut signals = Signals::new(&[libc::SIGWINCH])
+            let mut signals = Signals::new(watched_signals)
                 .expect("Something went wrong while registering the handler");
             thread::spawn(move || {
                 for signal in signals.forever() {
-                    match signal {
-                        libc::SIGWINCH => {
-                            if let Err(_) = tx.send(Event::TerminalResized) {
-                                return;
-                            }
-                        }
+                    let event = match signal {
+                        libc::SIGWINCH => Event::TerminalResized,
+                        libc::SIGTSTP => Event::SuspendRequested,
+                        libc::SIGCONT => Event::Continued,
+                        libc::SIGINT => Event::Interrupted,
+                        libc::SIGTERM => Event::Terminated,
+                        libc::SIGHUP => Event::HungUp,
 
                         _ => {
                             // We didn't register for any other signals, so let's ignore them.
+                            continue;
                         }
+                    };
+
+                    if tx.send(event).is_err() {
+                        return;
                     }
                 }
             });
         }
 
-        EventsSource { rx }
+        EventsSource {
+            rx,
+            tx,
+            resume_input_tx,
+            input_paused: false,
+        }
+    }
+
+    /// A channel through which other threads can send their events to `next()`.
+    pub fn sender(&self) -> mpsc::Sender<Event> {
+        self.tx.clone()
     }
 
     /// Get next event (blocking operation)
-    pub fn next(&self) -> Result<Event, mpsc::RecvError> {
-        self.rx.recv()
+    ///
+    /// If the previous event was a keypress, this lets the input thread read stdin again. If
+    /// nothing happens until `deadline`, `Event::Tick` is returned; if the deadline has passed
+    /// already, it's returned right away, so that a stream of other events can't hold timers up.
+    pub fn next(&mut self, deadline: Option<Instant>) -> Result<Event, mpsc::RecvError> {
+        self.resume_input();
+
+        let event = match deadline {
+            Some(deadline) if deadline <= Instant::now() => Event::Tick,
+            Some(deadline) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                match self.rx.recv_timeout(timeout) {
+                    Ok(event) => event,
+                    Err(mpsc::RecvTimeoutError::Timeout) => Event::Tick,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return Err(mpsc::RecvError),
+                }
+            }
+            None => self.rx.recv()?,
+        };
+        if let Event::Key(_) = event {
+            self.input_paused = true;
+        }
+        Ok(event)
+    }
+
+    /// Get the next event if there's one already, e.g. the next of the keys that the user holds
+    /// down. Like `next()`, this lets the input thread read stdin again.
+    ///
+    /// A key that's waiting in stdin is only returned if the input thread reads it within
+    /// `INPUT_GRACE`; otherwise, `None` is returned and the key comes from a later call.
+    pub fn try_next(&mut self) -> Option<Event> {
+        self.resume_input();
+        let event = self.rx.recv_timeout(INPUT_GRACE).ok()?;
+        if let Event::Key(_) = event {
+            self.input_paused = true;
+        }
+        Some(event)
+    }
+
+    /// Let the input thread read stdin again, if it's waiting for us to process a keypress.
+    fn resume_input(&mut self) {
+        if self.input_paused {
+            self.input_paused = false;
+            // If the input thread is gone, we'll find out from `recv()` once the other threads
+            // are gone too.
+            let _ = self.resume_input_tx.send(());
+        }
     }
 }
@@ -1,100 +1,316 @@
 use ratatui::Terminal;
-use std::{error::Error, io};
-
-#[cfg(feature = "crossterm")]
-use ratatui::backend::CrosstermBackend;
-#[cfg(feature = "termion")]
-use ratatui::backend::TermionBackend;
-
-#[cfg(feature = "crossterm")]
-use crossterm::{
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-#[cfg(feature = "termion")]
-use termion::{
-    raw::{IntoRawMode, RawTerminal},
-    screen::{AlternateScreen, IntoAlternateScreen},
+use std::{
+    cell::RefCell,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, Command},
+    rc::Rc,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
 };
 
+mod cli;
 mod events_source;
+mod terminal;
 
+use cli::Options;
 use events_source::EventsSource;
+use terminal::{TerminalBackend, TerminalGuard};
 use ui::{
     app::App,
+    cache::Cache,
+    config::Config,
     event::{Event, Key},
+    example,
+    feed::Feed,
+    opml, parser, reload, urls,
 };
 
-/// Setup a termion terminal with alternate screen enabled.
-#[cfg(feature = "termion")]
-fn setup_termion_terminal(
-) -> Result<Terminal<TermionBackend<AlternateScreen<RawTerminal<io::Stdout>>>>, io::Error> {
-    let stdout = io::stdout().into_raw_mode()?;
-    let stdout = stdout.into_alternate_screen()?;
+/// Open the cache named on the command line, or the default one, creating its directory if
+/// necessary.
+fn open_cache(options: &Options) -> Result<Cache, Box<dyn Error>> {
+    let path = match options.cache_file {
+        Some(ref path) => path.clone(),
+        None => cli::default_cache_file()
+            .ok_or("can't find the cache file: neither XDG_DATA_HOME nor HOME are set")?,
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let cache = Cache::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(cache)
+}
 
-    let backend = TermionBackend::new(stdout);
+/// Read the config file named on the command line, or the default one if it exists. Without
+/// either, the defaults are used.
+fn load_config(options: &Options) -> Result<Config, Box<dyn Error>> {
+    let mut config = Config::default();
+    let path = match options.config_file {
+        Some(ref path) => Some(path.clone()),
+        None => cli::default_config_file().filter(|path| path.exists()),
+    };
+    if let Some(path) = path {
+        config.load_file(&path)?;
+    }
+    Ok(config)
+}
 
-    Terminal::new(backend)
+/// The urls file named on the command line, or the default one if it exists.
+fn url_file(options: &Options) -> Option<PathBuf> {
+    match options.url_file {
+        Some(ref path) => Some(path.clone()),
+        None => cli::default_url_file().filter(|path| path.exists()),
+    }
 }
 
-/// Setup a crossterm terminal with alternate screen enabled.
-#[cfg(feature = "crossterm")]
-fn setup_crossterm_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>, io::Error> {
-    enable_raw_mode()?;
-    execute!(io::stdout(), EnterAlternateScreen)?;
+/// Feeds to display: the ones from feed files at `paths`, or the ones listed in the `url_file`, or
+/// examples if there's neither. Items that were seen before get their read state from the `cache`.
+fn load_feeds(
+    paths: &[PathBuf],
+    url_file: Option<&Path>,
+    cache: &Cache,
+) -> Result<Vec<Rc<RefCell<Feed>>>, Box<dyn Error>> {
+    if let Some(path) = url_file {
+        // Subscribed feeds come straight from the cache, so there's nothing to merge.
+        let subscriptions = urls::parse_file(path)?;
+        return Ok(urls::load_feeds(&subscriptions, cache)?);
+    }
 
-    let backend = CrosstermBackend::new(io::stdout());
+    let feeds = if paths.is_empty() {
+        example::example_feeds()
+    } else {
+        let mut feeds = vec![];
+        for path in paths {
+            let mut feed =
+                parser::parse_file(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            feed.url = format!("file://{}", fs::canonicalize(path)?.display());
+            feeds.push(Rc::new(RefCell::new(feed)));
+        }
+        feeds
+    };
 
-    Terminal::new(backend)
+    for feed in &feeds {
+        cache.merge_into(&mut feed.borrow_mut())?;
+    }
+    Ok(feeds)
 }
 
-/// Return terminal to its original state.
-#[cfg(feature = "crossterm")]
-fn teardown_crossterm_terminal() -> Result<(), io::Error> {
-    execute!(io::stdout(), LeaveAlternateScreen)?;
-    disable_raw_mode()?;
+/// Add subscriptions from OPML file at `path` to the urls file, creating it if necessary.
+fn import_opml(path: &Path, options: &Options) -> Result<(), Box<dyn Error>> {
+    let url_file = match options.url_file {
+        Some(ref path) => path.clone(),
+        None => cli::default_url_file()
+            .ok_or("can't find the urls file: neither XDG_CONFIG_HOME nor HOME are set")?,
+    };
+
+    let imported = opml::import_file(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut subscriptions = if url_file.exists() {
+        urls::parse_file(&url_file)?
+    } else {
+        vec![]
+    };
+    let added = urls::merge(&mut subscriptions, imported);
+
+    if let Some(dir) = url_file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    urls::write_file(&url_file, &subscriptions)
+        .map_err(|e| format!("{}: {}", url_file.display(), e))?;
+    println!("Imported {} new feeds into {}", added, url_file.display());
     Ok(())
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    #[cfg(feature = "termion")]
-    let mut terminal = setup_termion_terminal()?;
-    #[cfg(feature = "crossterm")]
-    let mut terminal = setup_crossterm_terminal()?;
+/// Give the terminal to `command` and wait for it to finish, then take the terminal back.
+///
+/// The caller must make sure that nobody reads stdin while the command is running.
+fn run_external_command(
+    terminal: &mut Terminal<TerminalBackend>,
+    mut command: Command,
+) -> Result<(), io::Error> {
+    terminal::leave()?;
 
-    #[cfg(feature = "termion")]
-    let mut app: App<TermionBackend<AlternateScreen<RawTerminal<io::Stdout>>>> = App::new();
-    #[cfg(feature = "crossterm")]
-    let mut app: App<CrosstermBackend<io::Stdout>> = App::new();
+    // The user will see the program's own error messages, if any; there's nothing for us to add.
+    let _ = command.status();
 
-    let events = EventsSource::new();
-    loop {
-        terminal.draw(|mut frame| app.draw(&mut frame))?;
+    // Whatever the program left on the alternate screen, we don't know about it, so repaint
+    // everything.
+    resume(terminal)
+}
 
-        match events.next()? {
-            Event::Key(key) => match key {
-                Key::Ctrl(ref c) => match **c {
-                    Key::Char('v') => app.cycle_to_next_formaction(),
-                    Key::Char('g') => app.cycle_to_previous_formaction(),
+/// Did `suspend()` take the terminal back already? If so, the SIGCONT that continued us, which
+/// arrives as `Event::Continued` afterwards, needs no handling.
+static RESUMED: AtomicBool = AtomicBool::new(false);
 
-                    _ => app.handle_key(key),
-                },
+/// Restore the terminal and stop the process, the way it would've stopped if we didn't catch
+/// SIGTSTP. Once we're continued, take the terminal back.
+fn suspend(terminal: &mut Terminal<TerminalBackend>) -> Result<(), io::Error> {
+    terminal::leave()?;
 
-                _ => app.handle_key(key),
-            },
+    // This returns once someone sends us SIGCONT, e.g. when the user runs `fg`.
+    signal_hook::low_level::emulate_default_handler(libc::SIGTSTP)?;
 
-            Event::TerminalResized => {
-                // Do nothing. We'll redraw the UI on the next iteration anyway.
+    RESUMED.store(true, Ordering::SeqCst);
+    resume(terminal)
+}
+
+/// Set the terminal back up after we were stopped, and repaint everything.
+fn resume(terminal: &mut Terminal<TerminalBackend>) -> Result<(), io::Error> {
+    terminal::enter()?;
+    terminal.clear()
+}
+
+/// How long a stream of events can hold off the next frame. Events that arrive in a burst, e.g.
+/// keys that the user holds down or a series of resizes, are handled together and drawn once.
+///
+/// This is best-effort: a key that the input thread doesn't get to quickly enough is handled after
+/// the frame instead, which costs an extra frame but nothing else.
+const MAX_FRAME_DELAY: Duration = Duration::from_millis(50);
+
+/// Process events until the app wants to quit.
+fn run(
+    terminal: &mut Terminal<TerminalBackend>,
+    app: &mut App<TerminalBackend>,
+) -> Result<(), Box<dyn Error>> {
+    let mut events = EventsSource::new();
+    loop {
+        if app.needs_redraw() {
+            terminal.draw(|frame| app.draw(frame))?;
+        }
+
+        let event = events.next(app.next_deadline())?;
+        handle_event(event, terminal, app, &events)?;
+
+        let frame_due = Instant::now() + MAX_FRAME_DELAY;
+        while !app.should_quit && Instant::now() < frame_due {
+            match events.try_next() {
+                Some(event) => handle_event(event, terminal, app, &events)?,
+                None => break,
             }
         }
 
         if app.should_quit {
-            break;
+            return Ok(());
         }
     }
+}
+
+/// Pass `event` to the `app`, and do what the app asks for in return. Background reloads report
+/// back through `events`.
+fn handle_event(
+    event: Event,
+    terminal: &mut Terminal<TerminalBackend>,
+    app: &mut App<TerminalBackend>,
+    events: &EventsSource,
+) -> Result<(), Box<dyn Error>> {
+    match event {
+        Event::Key(key) => match key {
+            // Raw mode disables the signal, so we have to emulate it. This isn't an operation
+            // that could be rebound: the terminal itself would've done this, not us.
+            Key::Ctrl(ref c) if **c == Key::Char('z') => {
+                suspend(terminal)?;
+                app.mark_dirty();
+            }
+
+            _ => app.handle_key(key),
+        },
+
+        Event::TerminalResized => app.mark_dirty(),
+
+        Event::SuspendRequested => {
+            suspend(terminal)?;
+            app.mark_dirty();
+        }
+
+        // If we were stopped by SIGSTOP rather than SIGTSTP, we didn't get a chance to
+        // restore the terminal, and the shell probably messed with it in the meantime. After
+        // SIGTSTP, `suspend()` has set the terminal up again already.
+        Event::Continued => {
+            if !RESUMED.swap(false, Ordering::SeqCst) {
+                resume(terminal)?;
+                app.mark_dirty();
+            }
+        }
+
+        Event::Interrupted | Event::Terminated | Event::HungUp => app.should_quit = true,
+
+        Event::FeedFetched { .. } | Event::ReloadFinished | Event::Timer(_) => {
+            app.handle_event(&event)
+        }
+
+        Event::Tick => app.handle_tick(),
+    }
+
+    // The input thread is paused until the next call to `events.next()` or `events.try_next()`,
+    // so stdin is ours to give away.
+    if let Some(command) = app.take_external_command() {
+        run_external_command(terminal, command)?;
+        app.mark_dirty();
+    }
+
+    if let Some(urls) = app.take_reload_request() {
+        let reload_threads = app.config().settings.reload_threads;
+        reload::spawn(urls, reload_threads, events.sender());
+    }
+
+    Ok(())
+}
+
+fn main() {
+    // `main` returning an error would print it with `Debug`, which is fine for programmers, but
+    // users deserve the readable `Display` version.
+    if let Err(e) = try_main() {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Everything `main` does, except for reporting errors.
+fn try_main() -> Result<(), Box<dyn Error>> {
+    let options = Options::from_env()?;
+    if options.show_help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
+    if let Some(ref path) = options.import_opml {
+        return import_opml(path, &options);
+    }
+
+    // Feed files replace the subscriptions rather than add to them.
+    let url_file = if options.feed_files.is_empty() {
+        url_file(&options)
+    } else {
+        None
+    };
+    let cache = open_cache(&options)?;
+    let feeds = load_feeds(&options.feed_files, url_file.as_deref(), &cache)?;
+
+    if options.export_opml {
+        print!("{}", opml::export(&feeds));
+        return Ok(());
+    }
+
+    let config = load_config(&options)?;
+    let mut app: App<TerminalBackend> = App::new(feeds, Rc::new(cache), url_file, config);
+    // Without a home directory, the history is simply forgotten on exit.
+    if let Some(dir) = cli::default_history_dir() {
+        app.load_history(&dir)?;
+    }
+    let mut terminal = terminal::new_terminal()?;
+
+    let guard = TerminalGuard::new()?;
+    let result = run(&mut terminal, &mut app);
+
+    // Whatever made us stop, save what we can and give the terminal back to the user. After
+    // SIGHUP, there's no terminal to give back, so that might fail; the first error is the one
+    // worth reporting.
+    let save_result = app.save_state();
+    let teardown_result = guard.restore();
 
-    #[cfg(feature = "crossterm")]
-    teardown_crossterm_terminal()?;
+    result?;
+    teardown_result?;
+    save_result?;
 
     Ok(())
 }
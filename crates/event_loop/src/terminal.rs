@@ -0,0 +1,209 @@
+//! Switching the terminal between our UI and the "normal" mode other programs expect.
+
+use ratatui::Terminal;
+use std::{
+    io, mem, panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+#[cfg(feature = "crossterm")]
+use ratatui::backend::CrosstermBackend;
+#[cfg(feature = "termion")]
+use ratatui::backend::TermionBackend;
+
+#[cfg(feature = "crossterm")]
+use crossterm::{
+    cursor, execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+#[cfg(feature = "termion")]
+use std::io::Write;
+#[cfg(feature = "termion")]
+use termion::{
+    cursor,
+    raw::{IntoRawMode, RawTerminal},
+    screen::{ToAlternateScreen, ToMainScreen},
+};
+
+/// Backend through which the UI is drawn.
+#[cfg(feature = "termion")]
+pub type TerminalBackend = TermionBackend<io::Stdout>;
+
+/// Backend through which the UI is drawn.
+#[cfg(feature = "crossterm")]
+pub type TerminalBackend = CrosstermBackend<io::Stdout>;
+
+/// Are we in raw mode and on the alternate screen?
+///
+/// `leave()` uses this to avoid switching to the main screen twice, e.g. once in the panic hook
+/// and once more when `TerminalGuard` is dropped during unwinding. Switching twice would restore
+/// the cursor position saved on entering, and the panic message would be overwritten.
+static ENTERED: AtomicBool = AtomicBool::new(false);
+
+/// Messages of panics on threads other than the UI one, e.g. the reload workers.
+///
+/// The UI keeps running after such a panic, so the message can't be printed right away: it would
+/// land on the alternate screen and be wiped by the next redraw. It's printed once the terminal is
+/// restored instead.
+static DEFERRED_PANICS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Raw mode handle, present while the terminal is in raw mode.
+///
+/// Termion doesn't let us switch raw mode on and off without holding on to the `RawTerminal` that
+/// remembers the original terminal attributes. `TermionBackend` owns its writer and doesn't give
+/// it back, so we keep the handle here instead and let the backend write to a plain stdout.
+#[cfg(feature = "termion")]
+static RAW_MODE: Mutex<Option<RawTerminal<io::Stdout>>> = Mutex::new(None);
+
+/// Create a terminal that draws onto stdout.
+///
+/// This doesn't change the terminal's mode; call `enter()` for that.
+pub fn new_terminal() -> Result<Terminal<TerminalBackend>, io::Error> {
+    #[cfg(feature = "termion")]
+    let backend = TermionBackend::new(io::stdout());
+    #[cfg(feature = "crossterm")]
+    let backend = CrosstermBackend::new(io::stdout());
+
+    Terminal::new(backend)
+}
+
+/// Switch the terminal to raw mode and the alternate screen.
+///
+/// This can be called while we're already in raw mode, e.g. to undo whatever the shell did to the
+/// terminal while we were stopped.
+#[cfg(feature = "termion")]
+pub fn enter() -> Result<(), io::Error> {
+    let mut raw_mode = RAW_MODE.lock().unwrap_or_else(|e| e.into_inner());
+    match *raw_mode {
+        Some(ref raw_mode) => raw_mode.activate_raw_mode()?,
+        None => *raw_mode = Some(io::stdout().into_raw_mode()?),
+    }
+
+    let mut stdout = io::stdout();
+    write!(stdout, "{}", ToAlternateScreen)?;
+    stdout.flush()?;
+
+    ENTERED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Switch the terminal to raw mode and the alternate screen.
+///
+/// This can be called while we're already in raw mode, e.g. to undo whatever the shell did to the
+/// terminal while we were stopped.
+#[cfg(feature = "crossterm")]
+pub fn enter() -> Result<(), io::Error> {
+    // Crossterm does nothing if it thinks raw mode is already on, so we have to switch it off
+    // first to make sure it's actually re-applied.
+    disable_raw_mode()?;
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+
+    ENTERED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Return terminal to the main screen and the mode it was in before `enter()`.
+///
+/// Does nothing if the terminal is already there.
+#[cfg(feature = "termion")]
+pub fn leave() -> Result<(), io::Error> {
+    if !ENTERED.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let mut stdout = io::stdout();
+    write!(stdout, "{}{}", ToMainScreen, cursor::Show)?;
+    stdout.flush()?;
+
+    // Dropping the handle restores the original terminal attributes.
+    let _ = RAW_MODE.lock().unwrap_or_else(|e| e.into_inner()).take();
+
+    Ok(())
+}
+
+/// Return terminal to the main screen and the mode it was in before `enter()`.
+///
+/// Does nothing if the terminal is already there.
+#[cfg(feature = "crossterm")]
+pub fn leave() -> Result<(), io::Error> {
+    if !ENTERED.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    execute!(io::stdout(), LeaveAlternateScreen, cursor::Show)?;
+    disable_raw_mode()
+}
+
+/// Keeps the terminal in raw mode and on the alternate screen, and restores it when dropped.
+///
+/// While the guard is alive, a panic restores the terminal too, before the panic message is
+/// printed; otherwise the message would end up on the alternate screen and vanish.
+pub struct TerminalGuard {
+    /// Should the terminal be restored when the guard is dropped?
+    active: bool,
+}
+
+impl TerminalGuard {
+    /// Switch the terminal to raw mode and the alternate screen.
+    pub fn new() -> Result<TerminalGuard, io::Error> {
+        install_panic_hook();
+        enter()?;
+        Ok(TerminalGuard { active: true })
+    }
+
+    /// Restore the terminal, reporting any errors (which `Drop` has to swallow).
+    pub fn restore(mut self) -> Result<(), io::Error> {
+        self.active = false;
+        let result = leave();
+        report_deferred_panics();
+        result
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.active {
+            let _ = leave();
+            report_deferred_panics();
+        }
+    }
+}
+
+/// Make panics of the current thread, which is the one that draws the UI, restore the terminal
+/// before the hook that was installed before, e.g. the default one, reports them. That hook also
+/// decides whether to print a backtrace.
+///
+/// Panics of other threads, e.g. the reload workers, leave the terminal alone, since the UI keeps
+/// running. Their messages are printed once the terminal is restored.
+fn install_panic_hook() {
+    let previous = panic::take_hook();
+    let ui_thread = thread::current().id();
+    panic::set_hook(Box::new(move |info| {
+        let thread = thread::current();
+        if thread.id() != ui_thread {
+            let message = format!("thread '{}' {}", thread.name().unwrap_or("<unnamed>"), info);
+            DEFERRED_PANICS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(message);
+            return;
+        }
+
+        let _ = leave();
+        report_deferred_panics();
+        previous(info);
+    }));
+}
+
+/// Print the messages of panics that happened on other threads while the UI was on screen.
+fn report_deferred_panics() {
+    let messages = mem::take(&mut *DEFERRED_PANICS.lock().unwrap_or_else(|e| e.into_inner()));
+    for message in messages {
+        eprintln!("{}", message);
+    }
+}
@@ -1,11 +1,35 @@
 //! State and the behaviour of the application.
 
-use ratatui::{backend::Backend, terminal::Frame};
-use std::{cell::RefCell, rc::Rc};
+use ratatui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    terminal::Frame,
+    text::Span,
+    widgets::{Block, Paragraph},
+};
+use std::{
+    cell::RefCell,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use crate::event::Key;
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::event::{Event, Key};
+use crate::feed::Feed;
 use crate::feed_list::FeedList;
 use crate::form_action::FormAction;
+use crate::history::{Histories, History};
+use crate::keymap::{Dispatch, Operation, PendingKeys};
+use crate::theme::Element;
+use crate::timer::{Timer, TimerId, Timers};
+
+/// How long to wait for the next key of a key sequence before giving up on it.
+pub const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// State of our application.
 pub struct App<B: Backend> {
@@ -35,18 +59,170 @@ pub struct App<B: Backend> {
 
     /// The index of the formaction that gets rendered and processes all the input.
     current_formaction_index: usize,
+
+    /// External program (browser, pager, editor) that should be given the terminal.
+    ///
+    /// The event loop takes this out after each event, suspends the UI, runs the program to
+    /// completion, then restores the UI.
+    external_command: Option<Command>,
+
+    /// URLs of feeds that should be fetched in the background.
+    ///
+    /// The event loop takes this out after each event and starts the fetching, which reports back
+    /// with `Event::FeedFetched` and `Event::ReloadFinished`.
+    reload_request: Option<Vec<String>>,
+
+    /// Settings, key bindings and colors.
+    config: Rc<Config>,
+
+    /// Keys typed so far towards a multi-key binding, and the count typed before them.
+    pending_keys: PendingKeys,
+
+    /// Timers that are scheduled to go off.
+    timers: Timers,
+
+    /// The timer for the pending key sequence, if it's incomplete.
+    key_timer: Option<TimerId>,
+
+    /// The timer for the message on the current formaction's status bar, and when it goes off.
+    status_timer: Option<(TimerId, Instant)>,
+
+    /// The timer for automatic reloads, if they're on.
+    reload_timer: Option<TimerId>,
+
+    /// Lines entered into the command line and the search prompt.
+    histories: Histories,
+
+    /// The directory in which the histories are saved, if they were loaded from one.
+    history_dir: Option<PathBuf>,
+
+    /// Operations that a command asked for, to be performed once the formaction that ran the
+    /// command is done.
+    queued_operations: Option<Vec<Operation>>,
+
+    /// Did the config change since the formactions were last told about it?
+    config_changed: bool,
+
+    /// Did anything change since the screen was last drawn?
+    dirty: bool,
+
+    /// How many times the screen was drawn.
+    redraw_count: usize,
 }
 
 impl<B: Backend> App<B> {
-    /// Create new, empty app.
-    pub fn new() -> App<B> {
-        App {
+    /// Create new app, displaying a list of `feeds` and keeping their state in `cache`. If the
+    /// feeds came from a urls file, `url_file` should point to it. Formactions look and behave the
+    /// way `config` says.
+    pub fn new(
+        feeds: Vec<Rc<RefCell<Feed>>>,
+        cache: Rc<Cache>,
+        url_file: Option<PathBuf>,
+        config: Config,
+    ) -> App<B> {
+        let config = Rc::new(config);
+        let histories = Histories::new(config.settings.history_limit);
+        let feed_list = FeedList::new(feeds, cache, url_file, config.clone(), histories.clone());
+        let mut app = App {
             should_quit: false,
-            formaction_stack: vec![Rc::new(RefCell::new(FeedList::new()))],
+            formaction_stack: vec![Rc::new(RefCell::new(feed_list))],
             current_formaction_index: 0,
+            external_command: None,
+            reload_request: None,
+            config,
+            pending_keys: PendingKeys::default(),
+            timers: Timers::default(),
+            key_timer: None,
+            status_timer: None,
+            reload_timer: None,
+            histories,
+            history_dir: None,
+            queued_operations: None,
+            config_changed: false,
+            // Nothing was drawn yet.
+            dirty: true,
+            redraw_count: 0,
+        };
+        app.schedule_reload();
+        app
+    }
+
+    /// Change the config with `change`. Formactions pick up the result once the current one is
+    /// done handling the key. If `change` fails, the config stays as it was.
+    pub fn update_config<F, E>(&mut self, change: F) -> Result<(), E>
+    where
+        F: FnOnce(&mut Config) -> Result<(), E>,
+    {
+        let mut config = Config::clone(&self.config);
+        change(&mut config)?;
+        self.config = Rc::new(config);
+        self.config_changed = true;
+        Ok(())
+    }
+
+    /// Perform `operations`, one after another, once the current formaction is done handling the
+    /// key. Like with macros, each goes to whichever formaction is current by then.
+    pub fn exec(&mut self, operations: Vec<Operation>) {
+        self.queued_operations
+            .get_or_insert_with(Vec::new)
+            .extend(operations);
+    }
+
+    /// Do what the current formaction asked for while it was busy: tell every formaction about
+    /// the new config, and perform the queued operations.
+    fn catch_up(&mut self) {
+        if self.config_changed {
+            self.config_changed = false;
+            for formaction in self.formaction_stack.clone() {
+                formaction.borrow_mut().set_config(self.config.clone());
+            }
+            self.histories.set_limit(self.config.settings.history_limit);
+            self.schedule_reload();
+            self.dirty = true;
+        }
+        if let Some(operations) = self.queued_operations.take() {
+            if !self.formaction_stack.is_empty() {
+                self.with_current_formaction(|formaction, app| {
+                    app.dispatch(formaction, Dispatch::Macro(operations))
+                });
+            }
         }
     }
 
+    /// Lines entered into the command line and the search prompt.
+    pub fn histories(&self) -> Histories {
+        self.histories.clone()
+    }
+
+    /// Lines entered into the command line.
+    pub fn cmdline_history(&self) -> Rc<RefCell<History>> {
+        self.histories.cmdline.clone()
+    }
+
+    /// Lines entered into the search prompt.
+    pub fn search_history(&self) -> Rc<RefCell<History>> {
+        self.histories.search.clone()
+    }
+
+    /// Read the command line and search histories from `history.cmdline` and `history.search` in
+    /// `dir`. `save_state()` will write them back there.
+    pub fn load_history(&mut self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        self.history_dir = Some(dir.to_owned());
+        for (history, name) in self.histories.files() {
+            let path = dir.join(name);
+            history
+                .borrow_mut()
+                .load_file(&path)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Settings, key bindings and colors.
+    pub fn config(&self) -> Rc<Config> {
+        self.config.clone()
+    }
+
     /// Helper function for doing something with the current formaction.
     ///
     /// The closure passed to this method takes two parameters:
@@ -68,28 +244,280 @@ impl<B: Backend> App<B> {
 
     /// Handle key `key` pressed by the user.
     ///
+    /// The current formaction gets the first say; if it doesn't use the key, the key is added to
+    /// the pending ones, and once they make up a binding in the formaction's context, the bound
+    /// operation is performed.
+    ///
     /// # Panics
     ///
     /// Panics if the formaction stack is empty.
     pub fn handle_key(&mut self, key: Key) {
         self.with_current_formaction(|formaction, app| {
-            formaction.borrow_mut().handle_key(key, app);
+            let key = formaction.borrow_mut().handle_key(key, app);
+            app.catch_up();
+            let key = match key {
+                Some(key) => key,
+                None => {
+                    app.dirty = true;
+                    return;
+                }
+            };
+            let context = formaction.borrow().context();
+            let shown_keys = app.pending_keys.to_string();
+            let dispatch = app.pending_keys.push(key, &app.config.keymap, context);
+            if app.pending_keys.to_string() != shown_keys {
+                app.dirty = true;
+            }
+            if let Some(id) = app.key_timer.take() {
+                app.timers.cancel(id);
+            }
+            if app.pending_keys.is_incomplete_sequence() {
+                app.key_timer = Some(app.timers.once(Timer::KeySequence, KEY_SEQUENCE_TIMEOUT));
+            }
+            app.dispatch(formaction, dispatch);
         });
     }
 
+    /// Make `timer` go off at `deadline`, and then once in every `period`, if there's one. Timers
+    /// that the app doesn't handle itself are passed to every formaction as `Event::Timer`.
+    pub fn add_timer(
+        &mut self,
+        timer: Timer,
+        deadline: Instant,
+        period: Option<Duration>,
+    ) -> TimerId {
+        self.timers.add(timer, deadline, period)
+    }
+
+    /// Make `Timer::Tick(tick)` go off once in every `period`, starting a period from now, until
+    /// it's cancelled. Every formaction gets it as `Event::Timer`.
+    pub fn every(&mut self, period: Duration, tick: u32) -> TimerId {
+        self.timers.every(Timer::Tick(tick), period)
+    }
+
+    /// Stop the timer with `id` from going off.
+    pub fn cancel_timer(&mut self, id: TimerId) {
+        self.timers.cancel(id);
+    }
+
+    /// When the next timer goes off, if any is scheduled. The event loop should call
+    /// `handle_tick()` if nothing happens by then.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the formaction stack is empty.
+    pub fn next_deadline(&mut self) -> Option<Instant> {
+        self.schedule_status_expiry();
+        self.timers.next_deadline()
+    }
+
+    /// Let the timers that are due go off.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the formaction stack is empty.
+    pub fn handle_tick(&mut self) {
+        for (id, timer) in self.timers.expire(Instant::now()) {
+            match timer {
+                Timer::KeySequence => {
+                    if self.key_timer == Some(id) {
+                        self.key_timer = None;
+                        self.handle_key_timeout();
+                    }
+                }
+
+                // The message is gone as soon as the screen is redrawn.
+                Timer::StatusMessage => {
+                    if self
+                        .status_timer
+                        .is_some_and(|(status_id, _)| status_id == id)
+                    {
+                        self.status_timer = None;
+                        self.dirty = true;
+                    }
+                }
+
+                _ => self.handle_event(&Event::Timer(timer)),
+            }
+        }
+    }
+
+    /// Make sure there's a timer for the message on the current formaction's status bar, so
+    /// that the screen gets redrawn once it times out.
+    fn schedule_status_expiry(&mut self) {
+        let deadline = self.with_current_formaction(|formaction, _app| {
+            formaction.borrow_mut().status_bar().deadline()
+        });
+        if self.status_timer.map(|(_, scheduled)| scheduled) == deadline {
+            return;
+        }
+        if let Some((id, _)) = self.status_timer.take() {
+            self.timers.cancel(id);
+        }
+        if let Some(deadline) = deadline {
+            let id = self.timers.add(Timer::StatusMessage, deadline, None);
+            self.status_timer = Some((id, deadline));
+        }
+    }
+
+    /// Schedule automatic reloads if the config asks for them, every `reload-time` minutes.
+    /// Reloads scheduled before are cancelled.
+    fn schedule_reload(&mut self) {
+        if let Some(id) = self.reload_timer.take() {
+            self.timers.cancel(id);
+        }
+        let settings = &self.config.settings;
+        if settings.auto_reload {
+            let period = Duration::from_secs(settings.reload_time.saturating_mul(60));
+            self.reload_timer = Some(self.timers.every(Timer::Reload, period));
+        }
+    }
+
+    /// Give up waiting for the rest of the pending key sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the formaction stack is empty.
+    fn handle_key_timeout(&mut self) {
+        self.with_current_formaction(|formaction, app| {
+            let context = formaction.borrow().context();
+            let dispatch = app.pending_keys.time_out(&app.config.keymap, context);
+            // The keys disappear from the corner.
+            app.dirty = true;
+            app.dispatch(formaction, dispatch);
+        });
+    }
+
+    /// Perform the operations that the pending keys resulted in, if any.
+    fn dispatch(&mut self, formaction: Rc<RefCell<dyn FormAction<B>>>, dispatch: Dispatch) {
+        match dispatch {
+            Dispatch::Perform(operation, count) => self.perform(formaction, operation, count),
+
+            // Each operation goes to whichever formaction is current by then, so a macro can
+            // e.g. open a feed and then move around its items.
+            Dispatch::Macro(operations) => {
+                for operation in operations {
+                    if self.should_quit || self.formaction_stack.is_empty() {
+                        break;
+                    }
+                    self.with_current_formaction(|formaction, app| {
+                        app.perform(formaction, operation, None)
+                    });
+                }
+            }
+
+            Dispatch::Pending | Dispatch::Unbound => {}
+        }
+    }
+
+    /// Perform `operation` with `count`. Switching between formactions is done right here,
+    /// everything else is up to the `formaction`.
+    fn perform(
+        &mut self,
+        formaction: Rc<RefCell<dyn FormAction<B>>>,
+        operation: Operation,
+        count: Option<usize>,
+    ) {
+        // Operations that are bound to keys are there to change something, so it's not worth
+        // asking each formaction whether it did.
+        self.dirty = true;
+        match operation {
+            Operation::NextDialog => self.cycle_to_next_formaction(),
+            Operation::PrevDialog => self.cycle_to_previous_formaction(),
+            Operation::HardQuit => self.should_quit = true,
+            Operation::Cmdline => formaction.borrow_mut().status_bar().open_command_line(),
+            Operation::OpenSearch => formaction.borrow_mut().status_bar().open_search(),
+            _ => formaction
+                .borrow_mut()
+                .handle_operation(operation, count, self),
+        }
+    }
+
+    /// Pass `event` to every formaction, starting from the bottom of the stack.
+    pub fn handle_event(&mut self, event: &Event) {
+        for formaction in self.formaction_stack.clone() {
+            formaction.borrow_mut().handle_event(event, self);
+        }
+    }
+
     /// Draw the app to the screen `frame`.
     ///
     /// # Panics
     ///
     /// Panics if the formaction stack is empty.
     pub fn draw(&mut self, frame: &mut Frame) {
+        self.dirty = false;
+        self.redraw_count += 1;
+
+        // Dialogs draw on top of this, so whatever they leave unstyled gets the background.
+        let background = Block::default().style(self.config.theme.style(Element::Background));
+        frame.render_widget(background, frame.size());
+
         self.with_current_formaction(|formaction, _app| {
             formaction.borrow_mut().draw(frame);
         });
+
+        // Like Vim's `showcmd`, show what's typed so far in the bottom right corner.
+        if !self.pending_keys.is_empty() {
+            let pending = self.pending_keys.to_string();
+            let area = frame.size();
+            let width = (pending.chars().count().min(u16::MAX as usize) as u16).min(area.width);
+            let corner = Rect::new(
+                area.right() - width,
+                area.bottom().saturating_sub(1),
+                width,
+                area.height.min(1),
+            );
+            let paragraph = Paragraph::new(Span::raw(pending)).alignment(Alignment::Right);
+            frame.render_widget(paragraph, corner);
+        }
+    }
+
+    /// Ask for the screen to be redrawn, e.g. because the terminal was resized, or something
+    /// changed in the background.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Did anything change since the screen was last drawn? If not, there's no need to draw it.
+    pub fn needs_redraw(&self) -> bool {
+        self.dirty
+    }
+
+    /// How many times the app was drawn.
+    pub fn redraw_count(&self) -> usize {
+        self.redraw_count
+    }
+
+    /// Let every formaction persist its state, and save the histories if they were loaded. Should
+    /// be called before the program exits, no matter why it's exiting.
+    ///
+    /// A failure doesn't stop the rest from saving; the first error is returned.
+    pub fn save_state(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut result = Ok(());
+        for formaction in &self.formaction_stack {
+            let formaction_result = formaction.borrow_mut().save_state();
+            if result.is_ok() {
+                result = formaction_result;
+            }
+        }
+        if let Some(ref dir) = self.history_dir {
+            for (history, name) in self.histories.files() {
+                let path = dir.join(name);
+                let history_result = fs::create_dir_all(dir)
+                    .and_then(|_| history.borrow().write_file(&path))
+                    .map_err(|e| format!("{}: {}", path.display(), e).into());
+                if result.is_ok() {
+                    result = history_result;
+                }
+            }
+        }
+        result
     }
 
     /// Add given formaction to the top of the stack, i.e. make it the new current formaction.
     pub fn add_formaction(&mut self, formaction: Rc<RefCell<dyn FormAction<B>>>) {
+        self.dirty = true;
         self.formaction_stack.push(formaction);
         self.current_formaction_index = self.formaction_stack.len().saturating_sub(1);
     }
@@ -100,12 +528,40 @@ impl<B: Backend> App<B> {
     ///
     /// Panics if the formaction stack is empty.
     pub fn quit_current_formaction(&mut self) {
+        self.dirty = true;
         let _ = self.formaction_stack.remove(self.current_formaction_index);
         self.current_formaction_index = self.formaction_stack.len().saturating_sub(1);
     }
 
+    /// Ask the event loop to run `command` in the foreground, with full access to the terminal.
+    ///
+    /// If another command was already requested, it's replaced by this one.
+    pub fn run_external_command(&mut self, command: Command) {
+        self.external_command = Some(command);
+    }
+
+    /// Take the pending external command, if any.
+    pub fn take_external_command(&mut self) -> Option<Command> {
+        self.external_command.take()
+    }
+
+    /// Ask the event loop to fetch feeds with given `urls` in the background.
+    ///
+    /// If another reload was already requested but not yet started, these feeds are added to it.
+    pub fn reload_feeds(&mut self, urls: Vec<String>) {
+        self.reload_request
+            .get_or_insert_with(Vec::new)
+            .extend(urls);
+    }
+
+    /// Take the URLs of feeds that should be reloaded, if any.
+    pub fn take_reload_request(&mut self) -> Option<Vec<String>> {
+        self.reload_request.take()
+    }
+
     /// Switch to the next formaction in the list, wrapping to the first one if the end is reached.
     pub fn cycle_to_next_formaction(&mut self) {
+        self.dirty = true;
         self.current_formaction_index =
             (self.current_formaction_index + 1) % self.formaction_stack.len();
     }
@@ -113,10 +569,11 @@ impl<B: Backend> App<B> {
     /// Switch to the previous formaction in the list, wrapping to the last one if the beginning is
     /// reached.
     pub fn cycle_to_previous_formaction(&mut self) {
+        self.dirty = true;
         if self.current_formaction_index == 0 {
             self.current_formaction_index = self.formaction_stack.len() - 1;
         } else {
-            self.current_formaction_index = self.current_formaction_index - 1;
+            self.current_formaction_index -= 1;
         }
     }
 }
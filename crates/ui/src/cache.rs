@@ -0,0 +1,213 @@
+//! On-disk storage for feeds, items and their read/unread state.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::{cell::RefCell, cmp::Reverse, collections::HashSet, path::Path, rc::Rc};
+
+use crate::feed::{Enclosure, Feed, Item};
+
+/// SQLite database holding feeds and their items.
+///
+/// The layout resembles Newsboat's `cache.db`, but it's not compatible with it, so don't point
+/// this at Newsboat's own cache.
+pub struct Cache {
+    connection: Connection,
+}
+
+impl Cache {
+    /// Open the cache stored in the file at `path`, creating it if it doesn't exist.
+    pub fn open(path: &Path) -> rusqlite::Result<Cache> {
+        Cache::with_connection(Connection::open(path)?)
+    }
+
+    /// Create a cache that only lives in memory and is gone once dropped.
+    pub fn in_memory() -> rusqlite::Result<Cache> {
+        Cache::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> rusqlite::Result<Cache> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS rss_feed (
+                rssurl TEXT PRIMARY KEY NOT NULL,
+                url TEXT NOT NULL,
+                title TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS rss_item (
+                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                guid TEXT NOT NULL,
+                title TEXT NOT NULL,
+                author TEXT NOT NULL,
+                url TEXT NOT NULL,
+                feedurl TEXT NOT NULL,
+                pubDate INTEGER,
+                content TEXT NOT NULL,
+                unread INTEGER NOT NULL,
+                flags TEXT NOT NULL,
+                enclosure_url TEXT,
+                enclosure_type TEXT,
+                enclosure_length INTEGER,
+                UNIQUE (feedurl, guid)
+            );
+            CREATE INDEX IF NOT EXISTS idx_feedurl ON rss_item (feedurl);",
+        )?;
+        Ok(Cache { connection })
+    }
+
+    /// Load the feed with given `url`, along with all its items, newest first.
+    ///
+    /// Returns `None` if the feed isn't in the cache.
+    pub fn load_feed(&self, url: &str) -> rusqlite::Result<Option<Feed>> {
+        let feed = self
+            .connection
+            .query_row(
+                "SELECT url, title FROM rss_feed WHERE rssurl = ?1",
+                params![url],
+                |row| {
+                    Ok(Feed {
+                        title: row.get(1)?,
+                        url: url.to_string(),
+                        link: row.get(0)?,
+                        items: vec![],
+                        ..Feed::default()
+                    })
+                },
+            )
+            .optional()?;
+
+        let mut feed = match feed {
+            Some(feed) => feed,
+            None => return Ok(None),
+        };
+
+        let mut statement = self.connection.prepare(
+            "SELECT guid, title, author, url, pubDate, content, unread, flags,
+                    enclosure_url, enclosure_type, enclosure_length
+            FROM rss_item
+            WHERE feedurl = ?1
+            ORDER BY pubDate DESC, id ASC",
+        )?;
+        feed.items = statement
+            .query_map(params![url], item_from_row)?
+            .map(|item| item.map(|item| Rc::new(RefCell::new(item))))
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(Some(feed))
+    }
+
+    /// Bring `feed` in line with the cache: items that were seen before get their read state and
+    /// flags back, and items that are only in the cache are added to the feed.
+    ///
+    /// Nothing is written to the cache; use `save_feed` for that.
+    pub fn merge_into(&self, feed: &mut Feed) -> rusqlite::Result<()> {
+        let cached = match self.load_feed(&feed.url)? {
+            Some(cached) => cached,
+            None => return Ok(()),
+        };
+
+        let mut seen = HashSet::new();
+        for item in &feed.items {
+            let mut item = item.borrow_mut();
+            if let Some(cached_item) = cached.items.iter().find(|c| c.borrow().guid == item.guid) {
+                let cached_item = cached_item.borrow();
+                item.unread = cached_item.unread;
+                item.flags = cached_item.flags.clone();
+            }
+            seen.insert(item.guid.clone());
+        }
+
+        for cached_item in cached.items {
+            if !seen.contains(&cached_item.borrow().guid) {
+                feed.items.push(cached_item);
+            }
+        }
+        feed.items.sort_by_key(|item| Reverse(item.borrow().date));
+
+        Ok(())
+    }
+
+    /// Write `feed` and all its items into the cache. The feed and items that are cached already
+    /// are updated; cached items that aren't in `feed` any more are kept.
+    pub fn save_feed(&self, feed: &Feed) -> rusqlite::Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+
+        transaction.execute(
+            "INSERT INTO rss_feed (rssurl, url, title) VALUES (?1, ?2, ?3)
+            ON CONFLICT (rssurl) DO UPDATE SET url = excluded.url, title = excluded.title",
+            params![feed.url, feed.link, feed.title],
+        )?;
+        for item in &feed.items {
+            save_item(&transaction, &feed.url, &item.borrow())?;
+        }
+
+        transaction.commit()
+    }
+
+    /// Write `item` of the feed with URL `feed_url` into the cache.
+    pub fn save_item(&self, feed_url: &str, item: &Item) -> rusqlite::Result<()> {
+        save_item(&self.connection, feed_url, item)
+    }
+}
+
+/// Insert `item` into the cache, or update it if it's already there.
+fn save_item(connection: &Connection, feed_url: &str, item: &Item) -> rusqlite::Result<()> {
+    let enclosure = item.enclosure.as_ref();
+    connection.execute(
+        "INSERT INTO rss_item (guid, title, author, url, feedurl, pubDate, content, unread, flags,
+                               enclosure_url, enclosure_type, enclosure_length)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+        ON CONFLICT (feedurl, guid) DO UPDATE SET
+            title = excluded.title,
+            author = excluded.author,
+            url = excluded.url,
+            pubDate = excluded.pubDate,
+            content = excluded.content,
+            unread = excluded.unread,
+            flags = excluded.flags,
+            enclosure_url = excluded.enclosure_url,
+            enclosure_type = excluded.enclosure_type,
+            enclosure_length = excluded.enclosure_length",
+        params![
+            item.guid,
+            item.title,
+            item.author,
+            item.url,
+            feed_url,
+            item.date.map(|date| date.timestamp()),
+            item.content,
+            item.unread,
+            item.flags,
+            enclosure.map(|e| &e.url),
+            enclosure.map(|e| &e.mime_type),
+            enclosure.and_then(|e| e.length),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Build an item out of a row selected by `Cache::load_feed`.
+fn item_from_row(row: &Row) -> rusqlite::Result<Item> {
+    let enclosure = row
+        .get::<_, Option<String>>(8)?
+        .map(|url| -> rusqlite::Result<Enclosure> {
+            Ok(Enclosure {
+                url,
+                mime_type: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
+                length: row.get(10)?,
+            })
+        })
+        .transpose()?;
+
+    Ok(Item {
+        guid: row.get(0)?,
+        title: row.get(1)?,
+        author: row.get(2)?,
+        url: row.get(3)?,
+        date: row
+            .get::<_, Option<i64>>(4)?
+            .and_then(|timestamp| DateTime::<Utc>::from_timestamp(timestamp, 0)),
+        content: row.get(5)?,
+        unread: row.get(6)?,
+        flags: row.get(7)?,
+        enclosure,
+    })
+}
@@ -0,0 +1,259 @@
+//! Commands entered on the command line, e.g. `set show-read-feeds no`.
+//!
+//! A command line is split into words the same way as the lines of the config file, so arguments
+//! with spaces have to be quoted: `source "my config"`. A line that's just a number, like `12`,
+//! is short for `goto 12`.
+//!
+//! Commands that work the same in every dialog (`quit`, `set`, `source`, `dumpconfig`, `exec`,
+//! `open`) are run by `run`; the rest are up to the dialog's `FormAction::run_command`.
+
+use ratatui::backend::Backend;
+use std::{error::Error, fs, path::Path};
+
+use crate::app::App;
+use crate::completion::Argument;
+use crate::form_action::FormAction;
+use crate::keymap::Operation;
+use crate::stateful_list::StatefulList;
+use crate::tokenizer::tokenize;
+
+/// What running a command came to: a message for the status line, if there's anything to say, or
+/// an error.
+pub type Outcome = Result<Option<String>, Box<dyn Error>>;
+
+/// A command entered on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    /// Name of the command.
+    pub name: String,
+
+    /// Arguments of the command, unquoted. There are as many as the command takes.
+    pub arguments: Vec<String>,
+}
+
+/// What a command is called and what it takes.
+#[derive(Debug, Clone, Copy)]
+pub struct Spec {
+    /// Name of the command.
+    pub name: &'static str,
+
+    /// The arguments, as shown in the usage message.
+    pub usage: &'static str,
+
+    /// The least number of arguments the command takes.
+    pub min_arguments: usize,
+
+    /// The most arguments the command takes, if there's a limit.
+    pub max_arguments: Option<usize>,
+
+    /// What the arguments are, for completion.
+    pub argument: Argument,
+}
+
+/// All the commands there are.
+pub const COMMANDS: &[Spec] = &[
+    Spec {
+        name: "quit",
+        usage: "",
+        min_arguments: 0,
+        max_arguments: Some(0),
+        argument: Argument::Nothing,
+    },
+    Spec {
+        name: "set",
+        usage: "NAME [VALUE]",
+        min_arguments: 1,
+        max_arguments: Some(2),
+        argument: Argument::Setting,
+    },
+    Spec {
+        name: "source",
+        usage: "FILE...",
+        min_arguments: 1,
+        max_arguments: None,
+        argument: Argument::Path,
+    },
+    Spec {
+        name: "dumpconfig",
+        usage: "FILE",
+        min_arguments: 1,
+        max_arguments: Some(1),
+        argument: Argument::Path,
+    },
+    Spec {
+        name: "exec",
+        usage: "OPERATION...",
+        min_arguments: 1,
+        max_arguments: None,
+        argument: Argument::Operation,
+    },
+    Spec {
+        name: "open",
+        usage: "",
+        min_arguments: 0,
+        max_arguments: Some(0),
+        argument: Argument::Nothing,
+    },
+    Spec {
+        name: "goto",
+        usage: "NUMBER|TEXT...",
+        min_arguments: 1,
+        max_arguments: None,
+        argument: Argument::Nothing,
+    },
+    Spec {
+        name: "search",
+        usage: "TEXT...",
+        min_arguments: 1,
+        max_arguments: None,
+        argument: Argument::Nothing,
+    },
+    Spec {
+        name: "tag",
+        usage: "[TAG]",
+        min_arguments: 0,
+        max_arguments: Some(1),
+        argument: Argument::Tag,
+    },
+    Spec {
+        name: "save",
+        usage: "FILE",
+        min_arguments: 1,
+        max_arguments: Some(1),
+        argument: Argument::Path,
+    },
+    Spec {
+        name: "import",
+        usage: "FILE",
+        min_arguments: 1,
+        max_arguments: Some(1),
+        argument: Argument::Path,
+    },
+    Spec {
+        name: "export",
+        usage: "FILE",
+        min_arguments: 1,
+        max_arguments: Some(1),
+        argument: Argument::Path,
+    },
+];
+
+/// Parse a command `line`. An empty line isn't a command, but it isn't an error either.
+///
+/// Fails if the command doesn't exist, or doesn't take that many arguments.
+pub fn parse(line: &str) -> Result<Option<Command>, String> {
+    let mut words = tokenize(line)?;
+    if words.is_empty() {
+        return Ok(None);
+    }
+    let mut name = words.remove(0);
+    if name.parse::<usize>().is_ok() && words.is_empty() {
+        words.push(name);
+        name = String::from("goto");
+    }
+
+    let spec = COMMANDS
+        .iter()
+        .find(|spec| spec.name == name)
+        .ok_or_else(|| format!("not a command: {}", name))?;
+    let too_many = spec.max_arguments.is_some_and(|max| words.len() > max);
+    if words.len() < spec.min_arguments || too_many {
+        return Err(format!("usage: {} {}", spec.name, spec.usage)
+            .trim_end()
+            .to_string());
+    }
+
+    Ok(Some(Command {
+        name,
+        arguments: words,
+    }))
+}
+
+/// Run the command `line` that the user entered in `formaction`.
+pub fn run<B, F>(line: &str, formaction: &mut F, app: &mut App<B>) -> Outcome
+where
+    B: Backend,
+    F: FormAction<B> + ?Sized,
+{
+    let command = match parse(line)? {
+        Some(command) => command,
+        None => return Ok(None),
+    };
+
+    match (command.name.as_str(), command.arguments.as_slice()) {
+        ("quit", _) => app.exec(vec![Operation::Quit]),
+        ("open", _) => app.exec(vec![Operation::Open]),
+
+        ("exec", operations) => {
+            let operations = operations
+                .iter()
+                .map(|name| {
+                    Operation::from_name(name)
+                        .ok_or_else(|| format!("unknown operation `{}`", name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            app.exec(operations);
+        }
+
+        ("set", [name]) => {
+            let value = app
+                .config()
+                .settings
+                .get(name)
+                .ok_or_else(|| format!("unknown setting `{}`", name))?;
+            return Ok(Some(format!("{} = {}", name, value)));
+        }
+        ("set", arguments) => {
+            app.update_config(|config| config.apply_command("set", arguments))?;
+        }
+
+        ("source", paths) => {
+            for path in paths {
+                app.update_config(|config| config.load_file(Path::new(path)))?;
+            }
+        }
+
+        ("dumpconfig", [path]) => {
+            fs::write(path, app.config().dump()).map_err(|e| format!("{}: {}", path, e))?;
+            return Ok(Some(format!("Saved configuration to {}", path)));
+        }
+
+        _ => return formaction.run_command(&command, app),
+    }
+    Ok(None)
+}
+
+/// Carry out `goto` in a `list` of things called `what`: select the entry with the number in
+/// `arguments`, or the next one whose `title` contains their text, ignoring case.
+pub fn goto<T, F>(list: &mut StatefulList<T>, arguments: &[String], what: &str, title: F) -> Outcome
+where
+    F: Fn(&T) -> String,
+{
+    let text = arguments.join(" ");
+    // Entries are numbered from 1 on screen.
+    if let Ok(number) = text.parse::<usize>() {
+        list.select_nearest(number.saturating_sub(1));
+        return Ok(None);
+    }
+
+    search(list, arguments, what, title)
+}
+
+/// Carry out `search` in a `list` of things called `what`: select the next entry whose `title`
+/// contains the text in `arguments`, ignoring case. Unlike with `goto`, numbers are just text.
+pub fn search<T, F>(
+    list: &mut StatefulList<T>,
+    arguments: &[String],
+    what: &str,
+    title: F,
+) -> Outcome
+where
+    F: Fn(&T) -> String,
+{
+    let text = arguments.join(" ");
+    let needle = text.to_lowercase();
+    if !list.select_next_matching(|entry| title(entry).to_lowercase().contains(&needle)) {
+        return Err(format!("no {} matches `{}`", what, text).into());
+    }
+    Ok(None)
+}
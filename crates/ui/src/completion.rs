@@ -0,0 +1,110 @@
+//! Completion of commands and their arguments on the command line.
+
+use std::fs;
+
+use crate::command::COMMANDS;
+use crate::config::{Settings, SETTING_NAMES};
+use crate::keymap::Operation;
+use crate::tokenizer::quote;
+use crate::widgets::text_line::Completions;
+
+/// What a command takes as its arguments, as far as completion is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Argument {
+    /// Nothing that can be completed.
+    Nothing,
+
+    /// Paths to files or directories.
+    Path,
+
+    /// A setting's name, then its value.
+    Setting,
+
+    /// Tags of the feeds.
+    Tag,
+
+    /// Names of operations.
+    Operation,
+}
+
+/// Completions for the last word of `line`, which is a command line typed up to the cursor.
+///
+/// The first word is completed from the names of the commands; the rest depend on what the
+/// command takes. Setting names and values come from `settings`, tags from `tags`.
+pub fn complete(line: &str, settings: &Settings, tags: &[String]) -> Completions {
+    let start = line
+        .char_indices()
+        .rfind(|(_, c)| c.is_whitespace())
+        .map_or(0, |(index, c)| index + c.len_utf8());
+    let word = &line[start..];
+    let previous = line[..start].split_whitespace().collect::<Vec<_>>();
+
+    let candidates = match previous.as_slice() {
+        [] => COMMANDS.iter().map(|spec| spec.name.to_string()).collect(),
+
+        [command, arguments @ ..] => {
+            let argument = COMMANDS
+                .iter()
+                .find(|spec| spec.name == *command)
+                .map_or(Argument::Nothing, |spec| spec.argument);
+            match (argument, arguments) {
+                (Argument::Path, _) => paths(word),
+                (Argument::Setting, []) => {
+                    SETTING_NAMES.iter().map(|name| name.to_string()).collect()
+                }
+                (Argument::Setting, [name]) => setting_values(settings, name),
+                (Argument::Tag, []) => tags.to_vec(),
+                (Argument::Operation, _) => Operation::names().map(String::from).collect(),
+                _ => vec![],
+            }
+        }
+    };
+
+    Completions {
+        start,
+        candidates: candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .collect(),
+    }
+}
+
+/// Values that the setting called `name` can be set to: yes and no for yes/no settings, or the
+/// current value for the rest.
+fn setting_values(settings: &Settings, name: &str) -> Vec<String> {
+    match settings.get(name) {
+        Some(value) if value == "yes" || value == "no" => {
+            vec![String::from("yes"), String::from("no")]
+        }
+        Some(value) => vec![quote(&value)],
+        None => vec![],
+    }
+}
+
+/// Paths of files and directories that start with `word`, sorted. Directories end with a slash, so
+/// that completion can go on inside them. Like in shells, hidden files only turn up if `word` asks
+/// for them.
+fn paths(word: &str) -> Vec<String> {
+    let (dir, prefix) = match word.rfind('/') {
+        Some(slash) => word.split_at(slash + 1),
+        None => ("", word),
+    };
+    let entries = match fs::read_dir(if dir.is_empty() { "." } else { dir }) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if name.starts_with('.') && !prefix.starts_with('.') {
+                return None;
+            }
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir, name, slash))
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
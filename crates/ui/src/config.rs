@@ -0,0 +1,496 @@
+//! Parsing the config file.
+//!
+//! Each line is a command followed by its arguments, e.g.:
+//!
+//! ```text
+//! # Comments start with a hash.
+//! set browser "firefox --new-tab %u"
+//! set show-read-feeds no
+//! set reload-threads `nproc`
+//! include colors
+//! color listfocus black yellow bold
+//! bind-key j down
+//! bind-key k up articlelist
+//! bind-key gg home
+//! unbind-key R feedlist
+//! macro b open-in-browser ; \
+//!     quit
+//! ```
+//!
+//! - `set NAME VALUE` changes a setting, see `Settings`;
+//! - `include PATH` reads commands from another file. A relative path is relative to the directory
+//!   of the file that includes it;
+//! - `color ELEMENT FOREGROUND BACKGROUND [ATTRIBUTE]...` sets how a part of the UI looks, see
+//!   `theme`. True colors have to be quoted, like `"#ffd700"`, or they'd start a comment;
+//! - `bind-key KEY OPERATION [DIALOG]` makes a key perform an operation in the given dialog, or in
+//!   all of them if none is given. `unbind-key KEY [DIALOG]` makes it do nothing, and
+//!   `unbind-key -a` removes all bindings, including the default ones;
+//! - `macro KEY OPERATION [; OPERATION]...` makes the macro prefix (`,` by default) followed by
+//!   the key perform all the operations, one after another.
+//!
+//! Text in backticks is replaced with the output of the shell command it contains. A backslash at
+//! the end of a line joins the next line to it.
+
+use chrono::format::{Item, StrftimeItems};
+use ratatui::style::{Color, Modifier, Style};
+use std::{
+    env, error, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::event::Key;
+use crate::history;
+use crate::keymap::{self, Context, KeyMap, Operation};
+use crate::reload;
+use crate::theme::{self, Element, Theme};
+use crate::tokenizer::{quote, tokenize_with_commands};
+
+/// Everything that can be changed in the config file.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Values of `set` commands.
+    pub settings: Settings,
+
+    /// What keys do in each dialog.
+    pub keymap: KeyMap,
+
+    /// Looks of the UI elements.
+    pub theme: Theme,
+}
+
+/// Settings changed with `set NAME VALUE`. Field docs give the names.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// `browser`: command that opens links. `%u` in it is replaced with the link; if there's no
+    /// `%u`, the link is appended. If unset, `$BROWSER` is used, or `lynx` as a last resort.
+    pub browser: Option<String>,
+
+    /// `show-read-feeds`: should feeds without unread items be listed?
+    pub show_read_feeds: bool,
+
+    /// `show-read-articles`: should read items be listed?
+    pub show_read_articles: bool,
+
+    /// `show-keymap-hint`: should dialogs show the keys for the most useful operations?
+    pub show_keymap_hint: bool,
+
+    /// `reload-threads`: how many feeds are fetched at once.
+    pub reload_threads: usize,
+
+    /// `auto-reload`: should all the feeds be reloaded every `reload-time` minutes?
+    pub auto_reload: bool,
+
+    /// `reload-time`: how many minutes pass between automatic reloads.
+    pub reload_time: u64,
+
+    /// `history-limit`: how many command lines and searches are remembered. Zero means none.
+    pub history_limit: usize,
+
+    /// `feedlist-format`: how rows of the feed list look, see `format::feed_row`.
+    pub feedlist_format: String,
+
+    /// `articlelist-format`: how rows of the item list look, see `format::item_row`.
+    pub articlelist_format: String,
+
+    /// `datetime-format`: how dates in the item list look, in `strftime` syntax.
+    pub datetime_format: String,
+
+    /// `feedlist-title-format`: title of the feed list, see `format::feedlist_title`.
+    pub feedlist_title_format: String,
+
+    /// `articlelist-title-format`: title of the item list, see `format::articlelist_title`.
+    pub articlelist_title_format: String,
+
+    /// `itemview-title-format`: title of the article view, see `format::itemview_title`.
+    pub itemview_title_format: String,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            browser: None,
+            show_read_feeds: true,
+            show_read_articles: true,
+            show_keymap_hint: true,
+            reload_threads: reload::DEFAULT_THREADS,
+            auto_reload: false,
+            reload_time: 60,
+            history_limit: history::DEFAULT_LIMIT,
+            feedlist_format: String::from("%4i %n %9u %t"),
+            articlelist_format: String::from("%4i %f %D %6L  %t"),
+            datetime_format: String::from("%b %d"),
+            feedlist_title_format: String::from("%N %V - Your Feeds (%u unread, %t total)"),
+            articlelist_title_format: String::from(
+                "%N %V - Articles in feed '%T' (%u unread, %t total)",
+            ),
+            itemview_title_format: String::from("%N %V - Article '%T' (%u unread, %t total)"),
+        }
+    }
+}
+
+/// Names of the settings, as `set` knows them.
+pub const SETTING_NAMES: &[&str] = &[
+    "browser",
+    "show-read-feeds",
+    "show-read-articles",
+    "show-keymap-hint",
+    "reload-threads",
+    "auto-reload",
+    "reload-time",
+    "history-limit",
+    "feedlist-format",
+    "articlelist-format",
+    "datetime-format",
+    "feedlist-title-format",
+    "articlelist-title-format",
+    "itemview-title-format",
+];
+
+impl Settings {
+    /// Value of the setting called `name`, the way `set` would take it. Yes/no settings are `yes`
+    /// or `no`, and an unset `browser` is empty.
+    pub fn get(&self, name: &str) -> Option<String> {
+        let yes_no = |value: bool| String::from(if value { "yes" } else { "no" });
+        Some(match name {
+            "browser" => self.browser.clone().unwrap_or_default(),
+            "show-read-feeds" => yes_no(self.show_read_feeds),
+            "show-read-articles" => yes_no(self.show_read_articles),
+            "show-keymap-hint" => yes_no(self.show_keymap_hint),
+            "reload-threads" => self.reload_threads.to_string(),
+            "auto-reload" => yes_no(self.auto_reload),
+            "reload-time" => self.reload_time.to_string(),
+            "history-limit" => self.history_limit.to_string(),
+            "feedlist-format" => self.feedlist_format.clone(),
+            "articlelist-format" => self.articlelist_format.clone(),
+            "datetime-format" => self.datetime_format.clone(),
+            "feedlist-title-format" => self.feedlist_title_format.clone(),
+            "articlelist-title-format" => self.articlelist_title_format.clone(),
+            "itemview-title-format" => self.itemview_title_format.clone(),
+            _ => return None,
+        })
+    }
+
+    /// Set the setting called `name` to `value`.
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "browser" => self.browser = Some(value.to_string()),
+            "show-read-feeds" => self.show_read_feeds = parse_bool(name, value)?,
+            "show-read-articles" => self.show_read_articles = parse_bool(name, value)?,
+            "show-keymap-hint" => self.show_keymap_hint = parse_bool(name, value)?,
+            "reload-threads" => {
+                self.reload_threads = value
+                    .parse()
+                    .ok()
+                    .filter(|threads| *threads > 0)
+                    .ok_or_else(|| {
+                        format!("`{}` should be a positive number, not `{}`", name, value)
+                    })?;
+            }
+            "auto-reload" => self.auto_reload = parse_bool(name, value)?,
+            "reload-time" => {
+                self.reload_time = value
+                    .parse()
+                    .ok()
+                    .filter(|minutes| *minutes > 0)
+                    .ok_or_else(|| {
+                        format!("`{}` should be a positive number, not `{}`", name, value)
+                    })?;
+            }
+            "history-limit" => {
+                self.history_limit = value
+                    .parse()
+                    .map_err(|_| format!("`{}` should be a number, not `{}`", name, value))?;
+            }
+            "feedlist-format" => self.feedlist_format = value.to_string(),
+            "articlelist-format" => self.articlelist_format = value.to_string(),
+            "datetime-format" => self.datetime_format = parse_datetime_format(name, value)?,
+            "feedlist-title-format" => self.feedlist_title_format = value.to_string(),
+            "articlelist-title-format" => self.articlelist_title_format = value.to_string(),
+            "itemview-title-format" => self.itemview_title_format = value.to_string(),
+            _ => return Err(format!("unknown setting `{}`", name)),
+        }
+        Ok(())
+    }
+}
+
+/// Parse `value` of a yes/no setting called `name`.
+fn parse_bool(name: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "yes" | "true" => Ok(true),
+        "no" | "false" => Ok(false),
+        _ => Err(format!("`{}` should be yes or no, not `{}`", name, value)),
+    }
+}
+
+/// Check that `value` of a date format setting called `name` is something `strftime` understands.
+fn parse_datetime_format(name: &str, value: &str) -> Result<String, String> {
+    // chrono only finds out that a format is broken when it's used, and then it panics.
+    if StrftimeItems::new(value).any(|item| item == Item::Error) {
+        return Err(format!("`{}` isn't a valid date format: `{}`", name, value));
+    }
+    Ok(value.to_string())
+}
+
+/// Reasons why the config file couldn't be loaded.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file couldn't be read.
+    Io(PathBuf, io::Error),
+
+    /// A line of the file doesn't make sense.
+    Malformed {
+        /// The file in which the line is.
+        path: PathBuf,
+
+        /// Line number, starting from 1. For lines continued with a backslash, it's the first one.
+        line: usize,
+
+        /// What's wrong with the line.
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => {
+                write!(
+                    f,
+                    "{}: couldn't read the config file: {}",
+                    path.display(),
+                    e
+                )
+            }
+            ConfigError::Malformed {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ConfigError::Io(_, e) => Some(e),
+            ConfigError::Malformed { .. } => None,
+        }
+    }
+}
+
+impl Config {
+    /// Apply the commands from the config file at `path`.
+    pub fn load_file(&mut self, path: &Path) -> Result<(), ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_owned(), e))?;
+        self.load_str(&text, path)
+    }
+
+    /// Apply the commands from the contents of a config file. `path` is used in error messages,
+    /// and to find the files it includes.
+    ///
+    /// Commands before the malformed line, if any, are still applied.
+    pub fn load_str(&mut self, text: &str, path: &Path) -> Result<(), ConfigError> {
+        self.load_included(text, path, &mut vec![])
+    }
+
+    /// Apply the commands from `text` of the file at `path`, which was included by the files in
+    /// `including`, innermost last.
+    fn load_included(
+        &mut self,
+        text: &str,
+        path: &Path,
+        including: &mut Vec<PathBuf>,
+    ) -> Result<(), ConfigError> {
+        including.push(path.to_owned());
+        for (line, text) in logical_lines(text) {
+            let malformed = |message| ConfigError::Malformed {
+                path: path.to_owned(),
+                line,
+                message,
+            };
+
+            let tokens = tokenize_with_commands(&text).map_err(malformed)?;
+            match tokens.split_first() {
+                None => {}
+
+                Some((command, arguments)) if command == "include" => {
+                    let included = match arguments {
+                        [included] => resolve_include(included, path),
+                        _ => return Err(malformed(String::from("usage: include PATH"))),
+                    };
+                    if including.contains(&included) {
+                        return Err(malformed(format!("{} includes itself", included.display())));
+                    }
+                    let included_text = fs::read_to_string(&included).map_err(|e| {
+                        malformed(format!("couldn't include {}: {}", included.display(), e))
+                    })?;
+                    self.load_included(&included_text, &included, including)?;
+                }
+
+                Some((command, arguments)) => {
+                    self.apply_command(command, arguments).map_err(malformed)?
+                }
+            }
+        }
+        including.pop();
+        Ok(())
+    }
+
+    /// The config file that recreates this config on top of the defaults: every setting, every
+    /// color, and all the key bindings and macros.
+    pub fn dump(&self) -> String {
+        let mut lines = vec![];
+        for name in SETTING_NAMES {
+            // An empty browser would be different from an unset one.
+            if *name == "browser" && self.settings.browser.is_none() {
+                continue;
+            }
+            if let Some(value) = self.settings.get(name) {
+                lines.push(format!("set {} {}", name, quote(&value)));
+            }
+        }
+        lines.extend(self.theme.dump());
+        // The defaults would linger otherwise.
+        lines.push(String::from("unbind-key -a"));
+        lines.extend(self.keymap.dump());
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+        text
+    }
+
+    /// Apply `command` with its `arguments`, like a line of the config file. `include` only makes
+    /// sense in a file, so it's taken care of by the file's loader.
+    pub fn apply_command(&mut self, command: &str, arguments: &[String]) -> Result<(), String> {
+        match (command, arguments) {
+            ("set", [name, value]) => self.settings.set(name, value)?,
+            ("set", _) => return Err(String::from("usage: set NAME VALUE")),
+
+            ("color", [element, foreground, background, attributes @ ..]) => {
+                let element = Element::from_name(element)
+                    .ok_or_else(|| format!("unknown element `{}`", element))?;
+                let mut style = Style::default()
+                    .fg(parse_color(foreground)?)
+                    .bg(parse_color(background)?);
+                for attribute in attributes {
+                    style = style.add_modifier(parse_attribute(attribute)?);
+                }
+                self.theme.set(element, style);
+            }
+            ("color", _) => {
+                return Err(String::from(
+                    "usage: color ELEMENT FOREGROUND BACKGROUND [ATTRIBUTE]...",
+                ))
+            }
+
+            ("bind-key", [key, operation]) => {
+                self.keymap
+                    .bind(parse_keys(key)?, parse_operation(operation)?, None);
+            }
+            ("bind-key", [key, operation, context]) => {
+                self.keymap.bind(
+                    parse_keys(key)?,
+                    parse_operation(operation)?,
+                    Some(parse_context(context)?),
+                );
+            }
+            ("bind-key", _) => return Err(String::from("usage: bind-key KEY OPERATION [DIALOG]")),
+
+            ("unbind-key", [all]) if all == "-a" => self.keymap.unbind_all(),
+            ("unbind-key", [key]) => self.keymap.unbind(&parse_keys(key)?, None),
+            ("unbind-key", [key, context]) => {
+                self.keymap
+                    .unbind(&parse_keys(key)?, Some(parse_context(context)?));
+            }
+            ("unbind-key", _) => return Err(String::from("usage: unbind-key KEY [DIALOG]")),
+
+            ("macro", [key, operations @ ..]) if !operations.is_empty() => {
+                let key =
+                    keymap::parse_key(key).ok_or_else(|| format!("`{}` is not a key", key))?;
+                let operations = operations
+                    .split(|token| token == ";")
+                    .map(|operation| match operation {
+                        [operation] => parse_operation(operation),
+                        _ => Err(String::from(
+                            "operations of a macro should be separated by ` ; `",
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.keymap.set_macro(key, operations);
+            }
+            ("macro", _) => {
+                return Err(String::from("usage: macro KEY OPERATION [; OPERATION]..."))
+            }
+
+            _ => return Err(format!("unknown command `{}`", command)),
+        }
+        Ok(())
+    }
+}
+
+/// Split `text` into lines, joining the ones that end with a backslash to the next one. Each line
+/// comes with the number of its first physical line, starting from 1.
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut lines = vec![];
+    let mut current: Option<(usize, String)> = None;
+    for (index, line) in text.lines().enumerate() {
+        let (number, mut joined) = current.take().unwrap_or((index + 1, String::new()));
+        match line.strip_suffix('\\') {
+            Some(continued) => {
+                joined.push_str(continued);
+                current = Some((number, joined));
+            }
+            None => {
+                joined.push_str(line);
+                lines.push((number, joined));
+            }
+        }
+    }
+    // A backslash on the last line has nothing to join.
+    lines.extend(current);
+    lines
+}
+
+/// Where the file that `included` names is, if it's included from the file at `path`.
+fn resolve_include(included: &str, path: &Path) -> PathBuf {
+    if let Some(relative) = included.strip_prefix("~/") {
+        if let Some(home) = env::var_os("HOME") {
+            return PathBuf::from(home).join(relative);
+        }
+    }
+    match path.parent() {
+        Some(dir) => dir.join(included),
+        None => PathBuf::from(included),
+    }
+}
+
+/// Parse the `name` of a key sequence, complaining if it's not one.
+fn parse_keys(name: &str) -> Result<Vec<Key>, String> {
+    keymap::parse_keys(name).ok_or_else(|| format!("`{}` is not a key", name))
+}
+
+/// Parse the `name` of an operation, complaining if it's not one.
+fn parse_operation(name: &str) -> Result<Operation, String> {
+    Operation::from_name(name).ok_or_else(|| format!("unknown operation `{}`", name))
+}
+
+/// Parse the `name` of a dialog, complaining if it's not one.
+fn parse_context(name: &str) -> Result<Context, String> {
+    Context::from_name(name).ok_or_else(|| {
+        format!(
+            "unknown dialog `{}` (it should be feedlist, articlelist, article or global)",
+            name
+        )
+    })
+}
+
+/// Parse the `name` of a color, complaining if it's not one.
+fn parse_color(name: &str) -> Result<Color, String> {
+    theme::parse_color(name).ok_or_else(|| format!("unknown color `{}`", name))
+}
+
+/// Parse the `name` of a text attribute, complaining if it's not one.
+fn parse_attribute(name: &str) -> Result<Modifier, String> {
+    theme::parse_attribute(name).ok_or_else(|| format!("unknown attribute `{}`", name))
+}
@@ -1,6 +1,9 @@
 //! Events (e.g. keypresses, terminal resize etc.) that this UI can handle.
 
+use crate::timer::Timer;
+
 /// Supported keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Key {
     Backspace,
     Left,
@@ -13,6 +16,8 @@ pub enum Key {
     PageDown,
     Delete,
     Insert,
+    /// Shift-Tab. Tab itself is `Char('\t')`.
+    BackTab,
     F(u8),
     Char(char),
     Alt(Box<Key>),
@@ -29,4 +34,49 @@ pub enum Event {
     ///
     /// This is SIGWINCH.
     TerminalResized,
+
+    /// Someone asked us to stop, e.g. the user pressed Ctrl-Z.
+    ///
+    /// This is SIGTSTP. Since the signal is caught, we won't stop until we stop ourselves.
+    SuspendRequested,
+
+    /// We were stopped, and now are running again.
+    ///
+    /// This is SIGCONT.
+    Continued,
+
+    /// User interrupted us, and we should quit.
+    ///
+    /// This is SIGINT. In raw mode, Ctrl-C doesn't produce it, so it comes from another process.
+    Interrupted,
+
+    /// Someone asked us to quit, e.g. the session manager or the service manager.
+    ///
+    /// This is SIGTERM.
+    Terminated,
+
+    /// Our terminal went away, e.g. the terminal emulator or the tmux session was closed.
+    ///
+    /// This is SIGHUP.
+    HungUp,
+
+    /// A background reload fetched a feed, or failed to.
+    FeedFetched {
+        /// URL of the feed.
+        url: String,
+
+        /// Contents of the feed, or a description of what went wrong.
+        result: Result<String, String>,
+    },
+
+    /// A background reload is over: all of its feeds were fetched or failed to be.
+    ReloadFinished,
+
+    /// Nothing happened before the deadline that the app asked for, so some of its timers are
+    /// due.
+    Tick,
+
+    /// `timer` that a formaction might be interested in went off, e.g. it's time to reload the
+    /// feeds.
+    Timer(Timer),
 }
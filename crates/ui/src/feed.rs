@@ -0,0 +1,122 @@
+//! Feeds and their items (articles).
+
+use chrono::{DateTime, Utc};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::filter::Filter;
+
+/// A file attached to an item, e.g. a podcast episode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Enclosure {
+    /// Where the file can be downloaded from.
+    pub url: String,
+
+    /// MIME type of the file, e.g. "audio/mpeg". Might be empty if the feed didn't specify it.
+    pub mime_type: String,
+
+    /// Size of the file in bytes, if the feed specified it.
+    pub length: Option<u64>,
+}
+
+/// A single item (article) of a feed.
+#[derive(Debug, Clone, Default)]
+pub struct Item {
+    /// Title of the item.
+    pub title: String,
+
+    /// Link to the item's web page.
+    pub url: String,
+
+    /// Globally unique identifier of the item, which tells it apart from other items of the same
+    /// feed even if its title or link change.
+    pub guid: String,
+
+    /// Author of the item. Might be empty.
+    pub author: String,
+
+    /// When the item was published, if known.
+    pub date: Option<DateTime<Utc>>,
+
+    /// Contents of the item (HTML or plain text).
+    pub content: String,
+
+    /// Has the user not read this item yet?
+    pub unread: bool,
+
+    /// User-defined flags, each one a single ASCII letter. Kept sorted and without duplicates.
+    pub flags: String,
+
+    /// File attached to the item, if any.
+    pub enclosure: Option<Enclosure>,
+}
+
+impl Item {
+    /// Size of the item's contents, in characters.
+    pub fn size(&self) -> usize {
+        self.content.chars().count()
+    }
+
+    /// Add flags from `flags`, ignoring anything that's not an ASCII letter.
+    pub fn set_flags(&mut self, flags: &str) {
+        let mut result = self.flags.chars().collect::<Vec<_>>();
+        result.extend(flags.chars().filter(char::is_ascii_alphabetic));
+        result.sort_unstable();
+        result.dedup();
+        self.flags = result.into_iter().collect();
+    }
+}
+
+/// A feed, i.e. a list of items with a title.
+#[derive(Debug, Clone, Default)]
+pub struct Feed {
+    /// Title of the feed.
+    pub title: String,
+
+    /// URL from which the feed is fetched.
+    pub url: String,
+
+    /// Link to the feed's web page.
+    pub link: String,
+
+    /// Items of this feed, newest first.
+    pub items: Vec<Rc<RefCell<Item>>>,
+
+    /// Tags the user gave to this feed in the urls file.
+    pub tags: Vec<String>,
+
+    /// Title the user gave to this feed in the urls file, which is shown instead of `title`.
+    pub custom_title: Option<String>,
+
+    /// Should this feed be left out of the feed list? Its items can still show up in query feeds.
+    pub hidden: bool,
+
+    /// For query feeds, the filter that picks their items from other feeds.
+    pub query: Option<Filter>,
+}
+
+impl Feed {
+    /// Number of items the user hasn't read yet.
+    pub fn unread_count(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.borrow().unread)
+            .count()
+    }
+
+    /// Number of items in this feed.
+    pub fn total_count(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Title to show to the user: the custom one if there is one, then the feed's own title, and
+    /// its URL if it doesn't have a title at all.
+    pub fn display_title(&self) -> &str {
+        if let Some(ref title) = self.custom_title {
+            title
+        } else if self.title.is_empty() {
+            &self.url
+        } else {
+            &self.title
+        }
+    }
+}
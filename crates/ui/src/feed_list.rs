@@ -2,62 +2,258 @@
 
 use ratatui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{List, ListItem, Paragraph},
+    layout::{Constraint, Layout},
+    widgets::{List, ListItem, ListState, Paragraph},
     Frame,
 };
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use unicode_width::UnicodeWidthStr;
 
 use crate::app::App;
-use crate::event::Key;
+use crate::cache::Cache;
+use crate::command::{self, Command};
+use crate::config::{Config, Settings};
+use crate::event::Event;
+use crate::feed::Feed;
 use crate::form_action::FormAction;
+use crate::format;
+use crate::history::Histories;
 use crate::item_list::ItemList;
+use crate::keymap::{Context, Operation};
+use crate::opml;
+use crate::reload;
 use crate::stateful_list::StatefulList;
-use crate::widgets::text_line;
-
-/// Which widget should process input?
-pub enum Focus {
-    /// Input goes to the feedlist.
-    Dialog,
-
-    /// Input goes to the command line.
-    ///
-    /// Command line has a state that's only useful when the focus is on the command line, so we
-    /// keep it here rather than in `FeedList`.
-    CommandLine(text_line::TextLineState),
+use crate::status_bar::StatusBar;
+use crate::theme::Element;
+use crate::timer::Timer;
+use crate::urls::{self, Subscription};
+
+/// Progress of a background reload.
+struct ReloadProgress {
+    /// Number of feeds that were fetched (or failed to be) so far.
+    done: usize,
+
+    /// Number of feeds being reloaded.
+    total: usize,
+
+    /// Number of feeds that couldn't be fetched or parsed.
+    failed: usize,
+
+    /// What went wrong with the last feed that failed.
+    last_error: Option<String>,
 }
 
 /// List of feeds.
 pub struct FeedList {
-    /// The state of the feedlist (what items it contains, what item is currently selected)
-    list_state: StatefulList,
+    /// All the feeds, including the hidden ones.
+    feeds: Vec<Rc<RefCell<Feed>>>,
+
+    /// The state of the feedlist (what feeds it contains, what feed is currently selected)
+    list_state: StatefulList<Rc<RefCell<Feed>>>,
+
+    /// Where the feeds are saved.
+    cache: Rc<Cache>,
+
+    /// The urls file that lists the feeds, if they came from one. Changes to the subscriptions
+    /// are written there.
+    url_file: Option<PathBuf>,
+
+    /// Messages and the command line.
+    status_bar: StatusBar,
+
+    /// The tag that listed feeds should have, if the user picked one with `tag`.
+    tag: Option<String>,
+
+    /// Progress of the background reload, if one is running.
+    reload: Option<ReloadProgress>,
 
-    /// Which widget should process input?
-    focus: Focus,
+    /// Keys for the most useful operations, shown below the list.
+    hints: String,
+
+    /// Number of rows the list took up when it was last drawn.
+    list_height: usize,
+
+    /// Settings, key bindings and colors.
+    config: Rc<Config>,
 }
 
 impl FeedList {
-    /// Create example feedlist.
-    pub fn new() -> FeedList {
-        let mut list_state = StatefulList::new();
-
-        list_state.items = vec![
-            "   1    (14/532) Planet Debian".to_string(),
-            "   2       (0/1) Интересное на ДОУ".to_string(),
-            "   3 N (23/4558) Fabio Franchino’s blog".to_string(),
-            "   4      (0/13) @prometheusmooc on Twitter".to_string(),
-            "   5    (12/482) /dev/lawyer".to_string(),
-            "   6 N   (3/148) non-O(n) musings".to_string(),
-        ];
-        list_state.state.select(Some(0));
-
+    /// Create a feedlist displaying `feeds` that aren't hidden. All of them will be saved to
+    /// `cache`. If the feeds came from a urls file, `url_file` should point to it. The feedlist
+    /// looks and behaves the way `config` says. Commands and searches are recalled from and added
+    /// to `histories`.
+    pub fn new(
+        feeds: Vec<Rc<RefCell<Feed>>>,
+        cache: Rc<Cache>,
+        url_file: Option<PathBuf>,
+        config: Rc<Config>,
+        histories: Histories,
+    ) -> FeedList {
+        let visible = feeds
+            .iter()
+            .filter(|feed| is_visible(&feed.borrow(), &config.settings, None))
+            .cloned()
+            .collect();
         FeedList {
-            list_state,
-            focus: Focus::Dialog,
+            feeds,
+            list_state: StatefulList::with_items(visible),
+            cache,
+            url_file,
+            status_bar: StatusBar::new(histories),
+            tag: None,
+            reload: None,
+            hints: hints(&config),
+            list_height: 0,
+            config,
+        }
+    }
+
+    /// Show the feeds that should be visible now, keeping the same feed selected if it's still
+    /// there.
+    fn refresh(&mut self) {
+        let visible = self
+            .feeds
+            .iter()
+            .filter(|feed| is_visible(&feed.borrow(), &self.config.settings, self.tag.as_deref()))
+            .cloned()
+            .collect::<Vec<_>>();
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|selected| visible.iter().position(|feed| Rc::ptr_eq(feed, selected)))
+            .or_else(|| {
+                let index = self.list_state.state.selected()?;
+                Some(index.min(visible.len().checked_sub(1)?))
+            })
+            .or_else(|| (!visible.is_empty()).then_some(0));
+        self.list_state.items = visible;
+        self.list_state.state.select(selected);
+    }
+
+    /// Fetch `feeds` in the background, unless another reload is still running.
+    fn reload<B: Backend>(&mut self, feeds: &[Rc<RefCell<Feed>>], app: &mut App<B>) {
+        if self.reload.is_some() {
+            self.status_bar
+                .info(String::from("Already reloading, please wait"));
+            return;
+        }
+
+        let urls = feeds
+            .iter()
+            .map(|feed| feed.borrow())
+            // Query feeds are updated along with the feeds they take items from.
+            .filter(|feed| feed.query.is_none())
+            .map(|feed| feed.url.clone())
+            .collect::<Vec<_>>();
+        if urls.is_empty() {
+            return;
+        }
+
+        self.reload = Some(ReloadProgress {
+            done: 0,
+            total: urls.len(),
+            failed: 0,
+            last_error: None,
+        });
+        app.reload_feeds(urls);
+    }
+
+    /// Put the fetched feed with `url` into the list. `result` is the feed's document, or why it
+    /// couldn't be fetched.
+    fn feed_fetched(&mut self, url: &str, result: &Result<String, String>) {
+        let outcome = match result {
+            Ok(document) => {
+                // The same URL can be subscribed to more than once, e.g. with different tags.
+                let mut feeds = self.feeds.iter().filter(|feed| feed.borrow().url == url);
+                match feeds.next() {
+                    Some(first) => {
+                        let outcome = reload::apply(first, document, &self.cache);
+                        if outcome.is_ok() {
+                            let first = first.borrow();
+                            for feed in feeds {
+                                let mut feed = feed.borrow_mut();
+                                feed.title = first.title.clone();
+                                feed.link = first.link.clone();
+                                feed.items = first.items.clone();
+                            }
+                        }
+                        outcome.map_err(|e| e.to_string())
+                    }
+                    // The feed was removed while we fetched it.
+                    None => Ok(()),
+                }
+            }
+            Err(e) => Err(e.clone()),
+        };
+
+        if let Err(e) = outcome {
+            let error = format!("{}: {}", url, e);
+            self.status_bar.error(&error);
+            if let Some(ref mut progress) = self.reload {
+                progress.failed += 1;
+                progress.last_error = Some(error);
+            }
+        }
+        if let Some(ref mut progress) = self.reload {
+            progress.done += 1;
+        }
+    }
+
+    /// Subscriptions that the feeds were created from.
+    fn subscriptions(&self) -> Vec<Subscription> {
+        self.feeds
+            .iter()
+            .map(|feed| Subscription::from_feed(&feed.borrow()))
+            .collect()
+    }
+
+    /// Add feeds from OPML file at `path` to the list, and to the urls file if there is one.
+    fn import(&mut self, path: &Path) -> Result<String, Box<dyn Error>> {
+        let imported = opml::import_file(path)?;
+
+        let mut subscriptions = self.subscriptions();
+        let old_count = subscriptions.len();
+        let added = urls::merge(&mut subscriptions, imported);
+
+        // Feeds that we already had might've got new tags.
+        for (feed, subscription) in self.feeds.iter().zip(&subscriptions) {
+            feed.borrow_mut().tags = subscription.tags.clone();
+        }
+        for feed in urls::load_feeds(&subscriptions[old_count..], &self.cache)? {
+            if is_visible(&feed.borrow(), &self.config.settings, self.tag.as_deref()) {
+                self.list_state.push(feed.clone());
+            }
+            self.feeds.push(feed);
+        }
+        urls::update_query_feeds(&self.feeds);
+
+        match self.url_file {
+            Some(ref url_file) => {
+                urls::write_file(url_file, &subscriptions)?;
+                Ok(format!(
+                    "Imported {} new feeds into {}",
+                    added,
+                    url_file.display()
+                ))
+            }
+            None => Ok(format!(
+                "Imported {} new feeds (there's no urls file, so they're gone after restart)",
+                added
+            )),
         }
     }
+
+    /// Write all subscriptions to OPML file at `path`.
+    fn export(&self, path: &Path) -> Result<String, Box<dyn Error>> {
+        fs::write(path, opml::export(&self.feeds))?;
+        Ok(format!("Exported feeds to {}", path.display()))
+    }
 }
 
 impl<B: Backend> FormAction<B> for FeedList {
@@ -65,131 +261,245 @@ impl<B: Backend> FormAction<B> for FeedList {
         let layout = Layout::default()
             .constraints(
                 [
-                    Constraint::Length(1), // title
-                    Constraint::Min(0),    // feedlist
-                    Constraint::Length(1), // hints
-                    Constraint::Length(1), // command line (TODO: implement)
+                    Constraint::Length(1),                                            // title
+                    Constraint::Min(0),                                               // feedlist
+                    Constraint::Length(self.config.settings.show_keymap_hint.into()), // hints
+                    Constraint::Length(1),                                            // status bar
                 ]
                 .as_ref(),
             )
             .split(frame.size());
+        let theme = &self.config.theme;
 
         {
-            let title = Span::styled(
-                "Newsboat 2.20 (ну, почти) - Your Feeds (0 unread, 0 total)",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .bg(Color::Blue)
-                    .add_modifier(Modifier::BOLD),
+            let unread_feeds = self
+                .list_state
+                .items
+                .iter()
+                .filter(|feed| feed.borrow().unread_count() > 0)
+                .count();
+            let reloading = match self.reload {
+                Some(ref progress) => format!(" - Reloading {}/{}", progress.done, progress.total),
+                None => String::new(),
+            };
+            let width = usize::from(layout[0].width).saturating_sub(reloading.width());
+            let mut title = format::feedlist_title(
+                &self.config.settings,
+                width,
+                unread_feeds,
+                self.list_state.items.len(),
             );
-            let paragraph = Paragraph::new(title).style(Style::default().bg(Color::Blue));
+            title.push_str(&reloading);
+            let paragraph = Paragraph::new(title).style(theme.style(Element::Title));
             frame.render_widget(paragraph, layout[0]);
         }
 
         {
+            self.list_height = usize::from(layout[1].height);
+            let visible = self.list_state.visible_range(self.list_height);
+            let start = visible.start;
+            let selected = self.list_state.state.selected();
+            let width = usize::from(layout[1].width);
             let list = List::new(
-                self.list_state
-                    .items
+                self.list_state.items[visible]
                     .iter()
-                    .map(|text| {
-                        ListItem::new(Span::styled(
-                            text.to_string(),
-                            Style::default().fg(Color::Green),
-                        ))
+                    .enumerate()
+                    .map(|(i, feed)| {
+                        let i = start + i;
+                        let feed = feed.borrow();
+                        let row = format::feed_row(&self.config.settings, width, i + 1, &feed);
+                        ListItem::new(row)
+                            .style(theme.list_row(selected == Some(i), feed.unread_count() > 0))
                     })
                     .collect::<Vec<_>>(),
-            )
-            .highlight_style(
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
             );
 
-            frame.render_stateful_widget(list, layout[1], &mut self.list_state.state);
+            let mut state = ListState::default().with_selected(selected.map(|i| i - start));
+            frame.render_stateful_widget(list, layout[1], &mut state);
         }
 
         {
-            let hints = Span::styled(
-                "q:Quit UP:Previous DOWN:Next ENTER:Open",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .bg(Color::Blue)
-                    .add_modifier(Modifier::BOLD),
-            );
-            let paragraph = Paragraph::new(hints).style(Style::default().bg(Color::Blue));
+            let paragraph = Paragraph::new(self.hints.as_str()).style(theme.style(Element::Hint));
             frame.render_widget(paragraph, layout[2]);
         }
 
-        {
-            if let Focus::CommandLine(ref mut cli_state) = self.focus {
-                let line_layout = Layout::default()
-                    .constraints(
-                        [
-                            Constraint::Length(1), // Colon
-                            Constraint::Min(0),    // Text input
-                        ]
-                        .as_ref(),
-                    )
-                    .direction(Direction::Horizontal)
-                    .split(layout[3]);
-
-                let paragraph = Paragraph::new(Span::raw(":"));
-                frame.render_widget(paragraph, line_layout[0]);
-
-                let command_line = text_line::TextLine::new();
-                frame.render_stateful_widget(command_line, line_layout[1], cli_state);
-                frame.set_cursor(
-                    line_layout[1]
-                        // x+cursor_offset, with careful type conversions:
-                        // - cursor_offset is usize, so we limit it to u16::MAX
-                        // - u16+u16 won't fit into u16. Since we just want the cursor at the end of
-                        //   the text, we're using saturating addition to put cursor as far right as we
-                        //   possibly can
-                        .x
-                        .saturating_add(
-                            cli_state.cursor_display_offset().min(u16::MAX as usize) as u16
-                        ),
-                    line_layout[1].y,
-                );
+        self.status_bar
+            .draw(frame, layout[3], theme.style(Element::Info));
+    }
+
+    fn context(&self) -> Context {
+        Context::FeedList
+    }
+
+    fn status_bar(&mut self) -> &mut StatusBar {
+        &mut self.status_bar
+    }
+
+    /// Tags of all the feeds, sorted and without duplicates.
+    fn tags(&self) -> Vec<String> {
+        let mut tags = self
+            .feeds
+            .iter()
+            .flat_map(|feed| feed.borrow().tags.clone())
+            .collect::<Vec<_>>();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    fn run_command(&mut self, command: &Command, _app: &mut App<B>) -> command::Outcome {
+        match (command.name.as_str(), command.arguments.as_slice()) {
+            ("goto", arguments) => command::goto(&mut self.list_state, arguments, "feed", |feed| {
+                feed.borrow().display_title().to_string()
+            }),
+            ("search", arguments) => {
+                command::search(&mut self.list_state, arguments, "feed", |feed| {
+                    feed.borrow().display_title().to_string()
+                })
+            }
+
+            ("tag", []) => {
+                self.tag = None;
+                self.refresh();
+                Ok(None)
+            }
+            ("tag", [tag]) => {
+                if !self
+                    .feeds
+                    .iter()
+                    .any(|feed| feed.borrow().tags.contains(tag))
+                {
+                    return Err(format!("no feed is tagged `{}`", tag).into());
+                }
+                self.tag = Some(tag.clone());
+                self.refresh();
+                Ok(Some(format!("Showing feeds tagged `{}`", tag)))
             }
+
+            ("import", [path]) => self.import(Path::new(path)).map(Some),
+            ("export", [path]) => self.export(Path::new(path)).map(Some),
+
+            _ => Err(format!("`{}` can't be used here", command.name).into()),
         }
     }
 
-    fn handle_key(&mut self, key: Key, app: &mut App<B>) {
-        match self.focus {
-            Focus::Dialog => match key {
-                Key::Char(c) => match c {
-                    'q' => app.should_quit = true,
+    fn set_config(&mut self, config: Rc<Config>) {
+        self.hints = hints(&config);
+        self.config = config;
+        self.refresh();
+    }
 
-                    ':' => self.focus = Focus::CommandLine(text_line::TextLineState::default()),
+    fn handle_operation(&mut self, operation: Operation, count: Option<usize>, app: &mut App<B>) {
+        match operation {
+            Operation::Quit => app.should_quit = true,
 
-                    '\n' => app.add_formaction(Rc::new(RefCell::new(ItemList::new()))),
+            Operation::Reload => {
+                if let Some(feed) = self.list_state.selected().cloned() {
+                    self.reload(&[feed], app);
+                }
+            }
 
-                    _ => {}
-                },
+            Operation::ReloadAll => self.reload(&self.feeds.clone(), app),
 
-                Key::Up => self.list_state.previous(),
+            Operation::Open => {
+                if let Some(feed) = self.list_state.selected() {
+                    let item_list = ItemList::new(
+                        feed.clone(),
+                        self.cache.clone(),
+                        app.config(),
+                        app.histories(),
+                    );
+                    app.add_formaction(Rc::new(RefCell::new(item_list)));
+                }
+            }
 
-                Key::Down => self.list_state.next(),
+            Operation::Up => {
+                self.list_state.previous_by(count.unwrap_or(1));
+            }
 
-                _ => {}
-            },
+            Operation::Down => {
+                self.list_state.next_by(count.unwrap_or(1));
+            }
 
-            Focus::CommandLine(ref mut cli_state) => match key {
-                Key::Char(c) => match c {
-                    '\n' => {
-                        if cli_state.text() == "quit" {
-                            app.should_quit = true;
-                        } else {
-                            self.focus = Focus::Dialog;
-                        }
+            // Entries are numbered from 1 on screen.
+            Operation::Home => self
+                .list_state
+                .select_nearest(count.unwrap_or(1).saturating_sub(1)),
+
+            Operation::End => self
+                .list_state
+                .select_nearest(count.map_or(usize::MAX, |n| n.saturating_sub(1))),
+
+            Operation::Center => self.list_state.center(self.list_height),
+
+            _ => {}
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event, app: &mut App<B>) {
+        match event {
+            Event::FeedFetched { url, result } => {
+                self.feed_fetched(url, result);
+                urls::update_query_feeds(&self.feeds);
+                self.refresh();
+                app.mark_dirty();
+            }
+
+            // Unlike the user, the timer doesn't need to be told that a reload is running
+            // already.
+            Event::Timer(Timer::Reload) if self.reload.is_none() => {
+                self.reload(&self.feeds.clone(), app);
+                app.mark_dirty();
+            }
+
+            Event::ReloadFinished => {
+                app.mark_dirty();
+                if let Some(progress) = self.reload.take() {
+                    if let Some(last_error) = progress.last_error {
+                        // Keep the last error on screen, but let the user know it's not the only
+                        // one.
+                        self.status_bar.error(&format!(
+                            "{} of {} feeds failed to reload. {}",
+                            progress.failed, progress.total, last_error
+                        ));
                     }
+                }
+            }
 
-                    _ => cli_state.put_char(c),
-                },
+            _ => {}
+        }
+    }
 
-                _ => {}
-            },
+    fn save_state(&mut self) -> Result<(), Box<dyn Error>> {
+        for feed in &self.feeds {
+            let feed = feed.borrow();
+            // Items of query feeds are saved along with the feeds they came from.
+            if feed.query.is_none() {
+                self.cache.save_feed(&feed)?;
+            }
         }
+        Ok(())
     }
 }
+
+/// Should `feed` be listed, given the `settings`?
+fn is_visible(feed: &Feed, settings: &Settings, tag: Option<&str>) -> bool {
+    !feed.hidden
+        && (settings.show_read_feeds || feed.unread_count() > 0)
+        && tag.is_none_or(|tag| feed.tags.iter().any(|feed_tag| feed_tag == tag))
+}
+
+/// Keys for the most useful operations of the feedlist, according to `config`.
+fn hints(config: &Config) -> String {
+    config.keymap.hints(
+        Context::FeedList,
+        &[
+            (Operation::Quit, "Quit"),
+            (Operation::Up, "Previous"),
+            (Operation::Down, "Next"),
+            (Operation::Open, "Open"),
+            (Operation::Reload, "Reload"),
+            (Operation::ReloadAll, "Reload All"),
+        ],
+    )
+}
@@ -0,0 +1,403 @@
+//! Newsboat's filter language, used by query feeds to pick items from other feeds.
+//!
+//! An expression compares item attributes to values, e.g. `unread = "yes" and tags # "news"`, and
+//! combines comparisons with `and`, `or` and parentheses. `and` binds tighter than `or`.
+
+use chrono::Utc;
+use regex::Regex;
+use std::{cell::RefCell, cmp::Reverse, fmt, rc::Rc};
+
+use crate::feed::{Feed, Item};
+
+/// A parsed filter expression.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// Both sides must match.
+    And(Box<Filter>, Box<Filter>),
+
+    /// At least one side must match.
+    Or(Box<Filter>, Box<Filter>),
+
+    /// An attribute of the item must compare to a value in a certain way.
+    Comparison {
+        /// The attribute being compared.
+        attribute: Attribute,
+
+        /// How to compare.
+        operator: Operator,
+
+        /// The value to compare with.
+        value: String,
+
+        /// For `=~` and `!~`, the `value` compiled into a regular expression.
+        regex: Option<Regex>,
+    },
+}
+
+/// Things about an item that a filter can look at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    /// Item's title.
+    Title,
+    /// Link to item's web page.
+    Link,
+    /// Item's author.
+    Author,
+    /// Item's contents.
+    Content,
+    /// Item's GUID.
+    Guid,
+    /// "yes" if the item is unread, "no" otherwise.
+    Unread,
+    /// Item's flags.
+    Flags,
+    /// Number of whole days since the item was published.
+    Age,
+    /// Title of the item's feed.
+    FeedTitle,
+    /// URL of the item's feed.
+    FeedUrl,
+    /// Tags of the item's feed, separated by spaces.
+    Tags,
+}
+
+impl Attribute {
+    /// The attribute called `name` in filter expressions.
+    fn from_name(name: &str) -> Option<Attribute> {
+        let attribute = match name {
+            "title" => Attribute::Title,
+            "link" => Attribute::Link,
+            "author" => Attribute::Author,
+            "content" | "description" => Attribute::Content,
+            "guid" => Attribute::Guid,
+            "unread" => Attribute::Unread,
+            "flags" => Attribute::Flags,
+            "age" => Attribute::Age,
+            "feedtitle" => Attribute::FeedTitle,
+            "feedurl" | "rssurl" => Attribute::FeedUrl,
+            "tags" => Attribute::Tags,
+            _ => return None,
+        };
+        Some(attribute)
+    }
+
+    /// Value of this attribute for `item` from `feed`.
+    fn value(self, feed: &Feed, item: &Item) -> String {
+        match self {
+            Attribute::Title => item.title.clone(),
+            Attribute::Link => item.url.clone(),
+            Attribute::Author => item.author.clone(),
+            Attribute::Content => item.content.clone(),
+            Attribute::Guid => item.guid.clone(),
+            Attribute::Unread => String::from(if item.unread { "yes" } else { "no" }),
+            Attribute::Flags => item.flags.clone(),
+            Attribute::Age => item
+                .date
+                .map(|date| (Utc::now() - date).num_days().to_string())
+                .unwrap_or_default(),
+            Attribute::FeedTitle => feed.display_title().to_string(),
+            Attribute::FeedUrl => feed.url.clone(),
+            Attribute::Tags => feed.tags.join(" "),
+        }
+    }
+}
+
+/// Ways to compare an attribute to a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// `=` (or `==`): equal strings.
+    Equal,
+    /// `!=`: different strings.
+    NotEqual,
+    /// `=~`: the attribute matches the regular expression.
+    Matches,
+    /// `!~`: the attribute doesn't match the regular expression.
+    NotMatches,
+    /// `#`: one of the space-separated words of the attribute is the value; for `flags`, the
+    /// attribute contains the flag.
+    Contains,
+    /// `!#`: the opposite of `#`.
+    NotContains,
+    /// `<`: both sides are numbers, and the attribute is smaller.
+    Less,
+    /// `>`: both sides are numbers, and the attribute is greater.
+    Greater,
+    /// `<=`: both sides are numbers, and the attribute is not greater.
+    LessOrEqual,
+    /// `>=`: both sides are numbers, and the attribute is not smaller.
+    GreaterOrEqual,
+}
+
+/// A problem with a filter expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError(pub String);
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid filter expression: {}", self.0)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl Filter {
+    /// Parse filter `expression`.
+    pub fn parse(expression: &str) -> Result<Filter, FilterError> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let filter = parser.or_expression()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(FilterError(format!("unexpected `{}`", token.text()))),
+        }
+    }
+
+    /// Does `item` from `feed` pass this filter?
+    pub fn matches(&self, feed: &Feed, item: &Item) -> bool {
+        match self {
+            Filter::And(left, right) => left.matches(feed, item) && right.matches(feed, item),
+            Filter::Or(left, right) => left.matches(feed, item) || right.matches(feed, item),
+            Filter::Comparison {
+                attribute,
+                operator,
+                value,
+                regex,
+            } => {
+                let actual = attribute.value(feed, item);
+                let contains = || {
+                    if *attribute == Attribute::Flags {
+                        value.chars().all(|flag| actual.contains(flag))
+                    } else {
+                        actual.split_whitespace().any(|word| word == value)
+                    }
+                };
+                let compare = || match (actual.parse::<i64>(), value.parse::<i64>()) {
+                    (Ok(actual), Ok(value)) => Some(actual.cmp(&value)),
+                    _ => None,
+                };
+                let is_match = || regex.as_ref().is_some_and(|re| re.is_match(&actual));
+
+                match operator {
+                    Operator::Equal => actual == *value,
+                    Operator::NotEqual => actual != *value,
+                    Operator::Matches => is_match(),
+                    Operator::NotMatches => !is_match(),
+                    Operator::Contains => contains(),
+                    Operator::NotContains => !contains(),
+                    Operator::Less => compare().is_some_and(|o| o.is_lt()),
+                    Operator::Greater => compare().is_some_and(|o| o.is_gt()),
+                    Operator::LessOrEqual => compare().is_some_and(|o| o.is_le()),
+                    Operator::GreaterOrEqual => compare().is_some_and(|o| o.is_ge()),
+                }
+            }
+        }
+    }
+
+    /// Items of `feeds` that pass this filter, newest first.
+    pub fn select(&self, feeds: &[Rc<RefCell<Feed>>]) -> Vec<Rc<RefCell<Item>>> {
+        let mut items = vec![];
+        for feed in feeds {
+            let feed = feed.borrow();
+            // Query feeds only contain items of other feeds, which we're going to see anyway.
+            if feed.query.is_some() {
+                continue;
+            }
+            items.extend(
+                feed.items
+                    .iter()
+                    .filter(|item| self.matches(&feed, &item.borrow()))
+                    .cloned(),
+            );
+        }
+        items.sort_by_key(|item| Reverse(item.borrow().date));
+        items
+    }
+}
+
+/// A lexical element of a filter expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// An attribute name, a bare value, or `and`/`or`.
+    Word(String),
+    /// A double-quoted value, with the quotes and escapes removed.
+    Quoted(String),
+    /// A comparison operator.
+    Operator(&'static str),
+    /// `(`.
+    Open,
+    /// `)`.
+    Close,
+}
+
+impl Token {
+    /// How the token looks in the expression, for error messages.
+    fn text(&self) -> String {
+        match self {
+            Token::Word(word) => word.clone(),
+            Token::Quoted(text) => format!("\"{}\"", text),
+            Token::Operator(operator) => operator.to_string(),
+            Token::Open => String::from("("),
+            Token::Close => String::from(")"),
+        }
+    }
+}
+
+/// Operators, longest first so that e.g. `!=` isn't taken for `!` followed by `=`.
+const OPERATORS: [&str; 11] = ["==", "!=", "=~", "!~", "!#", "<=", ">=", "=", "#", "<", ">"];
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = vec![];
+    let mut rest = expression.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        if c == '(' || c == ')' {
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            rest = &rest[1..];
+        } else if c == '"' {
+            let mut text = String::new();
+            let mut chars = rest[1..].char_indices();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        end = Some(i + 2);
+                        break;
+                    }
+                    '\\' => match chars.next() {
+                        Some((_, escaped)) => text.push(escaped),
+                        None => break,
+                    },
+                    _ => text.push(c),
+                }
+            }
+            let end = end.ok_or_else(|| FilterError(String::from("unterminated string")))?;
+            tokens.push(Token::Quoted(text));
+            rest = &rest[end..];
+        } else if let Some(operator) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            tokens.push(Token::Operator(operator));
+            rest = &rest[operator.len()..];
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || "()\"=!#<>~".contains(c))
+                .unwrap_or(rest.len());
+            if end == 0 {
+                return Err(FilterError(format!("unexpected `{}`", c)));
+            }
+            tokens.push(Token::Word(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive-descent parser over the tokens of an expression.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn next_is_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w == word)
+    }
+
+    fn or_expression(&mut self) -> Result<Filter, FilterError> {
+        let mut filter = self.and_expression()?;
+        while self.next_is_word("or") {
+            self.position += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.and_expression()?));
+        }
+        Ok(filter)
+    }
+
+    fn and_expression(&mut self) -> Result<Filter, FilterError> {
+        let mut filter = self.primary()?;
+        while self.next_is_word("and") {
+            self.position += 1;
+            filter = Filter::And(Box::new(filter), Box::new(self.primary()?));
+        }
+        Ok(filter)
+    }
+
+    fn primary(&mut self) -> Result<Filter, FilterError> {
+        match self.next() {
+            Some(Token::Open) => {
+                let filter = self.or_expression()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err(FilterError(String::from("missing `)`"))),
+                }
+            }
+
+            Some(Token::Word(name)) => {
+                let attribute = Attribute::from_name(&name)
+                    .ok_or_else(|| FilterError(format!("unknown attribute `{}`", name)))?;
+
+                let operator = match self.next() {
+                    Some(Token::Operator(operator)) => match operator {
+                        "=" | "==" => Operator::Equal,
+                        "!=" => Operator::NotEqual,
+                        "=~" => Operator::Matches,
+                        "!~" => Operator::NotMatches,
+                        "#" => Operator::Contains,
+                        "!#" => Operator::NotContains,
+                        "<" => Operator::Less,
+                        ">" => Operator::Greater,
+                        "<=" => Operator::LessOrEqual,
+                        _ => Operator::GreaterOrEqual,
+                    },
+                    _ => {
+                        return Err(FilterError(format!(
+                            "expected an operator after `{}`",
+                            name
+                        )))
+                    }
+                };
+
+                let value = match self.next() {
+                    Some(Token::Quoted(value)) | Some(Token::Word(value)) => value,
+                    _ => {
+                        return Err(FilterError(format!(
+                            "expected a value after `{} {}`",
+                            name,
+                            self.tokens[self.position - 2].text()
+                        )))
+                    }
+                };
+
+                let regex = match operator {
+                    Operator::Matches | Operator::NotMatches => Some(
+                        Regex::new(&value)
+                            .map_err(|e| FilterError(format!("bad regular expression: {}", e)))?,
+                    ),
+                    _ => None,
+                };
+
+                Ok(Filter::Comparison {
+                    attribute,
+                    operator,
+                    value,
+                    regex,
+                })
+            }
+
+            Some(token) => Err(FilterError(format!("unexpected `{}`", token.text()))),
+            None => Err(FilterError(String::from("unexpected end of expression"))),
+        }
+    }
+}
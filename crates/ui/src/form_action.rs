@@ -1,16 +1,65 @@
 //! A trait that each "formaction" (dialog) implements.
 
 use ratatui::{backend::Backend, terminal::Frame};
+use std::{error::Error, rc::Rc};
 
 use crate::app::App;
-use crate::event::Key;
+use crate::command::{self, Command};
+use crate::config::Config;
+use crate::event::{Event, Key};
+use crate::keymap::{Context, Operation};
+use crate::status_bar::{self, StatusBar};
 
 /// A trait that each "formaction" (dialog) implements.
 pub trait FormAction<B: Backend> {
-    /// Handle `key` pressed by the user, possibly changing something within the `app` in the
-    /// process.
-    fn handle_key(&mut self, key: Key, app: &mut App<B>);
+    /// The set of key bindings that applies to this formaction.
+    fn context(&self) -> Context;
+
+    /// The bottom row of the formaction, with its messages and command line.
+    fn status_bar(&mut self) -> &mut StatusBar;
+
+    /// Handle `key` pressed by the user before it's looked up in the keymap, e.g. when the user
+    /// is typing into the command line.
+    ///
+    /// Returns the key if the formaction didn't use it, so that its binding takes effect.
+    fn handle_key(&mut self, key: Key, app: &mut App<B>) -> Option<Key> {
+        status_bar::handle_key(self, key, app)
+    }
+
+    /// Tags that `tag` on the command line can be completed to.
+    fn tags(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Perform `operation` that the user asked for, possibly changing something within the `app`
+    /// in the process.
+    ///
+    /// `count` is the number the user typed before the keys, if any. Movements are repeated that
+    /// many times, and jumps to the first or the last entry go to the entry with that number
+    /// instead.
+    fn handle_operation(&mut self, operation: Operation, count: Option<usize>, app: &mut App<B>);
+
+    /// Run `command` that the user entered, which isn't one of those that work the same in every
+    /// formaction (see `command::run`).
+    fn run_command(&mut self, command: &Command, _app: &mut App<B>) -> command::Outcome {
+        Err(format!("`{}` can't be used here", command.name).into())
+    }
+
+    /// Start looking and behaving the way `config` says. This is called when the user changes the
+    /// config while the program is running.
+    fn set_config(&mut self, config: Rc<Config>);
+
+    /// Handle `event` that isn't input, e.g. the outcome of a background reload. If that changes
+    /// what the formaction looks like, it should call `app.mark_dirty()`.
+    ///
+    /// Unlike keys, these events are passed to every formaction, not just the current one.
+    fn handle_event(&mut self, _event: &Event, _app: &mut App<B>) {}
 
     /// Draw this formaction onto the `frame`.
     fn draw(&mut self, frame: &mut Frame);
+
+    /// Persist whatever should survive a restart. Called right before the program exits.
+    fn save_state(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
 }
@@ -0,0 +1,299 @@
+//! Turning feeds and items into text for the lists and title bars.
+//!
+//! The text is described by format strings, the way Newsboat does it. Everything in a format is
+//! copied as-is, except for:
+//!
+//! - `%x`, which is replaced with the value of specifier `x` (what specifiers there are depends on
+//!   the format, see the functions below). An unknown specifier is replaced with nothing;
+//! - `%Nx`, which pads the value with spaces on the left, or cuts it, so that it takes up exactly
+//!   N columns. `%-Nx` does the same, but pads on the right. N can't be larger than 65535;
+//! - `%?x?THEN&ELSE?`, which is replaced with THEN if the value of `x` isn't empty, and with ELSE
+//!   otherwise. Both can contain specifiers; `&ELSE` can be omitted;
+//! - `%>c`, which fills the line with the character `c` up to its full width, pushing everything
+//!   after it to the right edge;
+//! - `%%`, which is replaced with a single percent sign.
+//!
+//! Widths are measured in terminal columns, so wide characters take up two.
+
+use chrono::Local;
+use std::{iter::Peekable, str::Chars};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::config::Settings;
+use crate::feed::{Feed, Item};
+
+/// What `%N` is replaced with in title formats.
+pub const PROGRAM_NAME: &str = "Newsboat";
+
+/// What `%V` is replaced with in title formats.
+pub const PROGRAM_VERSION: &str = "2.20 (ну, почти)";
+
+/// The widest `%Nx` can be. Terminals are at most this many columns wide, so wider values could
+/// never be seen anyway, and padding them would take up memory for nothing.
+const MAX_WIDTH: usize = u16::MAX as usize;
+
+/// Expand `template` into a line that's `width` columns wide, taking values of specifiers from
+/// `value`.
+///
+/// The line is only padded to `width` if there's a `%>` in the template; it's never cut to it.
+pub fn format(template: &str, width: usize, value: &dyn Fn(char) -> Option<String>) -> String {
+    let mut left = String::new();
+    let mut right: Option<(char, String)> = None;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        let output = match right {
+            Some((_, ref mut right)) => right,
+            None => &mut left,
+        };
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('>') => {
+                chars.next();
+                let fill = chars.next().unwrap_or(' ');
+                // Only the first fill counts, the rest are ignored.
+                if right.is_none() {
+                    right = Some((fill, String::new()));
+                }
+            }
+            Some('?') => {
+                chars.next();
+                expand_conditional(&mut chars, value, output);
+            }
+            _ => expand_specifier(&mut chars, value, output),
+        }
+    }
+
+    if let Some((fill, right)) = right {
+        let used = left.width() + right.width();
+        let fill_width = fill.width().unwrap_or(0).max(1);
+        let fills = width.saturating_sub(used) / fill_width;
+        left.push_str(&fill.to_string().repeat(fills));
+        left.push_str(&right);
+    }
+    left
+}
+
+/// Expand the rest of `%?x?THEN&ELSE?` into `output`.
+fn expand_conditional(
+    chars: &mut Peekable<Chars>,
+    value: &dyn Fn(char) -> Option<String>,
+    output: &mut String,
+) {
+    let condition = chars.next();
+    if chars.next() != Some('?') {
+        return;
+    }
+    let mut then = String::new();
+    let mut otherwise = String::new();
+    let mut in_otherwise = false;
+    for c in chars.by_ref() {
+        match c {
+            '?' => break,
+            '&' if !in_otherwise => in_otherwise = true,
+            _ if in_otherwise => otherwise.push(c),
+            _ => then.push(c),
+        }
+    }
+
+    let is_set = condition
+        .and_then(value)
+        .is_some_and(|value| !value.is_empty());
+    let branch = if is_set { then } else { otherwise };
+    output.push_str(&format(&branch, 0, value));
+}
+
+/// Expand the rest of `%x`, `%Nx`, `%-Nx` or `%%` into `output`.
+fn expand_specifier(
+    chars: &mut Peekable<Chars>,
+    value: &dyn Fn(char) -> Option<String>,
+    output: &mut String,
+) {
+    let left_aligned = chars.next_if_eq(&'-').is_some();
+    let mut width = None;
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        let digit = digit.to_digit(10).unwrap_or(0) as usize;
+        width = Some(
+            width
+                .unwrap_or(0usize)
+                .saturating_mul(10)
+                .saturating_add(digit)
+                .min(MAX_WIDTH),
+        );
+    }
+    let text = match chars.next() {
+        Some('%') => String::from("%"),
+        Some(specifier) => value(specifier).unwrap_or_default(),
+        None => return,
+    };
+    match width {
+        Some(width) => output.push_str(&fit(&text, width, left_aligned)),
+        None => output.push_str(&text),
+    }
+}
+
+/// Make `text` exactly `width` columns wide by cutting off its end or padding it with spaces.
+fn fit(text: &str, width: usize, left_aligned: bool) -> String {
+    let mut fitted = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let c_width = c.width().unwrap_or(0);
+        if used + c_width > width {
+            break;
+        }
+        fitted.push(c);
+        used += c_width;
+    }
+    let padding = " ".repeat(width - used);
+    if left_aligned {
+        fitted + &padding
+    } else {
+        padding + &fitted
+    }
+}
+
+/// Format a row of the feed list with `feedlist-format`. Specifiers:
+///
+/// - `%i`: 1-based `index`;
+/// - `%n`: `N` if the feed has unread items;
+/// - `%u`: unread and total item counts, like `(3/42)`;
+/// - `%U`, `%c`: unread and total item counts on their own;
+/// - `%t`: title;
+/// - `%T`: first tag;
+/// - `%l`: link to the website;
+/// - `%L`: URL of the feed.
+pub fn feed_row(settings: &Settings, width: usize, index: usize, feed: &Feed) -> String {
+    let unread = feed.unread_count();
+    format(&settings.feedlist_format, width, &|specifier| {
+        let value = match specifier {
+            'i' => index.to_string(),
+            'n' => String::from(if unread > 0 { "N" } else { " " }),
+            'u' => format!("({}/{})", unread, feed.total_count()),
+            'U' => unread.to_string(),
+            'c' => feed.total_count().to_string(),
+            't' => feed.display_title().to_string(),
+            'T' => feed.tags.first().cloned().unwrap_or_default(),
+            'l' => feed.link.clone(),
+            'L' => feed.url.clone(),
+            _ => return None,
+        };
+        Some(value)
+    })
+}
+
+/// Format a row of the item list with `articlelist-format`. Specifiers:
+///
+/// - `%i`: 1-based `index`;
+/// - `%f`: `N` if the item is unread, followed by `!` if it has flags;
+/// - `%D`: date, formatted with `datetime-format`, or blank if the item doesn't have one;
+/// - `%L`: size of the contents, see `human_readable_size`;
+/// - `%t`: title;
+/// - `%a`: author.
+pub fn item_row(settings: &Settings, width: usize, index: usize, item: &Item) -> String {
+    format(&settings.articlelist_format, width, &|specifier| {
+        let value = match specifier {
+            'i' => index.to_string(),
+            'f' => format!(
+                "{}{}",
+                if item.unread { 'N' } else { ' ' },
+                if item.flags.is_empty() { ' ' } else { '!' }
+            ),
+            'D' => {
+                // Items without a date get a blank of the same width, so that columns still line
+                // up.
+                let date = item
+                    .date
+                    .map_or_else(Local::now, |date| date.with_timezone(&Local));
+                let date = date.format(&settings.datetime_format).to_string();
+                match item.date {
+                    Some(_) => date,
+                    None => " ".repeat(date.width()),
+                }
+            }
+            'L' => human_readable_size(item.size()),
+            't' => item.title.clone(),
+            'a' => item.author.clone(),
+            _ => return None,
+        };
+        Some(value)
+    })
+}
+
+/// Format the title of the feed list with `feedlist-title-format`. Specifiers:
+///
+/// - `%N`, `%V`: name and version of the program;
+/// - `%u`: number of feeds with unread items;
+/// - `%t`: number of feeds.
+pub fn feedlist_title(
+    settings: &Settings,
+    width: usize,
+    unread_feeds: usize,
+    total_feeds: usize,
+) -> String {
+    format(&settings.feedlist_title_format, width, &|specifier| {
+        let value = match specifier {
+            'u' => unread_feeds.to_string(),
+            't' => total_feeds.to_string(),
+            _ => return program_info(specifier),
+        };
+        Some(value)
+    })
+}
+
+/// Format the title of the item list with `articlelist-title-format`. Specifiers:
+///
+/// - `%N`, `%V`: name and version of the program;
+/// - `%T`: title of the feed;
+/// - `%U`: URL of the feed;
+/// - `%u`, `%t`: number of unread items, and of all of them.
+pub fn articlelist_title(settings: &Settings, width: usize, feed: &Feed) -> String {
+    format(&settings.articlelist_title_format, width, &|specifier| {
+        let value = match specifier {
+            'T' => feed.display_title().to_string(),
+            'U' => feed.url.clone(),
+            'u' => feed.unread_count().to_string(),
+            't' => feed.total_count().to_string(),
+            _ => return program_info(specifier),
+        };
+        Some(value)
+    })
+}
+
+/// Format the title of the article view with `itemview-title-format`. Specifiers:
+///
+/// - `%N`, `%V`: name and version of the program;
+/// - `%T`: title of the item;
+/// - `%F`: title of the feed;
+/// - `%u`, `%t`: number of unread items in the feed, and of all of them.
+pub fn itemview_title(settings: &Settings, width: usize, feed: &Feed, item: &Item) -> String {
+    format(&settings.itemview_title_format, width, &|specifier| {
+        let value = match specifier {
+            'T' => item.title.clone(),
+            'F' => feed.display_title().to_string(),
+            'u' => feed.unread_count().to_string(),
+            't' => feed.total_count().to_string(),
+            _ => return program_info(specifier),
+        };
+        Some(value)
+    })
+}
+
+/// Values of the specifiers that all title formats have.
+fn program_info(specifier: char) -> Option<String> {
+    match specifier {
+        'N' => Some(PROGRAM_NAME.to_string()),
+        'V' => Some(PROGRAM_VERSION.to_string()),
+        _ => None,
+    }
+}
+
+/// Format `size` the way Newsboat does: as-is below a thousand, and in thousands otherwise.
+fn human_readable_size(size: usize) -> String {
+    if size < 1000 {
+        size.to_string()
+    } else {
+        format!("{:.1}K", size as f64 / 1000.0)
+    }
+}
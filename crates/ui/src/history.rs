@@ -0,0 +1,129 @@
+//! Lines previously entered into the command line and search prompts.
+
+use std::{cell::RefCell, fs, io, path::Path, rc::Rc};
+
+/// Name of the file in which the command line history is kept.
+pub const CMDLINE_FILE: &str = "history.cmdline";
+
+/// Name of the file in which the search history is kept.
+pub const SEARCH_FILE: &str = "history.search";
+
+/// How many entries a history keeps unless `history-limit` says otherwise.
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Lines entered into a prompt, oldest first.
+#[derive(Debug, Clone)]
+pub struct History {
+    /// The lines, oldest first.
+    entries: Vec<String>,
+
+    /// How many lines to keep. Older ones are forgotten.
+    limit: usize,
+}
+
+impl Default for History {
+    fn default() -> History {
+        History::new(DEFAULT_LIMIT)
+    }
+}
+
+impl History {
+    /// Create an empty history that keeps at most `limit` lines. With a limit of zero, nothing is
+    /// kept at all.
+    pub fn new(limit: usize) -> History {
+        History {
+            entries: vec![],
+            limit,
+        }
+    }
+
+    /// The lines, oldest first.
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Remember `line` as the newest one.
+    ///
+    /// Like in shells, blank lines and repeats of the newest line aren't remembered.
+    pub fn add(&mut self, line: &str) {
+        if line.trim().is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        self.enforce_limit();
+    }
+
+    /// Keep at most `limit` lines from now on, forgetting the oldest ones that are over it.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.enforce_limit();
+    }
+
+    /// Read the lines from file at `path`, one per line, in addition to those we have. A file that
+    /// doesn't exist is an empty history.
+    pub fn load_file(&mut self, path: &Path) -> io::Result<()> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let mut entries = text
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        entries.append(&mut self.entries);
+        self.entries = entries;
+        self.enforce_limit();
+        Ok(())
+    }
+
+    /// Write the lines to file at `path`, one per line, replacing whatever was there.
+    pub fn write_file(&self, path: &Path) -> io::Result<()> {
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(entry);
+            text.push('\n');
+        }
+        fs::write(path, text)
+    }
+
+    /// Forget the oldest lines that are over the limit.
+    fn enforce_limit(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.limit);
+        self.entries.drain(..excess);
+    }
+}
+
+/// The histories of the prompts on the status bar. Each prompt has its own, shared by the status
+/// bars of all the dialogs.
+#[derive(Debug, Clone, Default)]
+pub struct Histories {
+    /// Lines entered into the command line.
+    pub cmdline: Rc<RefCell<History>>,
+
+    /// Lines entered into the search prompt.
+    pub search: Rc<RefCell<History>>,
+}
+
+impl Histories {
+    /// Create empty histories that keep at most `limit` lines each.
+    pub fn new(limit: usize) -> Histories {
+        Histories {
+            cmdline: Rc::new(RefCell::new(History::new(limit))),
+            search: Rc::new(RefCell::new(History::new(limit))),
+        }
+    }
+
+    /// Make each history keep at most `limit` lines from now on.
+    pub fn set_limit(&self, limit: usize) {
+        for (history, _) in self.files() {
+            history.borrow_mut().set_limit(limit);
+        }
+    }
+
+    /// The histories along with the names of files in which they're saved.
+    pub fn files(&self) -> [(&Rc<RefCell<History>>, &'static str); 2] {
+        [(&self.cmdline, CMDLINE_FILE), (&self.search, SEARCH_FILE)]
+    }
+}
@@ -0,0 +1,811 @@
+//! Turning HTML of articles into styled lines of text.
+//!
+//! This is nowhere near a browser: we don't build a DOM, and tags that aren't closed are assumed
+//! to end where it makes sense for the tags around them. That is enough for what feeds usually
+//! contain: paragraphs, headings, emphasis, lists, quotes, code, tables, images and links. Links
+//! and images are numbered, and listed below the text.
+
+use quick_xml::escape::unescape;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::wrap::wrap;
+
+/// Width of the line that `<hr>` turns into.
+const RULE_WIDTH: usize = 40;
+
+/// What a link points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A web page, from `<a href>`.
+    Link,
+
+    /// A picture, from `<img src>`.
+    Image,
+}
+
+/// A link found in the article.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// Where the link points.
+    pub url: String,
+
+    /// What the link points at.
+    pub kind: LinkKind,
+}
+
+/// An article, ready to be displayed.
+#[derive(Debug, Clone, Default)]
+pub struct Rendered {
+    /// The text, followed by the list of links, if there are any.
+    pub lines: Vec<Line<'static>>,
+
+    /// Links found in the text. A link's number, which the text refers to, is its index plus one.
+    pub links: Vec<Link>,
+}
+
+/// Render `html` into lines of text that are at most `width` columns wide.
+pub fn render(html: &str, width: usize) -> Rendered {
+    let mut renderer = Renderer {
+        width,
+        ..Renderer::default()
+    };
+    let tokens = Tokenizer { rest: html };
+    for token in tokens {
+        match token {
+            Token::Text(text) => renderer.text(&text),
+            Token::Start { name, attributes } => renderer.start(&name, &attributes),
+            Token::End(name) => renderer.end(&name),
+        }
+    }
+    renderer.finish()
+}
+
+/// A piece of HTML.
+#[derive(Debug)]
+enum Token {
+    /// Text between tags, with entities decoded.
+    Text(String),
+
+    /// An opening tag.
+    Start {
+        /// Name of the tag, in lowercase.
+        name: String,
+
+        /// Names of the attributes, in lowercase, and their values, with entities decoded.
+        attributes: Vec<(String, String)>,
+    },
+
+    /// A closing tag, with its name in lowercase.
+    End(String),
+}
+
+/// Splits HTML into tokens. Comments, doctypes, scripts and stylesheets are skipped.
+struct Tokenizer<'a> {
+    /// HTML that hasn't been tokenized yet.
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+
+            if let Some(after) = self.rest.strip_prefix('<') {
+                if let Some(comment) = after.strip_prefix("!--") {
+                    self.rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+                    continue;
+                }
+                if after.starts_with('!') || after.starts_with('?') {
+                    self.rest = skip_past(after, ">");
+                    continue;
+                }
+                if let Some(after) = after.strip_prefix('/') {
+                    if after.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                        let name = tag_name(after);
+                        self.rest = skip_past(after, ">");
+                        return Some(Token::End(name));
+                    }
+                }
+                if after.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                    let (name, attributes, rest) = start_tag(after);
+                    self.rest = rest;
+                    if name == "script" || name == "style" {
+                        // Lowercasing ASCII doesn't move anything, so offsets stay the same.
+                        let closing = format!("</{}", name);
+                        self.rest = match rest.to_ascii_lowercase().find(&closing) {
+                            Some(start) => skip_past(&rest[start..], ">"),
+                            None => "",
+                        };
+                        continue;
+                    }
+                    return Some(Token::Start { name, attributes });
+                }
+            }
+
+            // A `<` that doesn't start a tag is just text.
+            let end = self
+                .rest
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| *c == '<')
+                .map_or(self.rest.len(), |(end, _)| end);
+            let text = decode_entities(&self.rest[..end]);
+            self.rest = &self.rest[end..];
+            return Some(Token::Text(text));
+        }
+    }
+}
+
+/// What's left of `text` after the first `pattern`, or nothing if there isn't one.
+fn skip_past<'a>(text: &'a str, pattern: &str) -> &'a str {
+    text.find(pattern)
+        .map_or("", |start| &text[start + pattern.len()..])
+}
+
+/// Name of the tag that `text` starts with, in lowercase.
+fn tag_name(text: &str) -> String {
+    text.chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Parse an opening tag from `text`, which starts right after the `<`. Returns the tag's name,
+/// its attributes and the text after the tag.
+fn start_tag(text: &str) -> (String, Vec<(String, String)>, &str) {
+    let name = tag_name(text);
+    let mut rest = &text[name.len()..];
+    let mut attributes = vec![];
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            rest = after;
+            break;
+        }
+
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || "=>/".contains(c))
+            .unwrap_or(rest.len());
+        if name_end == 0 {
+            // A stray `=`.
+            rest = &rest[1..];
+            continue;
+        }
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = "";
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let quoted = &after[1..];
+                    let end = quoted.find(quote).unwrap_or(quoted.len());
+                    value = &quoted[..end];
+                    rest = quoted.get(end + 1..).unwrap_or("");
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(after.len());
+                    value = &after[..end];
+                    rest = &after[end..];
+                }
+            }
+        }
+        attributes.push((name, decode_entities(value)));
+    }
+    (name, attributes, rest)
+}
+
+/// Replace character references in `text` with the characters. Ampersands that don't start a
+/// reference are left alone, since HTML in the wild is full of them.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        // The longest named references are about thirty characters long.
+        let reference = rest
+            .find(';')
+            .filter(|end| *end < 40)
+            .map(|end| &rest[..=end]);
+        match reference.and_then(|reference| Some((reference, unescape(reference).ok()?))) {
+            Some((reference, character)) => {
+                decoded.push_str(&character);
+                rest = &rest[reference.len()..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Value of the attribute called `name`, if the tag has it.
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(attribute, _)| attribute == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// What's put at the start of each line inside a list item or a quote.
+#[derive(Debug)]
+struct Prefix {
+    /// Prefix of the first line, e.g. the bullet of a list item.
+    first: String,
+
+    /// Prefix of the rest of the lines, e.g. spaces as wide as the bullet.
+    rest: String,
+
+    /// Has the first line been output already?
+    used: bool,
+}
+
+/// A list that's being rendered.
+#[derive(Debug)]
+struct List {
+    /// Number of the next item of an ordered list; `None` for unordered lists.
+    next_number: Option<usize>,
+
+    /// Number of prefixes that were there before the list started. The ones after that belong to
+    /// its items.
+    prefixes: usize,
+}
+
+/// A table that's being rendered. Cells are plain text; their looks are lost.
+#[derive(Debug, Default)]
+struct Table {
+    /// Rows so far, including the one that's being filled in.
+    rows: Vec<Row>,
+}
+
+/// A row of a table.
+#[derive(Debug)]
+struct Row {
+    /// Text of the cells so far.
+    cells: Vec<String>,
+
+    /// Is this row all `<th>`?
+    header: bool,
+}
+
+/// Keeps track of where we are in the HTML while it's turned into lines.
+#[derive(Debug, Default)]
+struct Renderer {
+    /// Width of the lines, in columns.
+    width: usize,
+
+    /// Lines that are done.
+    lines: Vec<Line<'static>>,
+
+    /// The line that's being built, without prefixes.
+    line: Vec<Span<'static>>,
+
+    /// Prefixes of open list items and quotes, outermost first.
+    prefixes: Vec<Prefix>,
+
+    /// Open lists, outermost first.
+    lists: Vec<List>,
+
+    /// For each open quote, the number of prefixes there were before it started.
+    quotes: Vec<usize>,
+
+    /// Number of open elements that make text bold.
+    bold: usize,
+
+    /// Number of open elements that make text italic.
+    italic: usize,
+
+    /// Number of open elements that underline text.
+    underlined: usize,
+
+    /// Number of open elements that cross text out.
+    crossed_out: usize,
+
+    /// Number of open `<pre>`s. Whitespace isn't collapsed inside them.
+    pre: usize,
+
+    /// Is the next newline the one right after `<pre>`, which should be ignored?
+    pre_start: bool,
+
+    /// Was there whitespace after the last word? It's output only if another word follows.
+    space: bool,
+
+    /// Style of the text around that whitespace.
+    space_style: Style,
+
+    /// Targets of open `<a>`s, if they have any.
+    anchors: Vec<Option<String>>,
+
+    /// Links found so far.
+    links: Vec<Link>,
+
+    /// Number of open tables. Tables in tables are flattened into the cells of the outer one.
+    table_depth: usize,
+
+    /// The outermost open table.
+    table: Table,
+}
+
+impl Renderer {
+    fn start(&mut self, name: &str, attributes: &[(String, String)]) {
+        if self.table_depth > 0 && !matches!(name, "table" | "tr" | "td" | "th" | "a" | "img") {
+            self.space = true;
+            return;
+        }
+
+        match name {
+            "p" | "dl" | "figure" => self.paragraph_break(),
+            "div" | "section" | "article" | "header" | "footer" | "address" | "figcaption"
+            | "dt" | "dd" => self.break_line(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.paragraph_break();
+                self.bold += 1;
+            }
+            "br" => self.new_line(),
+            "hr" => {
+                self.break_line();
+                self.push("-".repeat(RULE_WIDTH));
+                self.new_line();
+            }
+            "pre" => {
+                self.paragraph_break();
+                self.pre += 1;
+                self.pre_start = true;
+            }
+            "blockquote" => {
+                self.paragraph_break();
+                self.quotes.push(self.prefixes.len());
+                self.prefixes.push(Prefix::new("> ", "> "));
+            }
+
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.paragraph_break();
+                } else {
+                    self.break_line();
+                    // Give the bullet of the item that contains this list a line of its own.
+                    if self.prefixes.last().is_some_and(|prefix| !prefix.used) {
+                        self.new_line();
+                    }
+                }
+                let next_number = if name == "ol" {
+                    let start = attribute(attributes, "start").and_then(|start| start.parse().ok());
+                    Some(start.unwrap_or(1))
+                } else {
+                    None
+                };
+                self.lists.push(List {
+                    next_number,
+                    prefixes: self.prefixes.len(),
+                });
+            }
+            "li" => {
+                self.break_line();
+                let marker = match self.lists.last_mut() {
+                    Some(list) => {
+                        // The previous item might not have been closed.
+                        self.prefixes.truncate(list.prefixes);
+                        match list.next_number {
+                            Some(ref mut number) => {
+                                let marker = format!("{}. ", number);
+                                *number = number.saturating_add(1);
+                                marker
+                            }
+                            None => String::from("* "),
+                        }
+                    }
+                    None => String::from("* "),
+                };
+                let indent = " ".repeat(marker.width());
+                self.prefixes.push(Prefix::new(&marker, &indent));
+            }
+
+            "b" | "strong" => self.bold += 1,
+            "i" | "em" | "cite" | "dfn" | "var" => self.italic += 1,
+            "u" | "ins" => self.underlined += 1,
+            "s" | "strike" | "del" => self.crossed_out += 1,
+
+            "a" => self
+                .anchors
+                .push(attribute(attributes, "href").map(String::from)),
+            "img" => {
+                if let Some(url) = attribute(attributes, "src").filter(|url| !url.is_empty()) {
+                    let number = self.add_link(url, LinkKind::Image);
+                    let placeholder = match attribute(attributes, "alt") {
+                        Some(alt) if !alt.trim().is_empty() => {
+                            format!("[image {} ({})]", number, alt.trim())
+                        }
+                        _ => format!("[image {}]", number),
+                    };
+                    self.text(&placeholder);
+                }
+            }
+
+            "table" => {
+                if self.table_depth == 0 {
+                    self.paragraph_break();
+                    self.table = Table::default();
+                } else {
+                    self.space = true;
+                }
+                self.table_depth += 1;
+            }
+            "tr" if self.table_depth == 1 => {
+                self.table.rows.push(Row {
+                    cells: vec![],
+                    header: true,
+                });
+            }
+            "td" | "th" if self.table_depth == 1 => {
+                if self.table.rows.is_empty() {
+                    self.start("tr", attributes);
+                }
+                if let Some(row) = self.table.rows.last_mut() {
+                    row.cells.push(String::new());
+                    row.header &= name == "th";
+                }
+                self.space = false;
+            }
+            "tr" | "td" | "th" => self.space = true,
+
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        if self.table_depth > 0 && !matches!(name, "table" | "a") {
+            return;
+        }
+
+        match name {
+            "p" | "dl" | "figure" => self.paragraph_break(),
+            "div" | "section" | "article" | "header" | "footer" | "address" | "figcaption"
+            | "dt" | "dd" => self.break_line(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.bold = self.bold.saturating_sub(1);
+                self.paragraph_break();
+            }
+            "pre" => {
+                self.pre = self.pre.saturating_sub(1);
+                self.paragraph_break();
+            }
+            "blockquote" => {
+                self.break_line();
+                // The empty line after the last paragraph of the quote would have a quote marker.
+                if self.last_line_is_blank() {
+                    self.lines.pop();
+                }
+                if let Some(prefixes) = self.quotes.pop() {
+                    self.prefixes.truncate(prefixes);
+                }
+                self.paragraph_break();
+            }
+
+            "ul" | "ol" => {
+                self.break_line();
+                if let Some(list) = self.lists.pop() {
+                    self.prefixes.truncate(list.prefixes);
+                }
+                if self.lists.is_empty() {
+                    self.paragraph_break();
+                }
+            }
+            "li" => {
+                self.break_line();
+                if let Some(list) = self.lists.last() {
+                    self.prefixes.truncate(list.prefixes);
+                }
+            }
+
+            "b" | "strong" => self.bold = self.bold.saturating_sub(1),
+            "i" | "em" | "cite" | "dfn" | "var" => self.italic = self.italic.saturating_sub(1),
+            "u" | "ins" => self.underlined = self.underlined.saturating_sub(1),
+            "s" | "strike" | "del" => self.crossed_out = self.crossed_out.saturating_sub(1),
+
+            "a" => {
+                if let Some(Some(url)) = self.anchors.pop() {
+                    if !url.is_empty() {
+                        let number = self.add_link(&url, LinkKind::Link);
+                        // Keep the reference next to the text it's for.
+                        let space = self.space;
+                        self.space = false;
+                        self.text(&format!("[{}]", number));
+                        self.space = space;
+                    }
+                }
+            }
+
+            "table" => {
+                self.table_depth = self.table_depth.saturating_sub(1);
+                if self.table_depth == 0 {
+                    self.render_table();
+                    self.paragraph_break();
+                } else {
+                    self.space = true;
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Output `text` that appeared between tags.
+    fn text(&mut self, text: &str) {
+        if self.table_depth > 0 {
+            let cell = match self
+                .table
+                .rows
+                .last_mut()
+                .and_then(|row| row.cells.last_mut())
+            {
+                Some(cell) => cell,
+                // Text outside of cells isn't displayed by browsers either.
+                None => return,
+            };
+            for c in text.chars() {
+                if c.is_ascii_whitespace() {
+                    self.space = true;
+                } else {
+                    if self.space && !cell.is_empty() {
+                        cell.push(' ');
+                    }
+                    self.space = false;
+                    cell.push(c);
+                }
+            }
+            return;
+        }
+
+        if self.pre > 0 {
+            let mut text = text;
+            if self.pre_start {
+                text = text.strip_prefix('\n').unwrap_or(text);
+                self.pre_start = false;
+            }
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    self.new_line();
+                }
+                if !line.is_empty() {
+                    self.push(line.replace('\t', "        "));
+                }
+            }
+            return;
+        }
+
+        let mut words = String::new();
+        for c in text.chars() {
+            // Only ASCII whitespace collapses; non-breaking spaces stay.
+            if c.is_ascii_whitespace() {
+                self.space = true;
+                self.space_style = self.style();
+            } else {
+                if self.space {
+                    if !words.is_empty() {
+                        words.push(' ');
+                    } else if !self.line.is_empty() {
+                        // Spaces between differently styled words go with the text they were in.
+                        self.push_styled(String::from(" "), self.space_style);
+                    }
+                }
+                self.space = false;
+                words.push(c);
+            }
+        }
+        if !words.is_empty() {
+            self.push(words);
+        }
+    }
+
+    /// Append `text` to the current line, in the current style.
+    fn push(&mut self, text: String) {
+        self.push_styled(text, self.style());
+    }
+
+    /// Append `text` to the current line, in the given `style`.
+    fn push_styled(&mut self, text: String, style: Style) {
+        match self.line.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push_str(&text),
+            _ => self.line.push(Span::styled(text, style)),
+        }
+    }
+
+    /// Style of the text in the currently open elements.
+    fn style(&self) -> Style {
+        let mut style = Style::default();
+        for (count, modifier) in [
+            (self.bold, Modifier::BOLD),
+            (self.italic, Modifier::ITALIC),
+            (self.underlined, Modifier::UNDERLINED),
+            (self.crossed_out, Modifier::CROSSED_OUT),
+        ] {
+            if count > 0 {
+                style = style.add_modifier(modifier);
+            }
+        }
+        style
+    }
+
+    /// Finish the current line, even if it's empty.
+    fn new_line(&mut self) {
+        let mut first = String::new();
+        let mut rest = String::new();
+        for prefix in &mut self.prefixes {
+            first.push_str(if prefix.used {
+                &prefix.rest
+            } else {
+                &prefix.first
+            });
+            rest.push_str(&prefix.rest);
+            prefix.used = true;
+        }
+        if self.line.is_empty() {
+            first.truncate(first.trim_end().len());
+        }
+
+        let width = self.width.saturating_sub(first.width());
+        let wrapped = wrap(&self.line, width);
+        for (i, mut line) in wrapped.into_iter().enumerate() {
+            let prefix = if i == 0 { &first } else { &rest };
+            if !prefix.is_empty() {
+                line.spans.insert(0, Span::raw(prefix.clone()));
+            }
+            self.lines.push(line);
+        }
+        self.line.clear();
+        self.space = false;
+    }
+
+    /// Finish the current line, unless there's nothing on it.
+    fn break_line(&mut self) {
+        if !self.line.is_empty() {
+            self.new_line();
+        }
+    }
+
+    /// Finish the current line, and separate whatever follows with an empty line.
+    fn paragraph_break(&mut self) {
+        self.break_line();
+        if !self.last_line_is_blank() {
+            let prefix = self
+                .prefixes
+                .iter()
+                .map(|prefix| prefix.rest.as_str())
+                .collect::<String>();
+            self.lines.push(Line::from(prefix.trim_end().to_string()));
+        }
+    }
+
+    /// Is the last line empty, or just a quote marker? If there are no lines, it's as good as
+    /// empty, since we don't want to start with an empty line.
+    fn last_line_is_blank(&self) -> bool {
+        match self.lines.last() {
+            Some(line) => line.spans.iter().all(|span| {
+                span.content
+                    .trim_matches(|c| c == '>' || c == ' ')
+                    .is_empty()
+            }),
+            None => true,
+        }
+    }
+
+    /// Number `url` and return its number. Links to the same place share a number.
+    fn add_link(&mut self, url: &str, kind: LinkKind) -> usize {
+        let index = match self
+            .links
+            .iter()
+            .position(|link| link.url == url && link.kind == kind)
+        {
+            Some(index) => index,
+            None => {
+                self.links.push(Link {
+                    url: url.to_string(),
+                    kind,
+                });
+                self.links.len() - 1
+            }
+        };
+        index + 1
+    }
+
+    /// Output the table that just ended, with columns lined up and the header separated from
+    /// the rest.
+    fn render_table(&mut self) {
+        let table = std::mem::take(&mut self.table);
+        let mut widths: Vec<usize> = vec![];
+        for row in &table.rows {
+            for (column, cell) in row.cells.iter().enumerate() {
+                match widths.get_mut(column) {
+                    Some(width) => *width = (*width).max(cell.width()),
+                    None => widths.push(cell.width()),
+                }
+            }
+        }
+
+        for (i, row) in table.rows.iter().enumerate() {
+            if row.cells.is_empty() {
+                continue;
+            }
+            let cells = widths.iter().enumerate().map(|(column, width)| {
+                let cell = row.cells.get(column).map_or("", String::as_str);
+                format!("{}{}", cell, " ".repeat(width - cell.width()))
+            });
+            let text = cells.collect::<Vec<_>>().join(" | ");
+            self.bold += usize::from(row.header);
+            self.push(text.trim_end().to_string());
+            self.bold -= usize::from(row.header);
+            self.new_line();
+
+            let is_last = i + 1 == table.rows.len();
+            if row.header && !is_last {
+                let rule = widths
+                    .iter()
+                    .map(|width| "-".repeat(*width))
+                    .collect::<Vec<_>>()
+                    .join("-+-");
+                self.push(rule);
+                self.new_line();
+            }
+        }
+    }
+
+    /// Finish rendering, and list the links below the text.
+    fn finish(mut self) -> Rendered {
+        self.break_line();
+        while !self.lines.is_empty() && self.last_line_is_blank() {
+            self.lines.pop();
+        }
+
+        if !self.links.is_empty() {
+            self.prefixes.clear();
+            if !self.lines.is_empty() {
+                self.new_line();
+            }
+            self.push_styled(String::from("Links:"), Style::default());
+            self.new_line();
+            for i in 0..self.links.len() {
+                let link = &self.links[i];
+                let kind = match link.kind {
+                    LinkKind::Link => "link",
+                    LinkKind::Image => "image",
+                };
+                let text = format!("[{}]: {} ({})", i + 1, link.url, kind);
+                self.push_styled(text, Style::default());
+                self.new_line();
+            }
+        }
+
+        Rendered {
+            lines: self.lines,
+            links: self.links,
+        }
+    }
+}
+
+impl Prefix {
+    fn new(first: &str, rest: &str) -> Prefix {
+        Prefix {
+            first: first.to_string(),
+            rest: rest.to_string(),
+            used: false,
+        }
+    }
+}
@@ -1,43 +1,87 @@
-//! List of feeds.
+//! List of items of a single feed.
 
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Layout},
-    style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{List, ListItem, Paragraph, Wrap},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, path::Path, rc::Rc};
 
 use crate::app::App;
-use crate::event::Key;
+use crate::cache::Cache;
+use crate::command::{self, Command};
+use crate::config::Config;
+use crate::event::Event;
+use crate::feed::{Feed, Item};
 use crate::form_action::FormAction;
-use crate::item_view::ItemView;
+use crate::format;
+use crate::history::Histories;
+use crate::item_view::{self, ItemView};
+use crate::keymap::{Context, Operation};
 use crate::stateful_list::StatefulList;
+use crate::status_bar::StatusBar;
+use crate::theme::Element;
 
 /// List of items.
 pub struct ItemList {
+    /// The feed whose items are displayed.
+    feed: Rc<RefCell<Feed>>,
+
     /// The state of the itemlist (what items it contains, what item is currently selected)
-    state: StatefulList,
+    state: StatefulList<Rc<RefCell<Item>>>,
+
+    /// Where the items' read state is saved.
+    cache: Rc<Cache>,
+
+    /// Keys for the most useful operations, shown below the list.
+    hints: String,
+
+    /// Messages and the command line.
+    status_bar: StatusBar,
+
+    /// Number of rows the list took up when it was last drawn.
+    list_height: usize,
+
+    /// Settings, key bindings and colors.
+    config: Rc<Config>,
 }
 
 impl ItemList {
-    /// Create example itemlist.
-    pub fn new() -> ItemList {
-        let mut state = StatefulList::new();
-
-        state.items = vec![
-            "   1    Apr 28   3.9K  NVidia acquires Mellanox".to_string(),
-            "   2    Apr 28    591  [$] Dumping kernel data structure with BPF".to_string(),
-            "   3    Apr 28    971  Wooden server rack".to_string(),
-            "   4    Apr 28   2.2K  Trouble fully setting up baremetal homelab".to_string(),
-            "   5    Apr 28    548  Looking for a very small server with 2 plus hot swap 3.5 inch driver I can install linux on.".to_string(),
-            "   6    Apr 28   1.7K  VLAN and iOT devices".to_string(),
-        ];
-        state.state.select(Some(0));
-
-        ItemList { state }
+    /// Create an itemlist displaying items of `feed`, whose state will be saved to `cache`. The
+    /// itemlist looks and behaves the way `config` says. Commands and searches are recalled from
+    /// and added to `histories`.
+    pub fn new(
+        feed: Rc<RefCell<Feed>>,
+        cache: Rc<Cache>,
+        config: Rc<Config>,
+        histories: Histories,
+    ) -> ItemList {
+        let state = StatefulList::with_items(visible_items(&feed.borrow(), &config));
+        ItemList {
+            feed,
+            state,
+            cache,
+            hints: hints(&config),
+            status_bar: StatusBar::new(histories),
+            list_height: 0,
+            config,
+        }
+    }
+
+    /// Show the feed's current items, keeping the same item selected if it's still there.
+    fn refresh(&mut self) {
+        let items = visible_items(&self.feed.borrow(), &self.config);
+        let selected = self
+            .state
+            .selected()
+            .and_then(|selected| items.iter().position(|item| Rc::ptr_eq(item, selected)))
+            .or_else(|| {
+                let index = self.state.state.selected()?;
+                Some(index.min(items.len().checked_sub(1)?))
+            });
+        self.state.items = items;
+        self.state.state.select(selected);
     }
 }
 
@@ -46,81 +90,166 @@ impl<B: Backend> FormAction<B> for ItemList {
         let layout = Layout::default()
             .constraints(
                 [
-                    Constraint::Length(1), // title
-                    Constraint::Min(0),    // itemlist
-                    Constraint::Length(1), // hints
-                    Constraint::Length(1), // command line (TODO: implement)
+                    Constraint::Length(1),                                            // title
+                    Constraint::Min(0),                                               // itemlist
+                    Constraint::Length(self.config.settings.show_keymap_hint.into()), // hints
+                    Constraint::Length(1),                                            // status bar
                 ]
                 .as_ref(),
             )
             .split(frame.size());
+        let theme = &self.config.theme;
 
         {
-            let title = Span::styled(
-                "Newsboat 2.20 (ну, почти) - Example Feed (0 unread, 0 total)",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .bg(Color::Blue)
-                    .add_modifier(Modifier::BOLD),
+            let feed = self.feed.borrow();
+            let title = format::articlelist_title(
+                &self.config.settings,
+                usize::from(layout[0].width),
+                &feed,
             );
             let paragraph = Paragraph::new(title)
                 .wrap(Wrap { trim: false })
-                .style(Style::default().bg(Color::Blue));
+                .style(theme.style(Element::Title));
             frame.render_widget(paragraph, layout[0]);
         }
 
         {
+            self.list_height = usize::from(layout[1].height);
+            let visible = self.state.visible_range(self.list_height);
+            let start = visible.start;
+            let selected = self.state.state.selected();
+            let width = usize::from(layout[1].width);
             let list = List::new(
-                self.state
-                    .items
+                self.state.items[visible]
                     .iter()
-                    .map(|text| {
-                        ListItem::new(Span::styled(
-                            text.to_string(),
-                            Style::default().fg(Color::Green),
-                        ))
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let i = start + i;
+                        let item = item.borrow();
+                        let row = format::item_row(&self.config.settings, width, i + 1, &item);
+                        ListItem::new(row).style(theme.list_row(selected == Some(i), item.unread))
                     })
                     .collect::<Vec<_>>(),
-            )
-            .highlight_style(
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
             );
 
-            frame.render_stateful_widget(list, layout[1], &mut self.state.state);
+            let mut state = ListState::default().with_selected(selected.map(|i| i - start));
+            frame.render_stateful_widget(list, layout[1], &mut state);
         }
 
         {
-            let hints = Span::styled(
-                "q:Quit UP:Previous DOWN:Next ENTER:Open",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .bg(Color::Blue)
-                    .add_modifier(Modifier::BOLD),
-            );
-            let paragraph = Paragraph::new(hints)
+            let paragraph = Paragraph::new(self.hints.as_str())
                 .wrap(Wrap { trim: false })
-                .style(Style::default().bg(Color::Blue));
+                .style(theme.style(Element::Hint));
             frame.render_widget(paragraph, layout[2]);
         }
+
+        self.status_bar
+            .draw(frame, layout[3], theme.style(Element::Info));
     }
 
-    fn handle_key(&mut self, key: Key, app: &mut App<B>) {
-        match key {
-            Key::Char(c) => match c {
-                'q' => app.quit_current_formaction(),
+    fn handle_event(&mut self, event: &Event, app: &mut App<B>) {
+        if let Event::FeedFetched { .. } = event {
+            // We can't tell which feeds query feeds take items from, so just refresh every time.
+            self.refresh();
+            app.mark_dirty();
+        }
+    }
+
+    fn context(&self) -> Context {
+        Context::ArticleList
+    }
 
-                '\n' => app.add_formaction(Rc::new(RefCell::new(ItemView::new()))),
+    fn status_bar(&mut self) -> &mut StatusBar {
+        &mut self.status_bar
+    }
 
-                _ => {}
+    fn run_command(&mut self, command: &Command, _app: &mut App<B>) -> command::Outcome {
+        match (command.name.as_str(), command.arguments.as_slice()) {
+            ("goto", arguments) => command::goto(&mut self.state, arguments, "article", |item| {
+                item.borrow().title.clone()
+            }),
+            ("search", arguments) => {
+                command::search(&mut self.state, arguments, "article", |item| {
+                    item.borrow().title.clone()
+                })
+            }
+
+            ("save", [path]) => match self.state.selected() {
+                Some(item) => {
+                    item_view::save_article(&self.feed.borrow(), &item.borrow(), Path::new(path))
+                }
+                None => Err("there's no article to save".into()),
             },
 
-            Key::Up => self.state.previous(),
+            _ => Err(format!("`{}` can't be used here", command.name).into()),
+        }
+    }
+
+    fn set_config(&mut self, config: Rc<Config>) {
+        self.hints = hints(&config);
+        self.config = config;
+        self.refresh();
+    }
 
-            Key::Down => self.state.next(),
+    fn handle_operation(&mut self, operation: Operation, count: Option<usize>, app: &mut App<B>) {
+        match operation {
+            Operation::Quit => app.quit_current_formaction(),
+
+            Operation::Open => {
+                if let Some(item) = self.state.selected() {
+                    let item_view = ItemView::new(
+                        self.feed.clone(),
+                        item.clone(),
+                        &self.cache,
+                        app.config(),
+                        app.histories(),
+                    );
+                    app.add_formaction(Rc::new(RefCell::new(item_view)));
+                }
+            }
+
+            Operation::Up => {
+                self.state.previous_by(count.unwrap_or(1));
+            }
+
+            Operation::Down => {
+                self.state.next_by(count.unwrap_or(1));
+            }
+
+            // Entries are numbered from 1 on screen.
+            Operation::Home => self
+                .state
+                .select_nearest(count.unwrap_or(1).saturating_sub(1)),
+
+            Operation::End => self
+                .state
+                .select_nearest(count.map_or(usize::MAX, |n| n.saturating_sub(1))),
+
+            Operation::Center => self.state.center(self.list_height),
 
             _ => {}
         }
     }
 }
+
+/// Keys for the most useful operations of the itemlist, according to `config`.
+fn hints(config: &Config) -> String {
+    config.keymap.hints(
+        Context::ArticleList,
+        &[
+            (Operation::Quit, "Quit"),
+            (Operation::Up, "Previous"),
+            (Operation::Down, "Next"),
+            (Operation::Open, "Open"),
+        ],
+    )
+}
+
+/// Items of `feed` that should be listed, given the `config`.
+fn visible_items(feed: &Feed, config: &Config) -> Vec<Rc<RefCell<Item>>> {
+    feed.items
+        .iter()
+        .filter(|item| config.settings.show_read_articles || item.borrow().unread)
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,663 @@
+//! Mapping of keys to operations, separately for each dialog.
+//!
+//! Keys are named the way Newsboat names them: a printable character stands for itself, `^X` is
+//! Ctrl-X, `M-x` is Alt-x, and special keys have upper-case names like `ENTER`, `UP` or `F1`.
+//!
+//! A binding can also be a sequence of keys, like `gg` or `zz`. Inside a sequence, special keys
+//! are written in angle brackets, e.g. `g<ENTER>`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, slice,
+};
+
+use crate::event::Key;
+
+/// Something the user can ask a dialog to do by pressing a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Close the current dialog, or quit if it's the feed list.
+    Quit,
+
+    /// Quit, no matter which dialog is open.
+    HardQuit,
+
+    /// Open the selected feed or item.
+    Open,
+
+    /// Fetch the selected feed.
+    Reload,
+
+    /// Fetch all feeds.
+    ReloadAll,
+
+    /// Select the previous entry, or scroll up.
+    Up,
+
+    /// Select the next entry, or scroll down.
+    Down,
+
+    /// Select the first entry, or scroll to the top.
+    Home,
+
+    /// Select the last entry, or scroll to the bottom.
+    End,
+
+    /// Scroll so that the selected entry is in the middle of the screen.
+    Center,
+
+    /// Open the article's link in the browser.
+    OpenInBrowser,
+
+    /// Start typing a command.
+    Cmdline,
+
+    /// Start typing a search: the next entry, or line of the article, that contains it is
+    /// selected.
+    OpenSearch,
+
+    /// Switch to the next open dialog.
+    NextDialog,
+
+    /// Switch to the previous open dialog.
+    PrevDialog,
+
+    /// Start a macro: the next key picks which one.
+    MacroPrefix,
+}
+
+/// Operations along with their names in the config file.
+const OPERATIONS: &[(Operation, &str)] = &[
+    (Operation::Quit, "quit"),
+    (Operation::HardQuit, "hard-quit"),
+    (Operation::Open, "open"),
+    (Operation::Reload, "reload"),
+    (Operation::ReloadAll, "reload-all"),
+    (Operation::Up, "up"),
+    (Operation::Down, "down"),
+    (Operation::Home, "home"),
+    (Operation::End, "end"),
+    (Operation::Center, "center"),
+    (Operation::OpenInBrowser, "open-in-browser"),
+    (Operation::Cmdline, "cmdline"),
+    (Operation::OpenSearch, "open-search"),
+    (Operation::NextDialog, "next-dialog"),
+    (Operation::PrevDialog, "prev-dialog"),
+    (Operation::MacroPrefix, "macro-prefix"),
+];
+
+impl Operation {
+    /// The operation called `name` in the config file.
+    pub fn from_name(name: &str) -> Option<Operation> {
+        OPERATIONS
+            .iter()
+            .find(|(_, op_name)| *op_name == name)
+            .map(|(operation, _)| *operation)
+    }
+
+    /// Names of all the operations in the config file.
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        OPERATIONS.iter().map(|(_, name)| *name)
+    }
+
+    /// The name of this operation in the config file.
+    pub fn name(self) -> &'static str {
+        OPERATIONS
+            .iter()
+            .find(|(operation, _)| *operation == self)
+            .map(|(_, name)| *name)
+            .expect("every operation should have a name")
+    }
+}
+
+/// A set of bindings. Each dialog has its own, and the global one applies to all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Context {
+    /// Bindings that apply to every dialog, unless the dialog's own bindings say otherwise.
+    Global,
+
+    /// The list of feeds.
+    FeedList,
+
+    /// The list of items of a feed.
+    ArticleList,
+
+    /// The article view.
+    Article,
+}
+
+/// Contexts along with their names in the config file.
+const CONTEXTS: &[(Context, &str)] = &[
+    (Context::Global, "global"),
+    (Context::FeedList, "feedlist"),
+    (Context::ArticleList, "articlelist"),
+    (Context::Article, "article"),
+];
+
+/// The name of `context` in the config file.
+fn context_name(context: Context) -> &'static str {
+    CONTEXTS
+        .iter()
+        .find(|(named_context, _)| *named_context == context)
+        .map(|(_, name)| *name)
+        .expect("every context should have a name")
+}
+
+impl Context {
+    /// The context called `name` in the config file.
+    pub fn from_name(name: &str) -> Option<Context> {
+        CONTEXTS
+            .iter()
+            .find(|(_, context_name)| *context_name == name)
+            .map(|(context, _)| *context)
+    }
+}
+
+/// Special keys along with their names in the config file.
+const KEY_NAMES: &[(Key, &str)] = &[
+    (Key::Char('\n'), "ENTER"),
+    (Key::Char(' '), "SPACE"),
+    (Key::Char('\t'), "TAB"),
+    (Key::BackTab, "BACKTAB"),
+    (Key::Backspace, "BACKSPACE"),
+    (Key::Left, "LEFT"),
+    (Key::Right, "RIGHT"),
+    (Key::Up, "UP"),
+    (Key::Down, "DOWN"),
+    (Key::Home, "HOME"),
+    (Key::End, "END"),
+    (Key::PageUp, "PPAGE"),
+    (Key::PageDown, "NPAGE"),
+    (Key::Delete, "DEL"),
+    (Key::Insert, "INS"),
+    (Key::Esc, "ESC"),
+];
+
+/// Parse the config file's `name` of a key.
+pub fn parse_key(name: &str) -> Option<Key> {
+    if let Some((key, _)) = KEY_NAMES.iter().find(|(_, key_name)| *key_name == name) {
+        return Some(key.clone());
+    }
+
+    if let Some(number) = name.strip_prefix('F') {
+        if let Ok(number) = number.parse::<u8>() {
+            return (1..=12).contains(&number).then_some(Key::F(number));
+        }
+    }
+
+    // Our Ctrl and Alt keys hold the base key, and Ctrl combinations are reported in lower case.
+    if let Some(c) = name.strip_prefix('^').and_then(single_char) {
+        return Some(Key::Ctrl(Box::new(Key::Char(c.to_ascii_lowercase()))));
+    }
+    if let Some(base) = name.strip_prefix("M-") {
+        return Some(Key::Alt(Box::new(parse_key(base)?)));
+    }
+
+    single_char(name).map(Key::Char)
+}
+
+/// Parse the config file's `name` of a key sequence.
+///
+/// A name that's a key on its own, like `UP`, is a sequence of one key; otherwise, each character
+/// is a key, except for `^X` and names in angle brackets.
+pub fn parse_keys(name: &str) -> Option<Vec<Key>> {
+    if let Some(key) = parse_key(name) {
+        return Some(vec![key]);
+    }
+
+    let mut keys = vec![];
+    let mut rest = name;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                keys.push(parse_key(&rest[1..end])?);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        if c == '^' {
+            if let Some(base) = rest[1..].chars().next() {
+                keys.push(Key::Ctrl(Box::new(Key::Char(base.to_ascii_lowercase()))));
+                rest = &rest[1 + base.len_utf8()..];
+                continue;
+            }
+        }
+        keys.push(Key::Char(c));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    if keys.is_empty() {
+        None
+    } else {
+        Some(keys)
+    }
+}
+
+/// The only character of `text`, if there's exactly one.
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// The config file's name of `key`; `parse_key` turns it back into the same key.
+pub fn key_name(key: &Key) -> String {
+    if let Some((_, name)) = KEY_NAMES.iter().find(|(named_key, _)| named_key == key) {
+        return name.to_string();
+    }
+
+    match key {
+        Key::F(number) => format!("F{}", number),
+        Key::Char(c) => c.to_string(),
+        Key::Ctrl(base) => match **base {
+            Key::Char(c) => format!("^{}", c.to_ascii_uppercase()),
+            ref base => format!("^{}", key_name(base)),
+        },
+        Key::Alt(base) => format!("M-{}", key_name(base)),
+        // Named keys were handled above.
+        _ => format!("{:?}", key),
+    }
+}
+
+/// The config file's name of a sequence of `keys`; `parse_keys` turns it back into the same keys.
+pub fn keys_name(keys: &[Key]) -> String {
+    if let [key] = keys {
+        return key_name(key);
+    }
+
+    let mut name = String::new();
+    for key in keys {
+        let key_name = key_name(key);
+        let is_ctrl_char = matches!(key, Key::Ctrl(base) if matches!(**base, Key::Char(_)));
+        if key_name.chars().count() == 1 || is_ctrl_char {
+            name.push_str(&key_name);
+        } else {
+            name.push_str(&format!("<{}>", key_name));
+        }
+    }
+    name
+}
+
+/// What a sequence of keys means in some context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lookup {
+    /// The keys perform an operation.
+    Bound(Operation),
+
+    /// The keys are the beginning of a longer binding. If they perform an operation on their own
+    /// too, it's the one inside.
+    Prefix(Option<Operation>),
+
+    /// The keys are the macro prefix followed by the key of a macro, which performs these
+    /// operations.
+    Macro(Vec<Operation>),
+
+    /// The keys mean nothing.
+    Unbound,
+}
+
+impl Lookup {
+    /// The single operation that the keys perform on their own, if any.
+    pub fn operation(&self) -> Option<Operation> {
+        match *self {
+            Lookup::Bound(operation) | Lookup::Prefix(Some(operation)) => Some(operation),
+            Lookup::Prefix(None) | Lookup::Macro(_) | Lookup::Unbound => None,
+        }
+    }
+}
+
+/// Mapping of keys to operations, separately for each dialog.
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    /// What each key sequence does in each context.
+    bindings: HashMap<(Context, Vec<Key>), Operation>,
+
+    /// Key sequences unbound in a dialog's context. They mean nothing there even if they're bound
+    /// globally.
+    unbound: HashSet<(Context, Vec<Key>)>,
+
+    /// Operations that each macro performs, by the key that follows the macro prefix.
+    macros: HashMap<Key, Vec<Operation>>,
+}
+
+impl Default for KeyMap {
+    /// Bindings that Newsboat has out of the box, as far as we support them.
+    fn default() -> KeyMap {
+        let mut keymap = KeyMap::empty();
+        let defaults = [
+            ("q", Operation::Quit, Context::Global),
+            ("Q", Operation::HardQuit, Context::Global),
+            ("ENTER", Operation::Open, Context::Global),
+            ("UP", Operation::Up, Context::Global),
+            ("DOWN", Operation::Down, Context::Global),
+            ("HOME", Operation::Home, Context::Global),
+            ("gg", Operation::Home, Context::Global),
+            ("END", Operation::End, Context::Global),
+            ("G", Operation::End, Context::Global),
+            ("zz", Operation::Center, Context::Global),
+            (":", Operation::Cmdline, Context::Global),
+            ("/", Operation::OpenSearch, Context::Global),
+            ("^V", Operation::NextDialog, Context::Global),
+            ("^G", Operation::PrevDialog, Context::Global),
+            (",", Operation::MacroPrefix, Context::Global),
+            ("r", Operation::Reload, Context::FeedList),
+            ("R", Operation::ReloadAll, Context::FeedList),
+            ("o", Operation::OpenInBrowser, Context::Article),
+        ];
+        for (name, operation, context) in defaults {
+            let keys = parse_keys(name).expect("default bindings should use valid key names");
+            keymap.bindings.insert((context, keys), operation);
+        }
+        keymap
+    }
+}
+
+impl KeyMap {
+    /// A keymap without any bindings.
+    pub fn empty() -> KeyMap {
+        KeyMap {
+            bindings: HashMap::new(),
+            unbound: HashSet::new(),
+            macros: HashMap::new(),
+        }
+    }
+
+    /// Make `keys` perform `operation` in `context`, or in every context if it's `None`.
+    pub fn bind(&mut self, keys: Vec<Key>, operation: Operation, context: Option<Context>) {
+        match context {
+            Some(context) => {
+                self.unbound.remove(&(context, keys.clone()));
+                self.bindings.insert((context, keys), operation);
+            }
+            None => {
+                for (context, _) in CONTEXTS {
+                    self.unbound.remove(&(*context, keys.clone()));
+                    self.bindings.insert((*context, keys.clone()), operation);
+                }
+            }
+        }
+    }
+
+    /// Make `keys` do nothing in `context`, or in every context if it's `None`.
+    ///
+    /// Unbinding keys in a dialog's context hides their global binding in that dialog, but the
+    /// other dialogs still have it.
+    pub fn unbind(&mut self, keys: &[Key], context: Option<Context>) {
+        match context {
+            Some(context) => {
+                self.bindings.remove(&(context, keys.to_vec()));
+                if context != Context::Global {
+                    self.unbound.insert((context, keys.to_vec()));
+                }
+            }
+            None => {
+                self.bindings
+                    .retain(|(_, bound_keys), _| bound_keys != keys);
+                self.unbound
+                    .retain(|(_, unbound_keys)| unbound_keys != keys);
+            }
+        }
+    }
+
+    /// Remove all bindings. Macros are kept, but without a key bound to `macro-prefix`, they can't
+    /// be used.
+    pub fn unbind_all(&mut self) {
+        self.bindings.clear();
+        self.unbound.clear();
+    }
+
+    /// Make the macro prefix followed by `key` perform `operations`, replacing the macro that
+    /// this key had before.
+    pub fn set_macro(&mut self, key: Key, operations: Vec<Operation>) {
+        self.macros.insert(key, operations);
+    }
+
+    /// The operation that `keys` are bound to in `context`, or globally if the context doesn't
+    /// have its own binding and the keys weren't unbound there.
+    fn bound(&self, context: Context, keys: &[Key]) -> Option<Operation> {
+        let key = (context, keys.to_vec());
+        match self.bindings.get(&key) {
+            Some(operation) => Some(*operation),
+            None if self.unbound.contains(&key) => None,
+            None => self.bindings.get(&(Context::Global, key.1)).copied(),
+        }
+    }
+
+    /// Does a binding of `keys` in `bound_context` apply in `context`?
+    fn applies(&self, bound_context: Context, keys: &[Key], context: Context) -> bool {
+        bound_context == context
+            || (bound_context == Context::Global
+                && !self.unbound.contains(&(context, keys.to_vec())))
+    }
+
+    /// What `keys` mean in `context`. The context's own binding takes precedence over the global
+    /// one.
+    pub fn lookup(&self, context: Context, keys: &[Key]) -> Lookup {
+        if let Some((first, rest)) = keys.split_first() {
+            if self.bound(context, slice::from_ref(first)) == Some(Operation::MacroPrefix) {
+                return match rest {
+                    [] => Lookup::Prefix(None),
+                    [key] => match self.macros.get(key) {
+                        Some(operations) => Lookup::Macro(operations.clone()),
+                        None => Lookup::Unbound,
+                    },
+                    _ => Lookup::Unbound,
+                };
+            }
+        }
+
+        let bound = self.bound(context, keys);
+        let is_prefix = self.bindings.keys().any(|(bound_context, bound_keys)| {
+            self.applies(*bound_context, bound_keys, context)
+                && bound_keys.len() > keys.len()
+                && bound_keys.starts_with(keys)
+        });
+
+        match (bound, is_prefix) {
+            (Some(operation), false) => Lookup::Bound(operation),
+            (bound, true) => Lookup::Prefix(bound),
+            (None, false) => Lookup::Unbound,
+        }
+    }
+
+    /// Commands that make an empty keymap into this one: `bind-key` for each binding,
+    /// `unbind-key` for keys unbound in a dialog, and `macro` for each macro, sorted so that the
+    /// result doesn't change from one call to another.
+    pub fn dump(&self) -> Vec<String> {
+        let mut bindings = self
+            .bindings
+            .iter()
+            .map(|((context, keys), operation)| {
+                format!(
+                    "bind-key {} {} {}",
+                    keys_name(keys),
+                    operation.name(),
+                    context_name(*context)
+                )
+            })
+            .collect::<Vec<_>>();
+        bindings.sort();
+
+        let mut unbound = self
+            .unbound
+            .iter()
+            .map(|(context, keys)| {
+                format!("unbind-key {} {}", keys_name(keys), context_name(*context))
+            })
+            .collect::<Vec<_>>();
+        unbound.sort();
+        bindings.extend(unbound);
+
+        let mut macros = self
+            .macros
+            .iter()
+            .map(|(key, operations)| {
+                let operations = operations
+                    .iter()
+                    .map(|operation| operation.name())
+                    .collect::<Vec<_>>();
+                format!("macro {} {}", key_name(key), operations.join(" ; "))
+            })
+            .collect::<Vec<_>>();
+        macros.sort();
+
+        bindings.extend(macros);
+        bindings
+    }
+
+    /// Keys that perform `operation` in `context`, if there are any.
+    ///
+    /// If several sequences do, the shortest one is picked, and among those, the one whose name
+    /// sorts first, so the answer doesn't change from one call to another.
+    pub fn keys_for(&self, context: Context, operation: Operation) -> Option<Vec<Key>> {
+        self.bindings
+            .iter()
+            .filter(|((bound_context, keys), bound_operation)| {
+                self.applies(*bound_context, keys, context) && **bound_operation == operation
+            })
+            .map(|((_, keys), _)| keys)
+            // A global binding might be overridden by the context's own one.
+            .filter(|keys| self.lookup(context, keys).operation() == Some(operation))
+            .min_by_key(|keys| (keys.len(), keys_name(keys)))
+            .cloned()
+    }
+
+    /// The hints line for a dialog in `context`: the keys for each of the `operations`, along with
+    /// their descriptions. Operations that aren't bound to any key are left out.
+    pub fn hints(&self, context: Context, operations: &[(Operation, &str)]) -> String {
+        operations
+            .iter()
+            .filter_map(|(operation, description)| {
+                let keys = self.keys_for(context, *operation)?;
+                Some(format!("{}:{}", keys_name(&keys), description))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// What to do about a key that the user pressed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dispatch {
+    /// Perform the operation. If the user typed a count before the keys, it's passed along.
+    Perform(Operation, Option<usize>),
+
+    /// Perform the operations of a macro, one after another.
+    Macro(Vec<Operation>),
+
+    /// Wait for more keys.
+    Pending,
+
+    /// The keys don't mean anything, so they were dropped.
+    Unbound,
+}
+
+/// The largest count that can be typed before an operation. Bigger ones are cut down to it, so
+/// that a stray row of digits doesn't make an operation repeat for ages.
+pub const MAX_COUNT: usize = 99_999;
+
+/// Keys that the user typed so far, which don't make up a binding yet, and the count typed before
+/// them.
+#[derive(Debug, Clone, Default)]
+pub struct PendingKeys {
+    /// The count, e.g. 5 in `5j`.
+    count: Option<usize>,
+
+    /// Keys typed after the count.
+    keys: Vec<Key>,
+}
+
+impl PendingKeys {
+    /// Add `key` to the pending ones, and find out what they mean in `context`.
+    ///
+    /// Digits typed before any other keys make up a count, unless a digit is bound to something
+    /// on its own. A zero can't start a count. Esc cancels whatever was typed so far.
+    pub fn push(&mut self, key: Key, keymap: &KeyMap, context: Context) -> Dispatch {
+        if key == Key::Esc && !self.is_empty() {
+            self.clear();
+            return Dispatch::Unbound;
+        }
+
+        if let Key::Char(c) = key {
+            if let Some(digit) = c.to_digit(10) {
+                let starts_count = digit != 0 || self.count.is_some();
+                if self.keys.is_empty()
+                    && starts_count
+                    && keymap.lookup(context, slice::from_ref(&key)) == Lookup::Unbound
+                {
+                    let count = self.count.unwrap_or(0);
+                    let count = count.saturating_mul(10).saturating_add(digit as usize);
+                    self.count = Some(count.min(MAX_COUNT));
+                    return Dispatch::Pending;
+                }
+            }
+        }
+
+        self.keys.push(key);
+        match keymap.lookup(context, &self.keys) {
+            Lookup::Bound(operation) => {
+                let count = self.count;
+                self.clear();
+                Dispatch::Perform(operation, count)
+            }
+            Lookup::Macro(operations) => {
+                self.clear();
+                Dispatch::Macro(operations)
+            }
+            Lookup::Prefix(_) => Dispatch::Pending,
+            Lookup::Unbound => {
+                self.clear();
+                Dispatch::Unbound
+            }
+        }
+    }
+
+    /// The user stopped typing halfway through a sequence. If the keys typed so far perform an
+    /// operation on their own, it's performed; otherwise, they're dropped.
+    ///
+    /// A lone count doesn't time out.
+    pub fn time_out(&mut self, keymap: &KeyMap, context: Context) -> Dispatch {
+        if self.keys.is_empty() {
+            return Dispatch::Pending;
+        }
+
+        let operation = keymap.lookup(context, &self.keys).operation();
+        let count = self.count;
+        self.clear();
+        match operation {
+            Some(operation) => Dispatch::Perform(operation, count),
+            None => Dispatch::Unbound,
+        }
+    }
+
+    /// Are we in the middle of a key sequence, i.e. should we wait for it to time out?
+    pub fn is_incomplete_sequence(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Was nothing typed yet?
+    pub fn is_empty(&self) -> bool {
+        self.count.is_none() && self.keys.is_empty()
+    }
+
+    /// Forget the keys typed so far.
+    pub fn clear(&mut self) {
+        self.count = None;
+        self.keys.clear();
+    }
+}
+
+impl fmt::Display for PendingKeys {
+    /// The count and the keys, the way the user typed them.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(count) = self.count {
+            write!(f, "{}", count)?;
+        }
+        if !self.keys.is_empty() {
+            write!(f, "{}", keys_name(&self.keys))?;
+        }
+        Ok(())
+    }
+}
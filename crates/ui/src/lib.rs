@@ -1,8 +1,28 @@
 pub mod app;
+pub mod cache;
+pub mod command;
+pub mod completion;
+pub mod config;
 pub mod event;
+pub mod example;
+pub mod feed;
 pub mod feed_list;
+pub mod filter;
 pub mod form_action;
+pub mod format;
+pub mod history;
+pub mod html;
 pub mod item_list;
 pub mod item_view;
+pub mod keymap;
+pub mod opml;
+pub mod parser;
+pub mod reload;
 pub mod stateful_list;
+pub mod status_bar;
+pub mod theme;
+pub mod timer;
+pub mod tokenizer;
+pub mod urls;
 pub mod widgets;
+pub mod wrap;
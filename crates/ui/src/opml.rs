@@ -0,0 +1,212 @@
+//! Importing subscriptions from OPML 1.0 and 2.0, and exporting them to OPML 2.0.
+//!
+//! Outlines that have a feed URL become subscriptions. Outlines without one are folders: their
+//! titles become tags of all the feeds nested inside them. OPML 2.0's `category` attribute is
+//! turned into tags too, which is also how tags are exported.
+
+use chrono::Utc;
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+use std::{cell::RefCell, error, fmt, fs, io, path::Path, rc::Rc};
+
+use crate::feed::Feed;
+use crate::urls::{self, Subscription};
+
+/// Reasons why an OPML document couldn't be imported.
+#[derive(Debug)]
+pub enum OpmlError {
+    /// The file couldn't be read.
+    Io(io::Error),
+
+    /// The document is not well-formed XML.
+    Xml(quick_xml::Error),
+
+    /// The document is XML, but not OPML. Contains the name of the root element.
+    NotOpml(String),
+}
+
+impl fmt::Display for OpmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OpmlError::Io(e) => write!(f, "couldn't read the OPML file: {}", e),
+            OpmlError::Xml(e) => write!(f, "the OPML file is not valid XML: {}", e),
+            OpmlError::NotOpml(root) => {
+                write!(f, "the document is not OPML (root element is `{}`)", root)
+            }
+        }
+    }
+}
+
+impl error::Error for OpmlError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            OpmlError::Io(e) => Some(e),
+            OpmlError::Xml(e) => Some(e),
+            OpmlError::NotOpml(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for OpmlError {
+    fn from(e: io::Error) -> OpmlError {
+        OpmlError::Io(e)
+    }
+}
+
+impl From<quick_xml::Error> for OpmlError {
+    fn from(e: quick_xml::Error) -> OpmlError {
+        OpmlError::Xml(e)
+    }
+}
+
+/// Read subscriptions from the OPML file at `path`.
+pub fn import_file(path: &Path) -> Result<Vec<Subscription>, OpmlError> {
+    let xml = fs::read_to_string(path)?;
+    import_str(&xml)
+}
+
+/// Read subscriptions from OPML document `xml`.
+pub fn import_str(xml: &str) -> Result<Vec<Subscription>, OpmlError> {
+    let mut reader = Reader::from_str(xml);
+    let mut subscriptions: Vec<Subscription> = vec![];
+    let mut seen_root = false;
+
+    // For each open outline, innermost last: the folder's title, or `None` if it's not a folder.
+    let mut folders: Vec<Option<String>> = vec![];
+
+    loop {
+        let (start, has_children) = match reader.read_event()? {
+            Event::Start(start) => (start, true),
+            Event::Empty(start) => (start, false),
+
+            Event::End(end) => {
+                if end.name().as_ref() == b"outline" {
+                    folders.pop();
+                }
+                continue;
+            }
+
+            Event::Eof => break,
+
+            _ => continue,
+        };
+
+        if !seen_root {
+            seen_root = true;
+            if start.name().as_ref() != b"opml" {
+                let root = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                return Err(OpmlError::NotOpml(root));
+            }
+        }
+        if start.name().as_ref() != b"outline" {
+            continue;
+        }
+
+        let url = attribute(&start, &["xmlUrl", "xmlurl"])?
+            .or(match attribute(&start, &["type"])?.as_deref() {
+                // OPML 1.0 exports of some readers use `url`, but so do plain links, which aren't
+                // feeds.
+                Some("rss") | Some("atom") => attribute(&start, &["url"])?,
+                _ => None,
+            })
+            .filter(|url| !url.is_empty());
+
+        match url {
+            Some(url) => {
+                let mut tags = folders.iter().flatten().cloned().collect::<Vec<_>>();
+                if let Some(categories) = attribute(&start, &["category"])? {
+                    tags.extend(categories_to_tags(&categories));
+                }
+                let mut subscription = Subscription::new(url);
+                subscription.add_tags(tags);
+                urls::merge(&mut subscriptions, vec![subscription]);
+                if has_children {
+                    folders.push(None);
+                }
+            }
+
+            None => {
+                if has_children {
+                    let title = attribute(&start, &["title", "text"])?.filter(|t| !t.is_empty());
+                    folders.push(title);
+                }
+            }
+        }
+    }
+
+    if !seen_root {
+        return Err(OpmlError::NotOpml(String::new()));
+    }
+    Ok(subscriptions)
+}
+
+/// Value of the first of `names` attributes that `element` has.
+fn attribute(element: &BytesStart, names: &[&str]) -> Result<Option<String>, quick_xml::Error> {
+    for name in names {
+        if let Some(attribute) = element.try_get_attribute(*name)? {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Turn OPML 2.0 `category` attribute, a comma-separated list of slash-separated paths, into tags.
+///
+/// Each path becomes a single tag, e.g. `/news/tech` becomes "news/tech".
+fn categories_to_tags(categories: &str) -> impl Iterator<Item = String> + '_ {
+    categories
+        .split(',')
+        .map(|category| category.trim().trim_matches('/'))
+        .filter(|category| !category.is_empty())
+        .map(String::from)
+}
+
+/// Render `feeds` as an OPML 2.0 document. Query feeds are left out, since other readers can't
+/// make sense of them.
+pub fn export(feeds: &[Rc<RefCell<Feed>>]) -> String {
+    let mut opml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <opml version=\"2.0\">\n  \
+          <head>\n    \
+            <title>newsboat-tui-rs subscriptions</title>\n    \
+            <dateCreated>{}</dateCreated>\n  \
+          </head>\n  \
+          <body>\n",
+        Utc::now().to_rfc2822()
+    );
+
+    for feed in feeds {
+        let feed = feed.borrow();
+        if feed.query.is_some() {
+            continue;
+        }
+
+        let title = escape(feed.display_title());
+        opml.push_str(&format!(
+            "    <outline type=\"rss\" xmlUrl=\"{}\" title=\"{}\" text=\"{}\"",
+            escape(&feed.url),
+            title,
+            title
+        ));
+        if !feed.link.is_empty() {
+            opml.push_str(&format!(" htmlUrl=\"{}\"", escape(&feed.link)));
+        }
+        if !feed.tags.is_empty() {
+            // Commas separate categories, so they can't be part of one.
+            let categories = feed
+                .tags
+                .iter()
+                .map(|tag| format!("/{}", tag.replace(',', " ")))
+                .collect::<Vec<_>>()
+                .join(",");
+            opml.push_str(&format!(" category=\"{}\"", escape(&categories)));
+        }
+        opml.push_str("/>\n");
+    }
+
+    opml.push_str("  </body>\n</opml>\n");
+    opml
+}
@@ -0,0 +1,527 @@
+//! Parsing RSS 0.9x, 1.0 and 2.0, and Atom 1.0 feeds.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use quick_xml::{escape::escape, events::Event, NsReader};
+use std::{cell::RefCell, error, fmt, fs, io, path::Path, rc::Rc};
+
+use crate::feed::{Enclosure, Feed, Item};
+
+const ATOM_NS: &str = "http://www.w3.org/2005/Atom";
+const CONTENT_NS: &str = "http://purl.org/rss/1.0/modules/content/";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const RSS_090_NS: &str = "http://my.netscape.com/rdf/simple/0.9/";
+const RSS_10_NS: &str = "http://purl.org/rss/1.0/";
+const XHTML_NS: &str = "http://www.w3.org/1999/xhtml";
+
+/// Reasons why a feed couldn't be parsed.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The file couldn't be read.
+    Io(io::Error),
+
+    /// The document is not well-formed XML.
+    Xml(quick_xml::Error),
+
+    /// The document is XML, but not a feed we know. Contains the name of the root element.
+    UnknownFormat(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "couldn't read the feed: {}", e),
+            ParseError::Xml(e) => write!(f, "the feed is not valid XML: {}", e),
+            ParseError::UnknownFormat(root) => write!(
+                f,
+                "the document is neither RSS nor Atom (root element is `{}`)",
+                root
+            ),
+        }
+    }
+}
+
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::Xml(e) => Some(e),
+            ParseError::UnknownFormat(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> ParseError {
+        ParseError::Io(e)
+    }
+}
+
+impl From<quick_xml::Error> for ParseError {
+    fn from(e: quick_xml::Error) -> ParseError {
+        ParseError::Xml(e)
+    }
+}
+
+/// Parse the feed stored in the file at `path`.
+///
+/// The feed's URL is left empty; it's up to the caller to fill it in.
+pub fn parse_file(path: &Path) -> Result<Feed, ParseError> {
+    let xml = fs::read_to_string(path)?;
+    parse_str(&xml)
+}
+
+/// Parse the feed from `xml`.
+///
+/// The feed's URL is left empty; it's up to the caller to fill it in.
+pub fn parse_str(xml: &str) -> Result<Feed, ParseError> {
+    let root = parse_document(xml)?;
+
+    match (root.namespace.as_deref(), root.name.as_str()) {
+        // RSS 0.91, 0.92 and 2.0 don't use namespaces.
+        (None, "rss") => {
+            let channel = root
+                .child(None, "channel")
+                .ok_or_else(|| ParseError::UnknownFormat(root.name.clone()))?;
+            Ok(parse_rss(
+                channel,
+                channel.children_named(None, "item"),
+                None,
+            ))
+        }
+
+        // RSS 0.90 and 1.0 are RDF, and their items are siblings of the channel.
+        (Some(RDF_NS), "RDF") => {
+            let namespace = [RSS_10_NS, RSS_090_NS]
+                .iter()
+                .find(|ns| root.child(Some(ns), "channel").is_some())
+                .ok_or_else(|| ParseError::UnknownFormat(root.name.clone()))?;
+            let channel = root
+                .child(Some(namespace), "channel")
+                .expect("we just checked that the channel exists");
+            Ok(parse_rss(
+                channel,
+                root.children_named(Some(namespace), "item"),
+                Some(namespace),
+            ))
+        }
+
+        (Some(ATOM_NS), "feed") => Ok(parse_atom(&root)),
+
+        _ => Err(ParseError::UnknownFormat(root.name)),
+    }
+}
+
+/// Build a feed out of RSS `channel` and its `items`, all in the given `namespace`.
+fn parse_rss<'a>(
+    channel: &Element,
+    items: impl Iterator<Item = &'a Element>,
+    namespace: Option<&str>,
+) -> Feed {
+    let items = items
+        .map(|item| {
+            let text_of = |name| item.child_text(namespace, name);
+
+            let url = text_of("link");
+            let guid = item
+                .child(namespace, "guid")
+                .map(Element::text)
+                .or_else(|| item.attribute(Some(RDF_NS), "about").map(String::from))
+                .filter(|guid| !guid.is_empty())
+                .unwrap_or_else(|| url.clone());
+
+            // RSS 2.0 wants an e-mail address there, but people put their names, too.
+            let mut author = text_of("author");
+            if author.is_empty() {
+                author = item.child_text(Some(DC_NS), "creator");
+            }
+
+            let date = item
+                .child(namespace, "pubDate")
+                .and_then(|date| parse_rfc822_date(&date.text()))
+                .or_else(|| {
+                    item.child(Some(DC_NS), "date")
+                        .and_then(|date| parse_rfc3339_date(&date.text()))
+                });
+
+            let mut content = item.child_text(Some(CONTENT_NS), "encoded");
+            if content.is_empty() {
+                content = text_of("description");
+            }
+
+            let enclosure = item.child(namespace, "enclosure").and_then(|enclosure| {
+                Some(Enclosure {
+                    url: enclosure.attribute(None, "url")?.to_string(),
+                    mime_type: enclosure.attribute(None, "type").unwrap_or("").to_string(),
+                    length: enclosure
+                        .attribute(None, "length")
+                        .and_then(|length| length.trim().parse().ok()),
+                })
+            });
+
+            Rc::new(RefCell::new(Item {
+                title: text_of("title"),
+                url,
+                guid,
+                author,
+                date,
+                content,
+                unread: true,
+                enclosure,
+                ..Item::default()
+            }))
+        })
+        .collect();
+
+    Feed {
+        title: channel.child_text(namespace, "title"),
+        link: channel.child_text(namespace, "link"),
+        items,
+        ..Feed::default()
+    }
+}
+
+/// Build a feed out of Atom `feed` element.
+fn parse_atom(feed: &Element) -> Feed {
+    let feed_author = atom_author(feed);
+
+    let items = feed
+        .children_named(Some(ATOM_NS), "entry")
+        .map(|entry| {
+            let url = atom_link(entry, "alternate").unwrap_or_default();
+
+            let mut guid = entry.child_text(Some(ATOM_NS), "id");
+            if guid.is_empty() {
+                guid = url.clone();
+            }
+
+            let author =
+                atom_author(entry).unwrap_or_else(|| feed_author.clone().unwrap_or_default());
+
+            let date = entry
+                .child(Some(ATOM_NS), "published")
+                .or_else(|| entry.child(Some(ATOM_NS), "updated"))
+                .and_then(|date| parse_rfc3339_date(&date.text()));
+
+            let content = entry
+                .child(Some(ATOM_NS), "content")
+                .or_else(|| entry.child(Some(ATOM_NS), "summary"))
+                .map(atom_text)
+                .unwrap_or_default();
+
+            let enclosure = entry
+                .children_named(Some(ATOM_NS), "link")
+                .find(|link| link.attribute(None, "rel") == Some("enclosure"))
+                .and_then(|link| {
+                    Some(Enclosure {
+                        url: link.attribute(None, "href")?.to_string(),
+                        mime_type: link.attribute(None, "type").unwrap_or("").to_string(),
+                        length: link
+                            .attribute(None, "length")
+                            .and_then(|length| length.trim().parse().ok()),
+                    })
+                });
+
+            Rc::new(RefCell::new(Item {
+                title: entry
+                    .child(Some(ATOM_NS), "title")
+                    .map(atom_text)
+                    .unwrap_or_default(),
+                url,
+                guid,
+                author,
+                date,
+                content,
+                unread: true,
+                enclosure,
+                ..Item::default()
+            }))
+        })
+        .collect();
+
+    Feed {
+        title: feed
+            .child(Some(ATOM_NS), "title")
+            .map(atom_text)
+            .unwrap_or_default(),
+        link: atom_link(feed, "alternate").unwrap_or_default(),
+        items,
+        ..Feed::default()
+    }
+}
+
+/// Name of the first author of an Atom feed or entry, if any.
+fn atom_author(element: &Element) -> Option<String> {
+    element
+        .child(Some(ATOM_NS), "author")
+        .map(|author| author.child_text(Some(ATOM_NS), "name"))
+        .filter(|name| !name.is_empty())
+}
+
+/// Target of the first Atom link with relation `rel`.
+///
+/// Links without a `rel` attribute are "alternate" links.
+fn atom_link(element: &Element, rel: &str) -> Option<String> {
+    element
+        .children_named(Some(ATOM_NS), "link")
+        .find(|link| link.attribute(None, "rel").unwrap_or("alternate") == rel)
+        .and_then(|link| link.attribute(None, "href"))
+        .map(String::from)
+}
+
+/// Contents of an Atom text construct (title, summary, content).
+///
+/// XHTML content is serialized back into a string, without the wrapping `div`.
+fn atom_text(element: &Element) -> String {
+    match element.attribute(None, "type") {
+        Some("xhtml") => match element.child(Some(XHTML_NS), "div") {
+            Some(div) => div.inner_xml(),
+            None => element.inner_xml(),
+        }
+        .trim()
+        .to_string(),
+        _ => element.text(),
+    }
+}
+
+/// Parse RFC 822 date, as used by RSS 2.0.
+fn parse_rfc822_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(date.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Parse RFC 3339 date, as used by Atom and Dublin Core.
+///
+/// Dublin Core allows dates without time, so these are accepted too, as midnight UTC.
+fn parse_rfc3339_date(date: &str) -> Option<DateTime<Utc>> {
+    let date = date.trim();
+    DateTime::parse_from_rfc3339(date)
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+        })
+}
+
+/// An XML element, with namespaces resolved.
+#[derive(Debug, Default)]
+struct Element {
+    /// Namespace URI, if the element is in a namespace.
+    namespace: Option<String>,
+
+    /// Local name, i.e. without the prefix.
+    name: String,
+
+    /// Attributes, except for namespace declarations.
+    attributes: Vec<Attribute>,
+
+    /// Child elements and text, in document order.
+    children: Vec<Node>,
+}
+
+/// An XML attribute, with namespace resolved.
+#[derive(Debug)]
+struct Attribute {
+    /// Namespace URI, if the attribute is in a namespace. Unprefixed attributes never are.
+    namespace: Option<String>,
+
+    /// Local name, i.e. without the prefix.
+    name: String,
+
+    /// Value, with entities already unescaped.
+    value: String,
+}
+
+/// A piece of XML document.
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+
+    /// Text or CDATA, with entities already unescaped.
+    Text(String),
+}
+
+impl Element {
+    /// Does this element have given namespace and name?
+    fn is(&self, namespace: Option<&str>, name: &str) -> bool {
+        self.namespace.as_deref() == namespace && self.name == name
+    }
+
+    /// First child element with given namespace and name.
+    fn child(&self, namespace: Option<&str>, name: &str) -> Option<&Element> {
+        self.children.iter().find_map(|node| match node {
+            Node::Element(e) if e.is(namespace, name) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// Text of the first child element with given namespace and name, or an empty string if
+    /// there is no such element.
+    fn child_text(&self, namespace: Option<&str>, name: &str) -> String {
+        self.child(namespace, name)
+            .map(Element::text)
+            .unwrap_or_default()
+    }
+
+    /// All child elements with given namespace and name.
+    fn children_named<'a>(
+        &'a self,
+        namespace: Option<&'a str>,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter_map(move |node| match node {
+            Node::Element(e) if e.is(namespace, name) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// Value of the attribute with given namespace and name.
+    fn attribute(&self, namespace: Option<&str>, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.namespace.as_deref() == namespace && a.name == name)
+            .map(|a| a.value.as_str())
+    }
+
+    /// Text directly inside this element, with surrounding whitespace removed.
+    fn text(&self) -> String {
+        let mut result = String::new();
+        for node in &self.children {
+            if let Node::Text(text) = node {
+                result.push_str(text);
+            }
+        }
+        result.trim().to_string()
+    }
+
+    /// Everything inside this element, serialized back into XML. Namespace prefixes are dropped.
+    fn inner_xml(&self) -> String {
+        let mut result = String::new();
+        for node in &self.children {
+            match node {
+                Node::Text(text) => result.push_str(&escape(text)),
+                Node::Element(e) => {
+                    result.push('<');
+                    result.push_str(&e.name);
+                    for attribute in &e.attributes {
+                        result.push_str(&format!(
+                            " {}=\"{}\"",
+                            attribute.name,
+                            escape(&attribute.value)
+                        ));
+                    }
+                    if e.children.is_empty() {
+                        result.push_str("/>");
+                    } else {
+                        result.push('>');
+                        result.push_str(&e.inner_xml());
+                        result.push_str(&format!("</{}>", e.name));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Parse `xml` into a tree, returning the root element.
+fn parse_document(xml: &str) -> Result<Element, ParseError> {
+    let mut reader = NsReader::from_str(xml);
+
+    // Elements that are open at the current point of the document, innermost last.
+    let mut stack: Vec<Element> = vec![];
+    let mut root = None;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => stack.push(element_from(&reader, &start)?),
+
+            Event::Empty(start) => {
+                let element = element_from(&reader, &start)?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(Node::Element(element)),
+                    None => root = Some(element),
+                }
+            }
+
+            Event::End(_) => {
+                // quick-xml checks that tags are balanced, so there's always an element here.
+                if let Some(element) = stack.pop() {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(Node::Element(element)),
+                        None => root = Some(element),
+                    }
+                }
+            }
+
+            Event::Text(text) => {
+                if let Some(parent) = stack.last_mut() {
+                    // Feeds often contain HTML entities that XML doesn't define. Keep the text
+                    // as-is rather than rejecting the whole feed.
+                    let text = match text.unescape() {
+                        Ok(text) => text.into_owned(),
+                        Err(_) => String::from_utf8_lossy(&text).into_owned(),
+                    };
+                    parent.children.push(Node::Text(text));
+                }
+            }
+
+            Event::CData(cdata) => {
+                if let Some(parent) = stack.last_mut() {
+                    let text = String::from_utf8_lossy(&cdata.into_inner()).into_owned();
+                    parent.children.push(Node::Text(text));
+                }
+            }
+
+            Event::Eof => break,
+
+            // Comments, processing instructions and the like don't matter to us.
+            _ => {}
+        }
+    }
+
+    root.ok_or_else(|| ParseError::UnknownFormat(String::new()))
+}
+
+/// Turn an opening tag into an (as yet childless) element.
+fn element_from(
+    reader: &NsReader<&[u8]>,
+    start: &quick_xml::events::BytesStart,
+) -> Result<Element, ParseError> {
+    let (namespace, name) = reader.resolve_element(start.name());
+    let mut element = Element {
+        namespace: namespace_uri(namespace),
+        name: String::from_utf8_lossy(name.as_ref()).into_owned(),
+        ..Element::default()
+    };
+
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        if attribute.key.as_namespace_binding().is_some() {
+            continue;
+        }
+
+        let (namespace, name) = reader.resolve_attribute(attribute.key);
+        element.attributes.push(Attribute {
+            namespace: namespace_uri(namespace),
+            name: String::from_utf8_lossy(name.as_ref()).into_owned(),
+            value: attribute.unescape_value()?.into_owned(),
+        });
+    }
+
+    Ok(element)
+}
+
+/// Namespace URI of a resolved name, if it's in a namespace.
+fn namespace_uri(namespace: quick_xml::name::ResolveResult) -> Option<String> {
+    match namespace {
+        quick_xml::name::ResolveResult::Bound(ns) => {
+            Some(String::from_utf8_lossy(ns.as_ref()).into_owned())
+        }
+        _ => None,
+    }
+}
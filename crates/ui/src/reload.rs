@@ -0,0 +1,116 @@
+//! Fetching feeds in the background.
+//!
+//! Fetching is done by worker threads, which report back through the same channel that delivers
+//! input and signals, so the UI stays responsive. The fetched documents are parsed by whoever
+//! receives the events, since feeds can't be sent between threads.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    error::Error,
+    fs, mem,
+    rc::Rc,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::cache::Cache;
+use crate::event::Event;
+use crate::feed::Feed;
+use crate::parser;
+
+/// How many feeds are fetched at once, unless configured otherwise.
+pub const DEFAULT_THREADS: usize = 4;
+
+/// How long to wait for a server before giving up on it.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fetch `urls` on `threads` worker threads, sending an `Event::FeedFetched` to `events` for each
+/// of them, and `Event::ReloadFinished` once they're all done.
+pub fn spawn(urls: Vec<String>, threads: usize, events: mpsc::Sender<Event>) {
+    let threads = threads.clamp(1, urls.len().max(1));
+    let queue = Arc::new(Mutex::new(urls.into_iter().collect::<VecDeque<_>>()));
+    // The last worker to finish reports that the reload is over.
+    let workers_left = Arc::new(Mutex::new(threads));
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .user_agent(concat!("newsboat-tui-rs/", env!("CARGO_PKG_VERSION")))
+        .build();
+
+    for _ in 0..threads {
+        let queue = queue.clone();
+        let workers_left = workers_left.clone();
+        let agent = agent.clone();
+        let events = events.clone();
+        thread::spawn(move || {
+            loop {
+                let url = match queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front() {
+                    Some(url) => url,
+                    None => break,
+                };
+                let result = fetch(&agent, &url);
+                if events.send(Event::FeedFetched { url, result }).is_err() {
+                    // Nobody's listening anymore, so there's no point in going on.
+                    return;
+                }
+            }
+
+            let mut workers_left = workers_left.lock().unwrap_or_else(|e| e.into_inner());
+            *workers_left -= 1;
+            if *workers_left == 0 {
+                let _ = events.send(Event::ReloadFinished);
+            }
+        });
+    }
+}
+
+/// Download the document at `url`, which can be an HTTP(S) or a `file://` URL.
+fn fetch(agent: &ureq::Agent, url: &str) -> Result<String, String> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return fs::read_to_string(path).map_err(|e| e.to_string());
+    }
+
+    match agent.get(url).call() {
+        Ok(response) => response.into_string().map_err(|e| e.to_string()),
+        Err(ureq::Error::Status(code, response)) => Err(format!(
+            "server responded with {} {}",
+            code,
+            response.status_text()
+        )),
+        Err(ureq::Error::Transport(e)) => Err(e.to_string()),
+    }
+}
+
+/// Replace the contents of `feed` with the freshly fetched `document`, keeping the read state and
+/// old items from the `cache`, and save the result to the cache.
+///
+/// If the document can't be parsed, the feed is left alone.
+pub fn apply(
+    feed: &Rc<RefCell<Feed>>,
+    document: &str,
+    cache: &Cache,
+) -> Result<(), Box<dyn Error>> {
+    let fetched = parser::parse_str(document)?;
+
+    let mut feed = feed.borrow_mut();
+    let old_items = mem::replace(&mut feed.items, fetched.items);
+    feed.title = fetched.title;
+    feed.link = fetched.link;
+    cache.merge_into(&mut feed)?;
+
+    // Items read via query feeds aren't saved right away, so what we have is newer than what the
+    // cache has.
+    for item in &feed.items {
+        let mut item = item.borrow_mut();
+        if let Some(old_item) = old_items.iter().find(|old| old.borrow().guid == item.guid) {
+            let old_item = old_item.borrow();
+            item.unread = old_item.unread;
+            item.flags = old_item.flags.clone();
+        }
+    }
+    cache.save_feed(&feed)?;
+
+    Ok(())
+}
@@ -1,56 +1,129 @@
-//! A list of `String`s, bundled with state from tui-rs.
+//! A list of `T`s, bundled with state from tui-rs.
 
 use ratatui::widgets::ListState;
+use std::ops::Range;
 
-/// A list of `String`s, bundled with state from tui-rs.
-pub struct StatefulList {
+/// A list of `T`s, bundled with state from tui-rs.
+pub struct StatefulList<T> {
     /// List state (from tui-rs crate).
     pub state: ListState,
 
     /// List of items to display.
-    pub items: Vec<String>,
+    pub items: Vec<T>,
 }
 
-impl StatefulList {
+impl<T> Default for StatefulList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> StatefulList<T> {
     /// Create new, empty list.
-    pub fn new() -> StatefulList {
+    pub fn new() -> StatefulList<T> {
         StatefulList {
             state: ListState::default(),
             items: Vec::new(),
         }
     }
 
+    /// Create a list of `items`, with the first one selected.
+    pub fn with_items(items: Vec<T>) -> StatefulList<T> {
+        let mut list = StatefulList::new();
+        list.items = items;
+        if !list.items.is_empty() {
+            list.state.select(Some(0));
+        }
+        list
+    }
+
+    /// Add `item` to the end of the list. If nothing was selected, select it.
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+        if self.state.selected().is_none() {
+            self.state.select(Some(self.items.len() - 1));
+        }
+    }
+
+    /// Currently selected item, if any.
+    pub fn selected(&self) -> Option<&T> {
+        self.state.selected().and_then(|i| self.items.get(i))
+    }
+
     /// Move to the next item. If already at the last one, stay there.
     pub fn next(&mut self) {
-        let i = match self.state.selected() {
-            None => 0,
-
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    self.items.len() - 1
-                } else {
-                    i + 1
-                }
-            }
-        };
-
-        self.state.select(Some(i));
+        self.next_by(1);
     }
 
     /// Move to the previous item. If already at the first one, stay there.
     pub fn previous(&mut self) {
-        let i = match self.state.selected() {
-            None => 0,
-
-            Some(i) => {
-                if i == 0 {
-                    0
-                } else {
-                    i - 1
-                }
-            }
+        self.previous_by(1);
+    }
+
+    /// Move `n` items down, stopping at the last one. If nothing was selected, select the first
+    /// item.
+    pub fn next_by(&mut self, n: usize) {
+        match self.state.selected() {
+            None => self.select_nearest(0),
+            Some(i) => self.select_nearest(i.saturating_add(n)),
+        }
+    }
+
+    /// Move `n` items up, stopping at the first one. If nothing was selected, select the first
+    /// item.
+    pub fn previous_by(&mut self, n: usize) {
+        match self.state.selected() {
+            None => self.select_nearest(0),
+            Some(i) => self.select_nearest(i.saturating_sub(n)),
+        }
+    }
+
+    /// Select the item at `index`, or the last one if there are fewer items.
+    pub fn select_nearest(&mut self, index: usize) {
+        match self.items.len().checked_sub(1) {
+            Some(last) => self.state.select(Some(index.min(last))),
+            None => self.state.select(None),
+        }
+    }
+
+    /// Select the first item after the selected one for which `matches` is true, going around to
+    /// the start of the list if necessary. Returns false if no item matches.
+    pub fn select_next_matching<F: Fn(&T) -> bool>(&mut self, matches: F) -> bool {
+        let start = self.state.selected().map_or(0, |selected| selected + 1);
+        let count = self.items.len();
+        let found = (0..count)
+            .map(|offset| (start + offset) % count)
+            .find(|index| matches(&self.items[*index]));
+        if found.is_some() {
+            self.state.select(found);
+        }
+        found.is_some()
+    }
+
+    /// Scroll the way the list widget would, so that the selected item is visible in a list that's
+    /// `height` rows tall, and return the range of items that are visible then.
+    ///
+    /// Drawing just those items is cheaper than handing all of them to the widget.
+    pub fn visible_range(&mut self, height: usize) -> Range<usize> {
+        let last = match self.items.len().checked_sub(1) {
+            Some(last) if height > 0 => last,
+            _ => return 0..0,
         };
+        let mut start = self.state.offset().min(last);
+        let selected = self.state.selected().unwrap_or(0).min(last);
+        if selected >= start + height {
+            start = selected + 1 - height;
+        } else if selected < start {
+            start = selected;
+        }
+        *self.state.offset_mut() = start;
+        start..(start + height).min(self.items.len())
+    }
 
-        self.state.select(Some(i));
+    /// Scroll so that the selected item is in the middle of a list that's `height` rows tall.
+    pub fn center(&mut self, height: usize) {
+        if let Some(selected) = self.state.selected() {
+            *self.state.offset_mut() = selected.saturating_sub(height / 2);
+        }
     }
 }
@@ -0,0 +1,291 @@
+//! The bottom row of every dialog: messages for the user, the command line, and the search prompt.
+
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::Span,
+    widgets::{Block, Paragraph},
+    Frame,
+};
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::App;
+use crate::command::{self, Command};
+use crate::completion;
+use crate::config::Settings;
+use crate::event::Key;
+use crate::form_action::FormAction;
+use crate::history::Histories;
+use crate::widgets::text_line;
+
+/// How long informational messages stay on screen.
+pub const INFO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long error messages stay on screen. They're more important, so they stay longer.
+pub const ERROR_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A message shown in place of the command line.
+struct Message {
+    /// What the message says.
+    text: String,
+
+    /// When the message should disappear.
+    expires: Instant,
+}
+
+/// A prompt that the status bar can host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    /// The command line, opened with `:`.
+    Command,
+
+    /// The search prompt, opened with `/`.
+    Search,
+}
+
+/// What the status bar did with a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    /// The command line isn't open, so the key is up to the dialog.
+    Ignored(Key),
+
+    /// The key edited the prompt, or closed it.
+    Consumed,
+
+    /// The user entered this command line.
+    Submitted(String),
+
+    /// The user entered this search.
+    Searched(String),
+}
+
+/// The bottom row of a dialog. It shows messages, e.g. the outcome of the last command, until
+/// they time out, and hosts the command line or the search prompt while one is open.
+pub struct StatusBar {
+    /// Message to show, if there's one.
+    message: Option<Message>,
+
+    /// The prompt that's open, if any. It's shown instead of the message, and gets all the keys.
+    open_prompt: Option<Prompt>,
+
+    /// The command line. Its kill ring and history outlive each command, so it's kept even while
+    /// it's closed.
+    command_line: text_line::TextLineState,
+
+    /// The search prompt, kept for the same reasons as the command line.
+    search_line: text_line::TextLineState,
+}
+
+impl StatusBar {
+    /// Create an empty status bar whose command line and search prompt recall and add lines to
+    /// their `histories`.
+    pub fn new(histories: Histories) -> StatusBar {
+        StatusBar {
+            message: None,
+            open_prompt: None,
+            command_line: text_line::TextLineState::with_history(histories.cmdline),
+            search_line: text_line::TextLineState::with_history(histories.search),
+        }
+    }
+
+    /// Show `text` for `timeout`, replacing the current message.
+    pub fn show(&mut self, text: String, timeout: Duration) {
+        self.message = Some(Message {
+            text,
+            expires: Instant::now() + timeout,
+        });
+    }
+
+    /// Show informational message `text`.
+    pub fn info(&mut self, text: String) {
+        self.show(text, INFO_TIMEOUT);
+    }
+
+    /// Show `error`, prefixed with "Error: ".
+    pub fn error(&mut self, error: &str) {
+        self.show(format!("Error: {}", error), ERROR_TIMEOUT);
+    }
+
+    /// The message on screen, unless it timed out.
+    pub fn message(&self) -> Option<&str> {
+        self.message
+            .as_ref()
+            .filter(|message| Instant::now() < message.expires)
+            .map(|message| message.text.as_str())
+    }
+
+    /// Remove the message from the screen.
+    pub fn clear_message(&mut self) {
+        self.message = None;
+    }
+
+    /// When the message will time out, if there's one on screen. The screen should be redrawn
+    /// then.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.message
+            .as_ref()
+            .map(|message| message.expires)
+            .filter(|&expires| Instant::now() < expires)
+    }
+
+    /// Open an empty command line.
+    pub fn open_command_line(&mut self) {
+        self.command_line.clear();
+        self.open_prompt = Some(Prompt::Command);
+    }
+
+    /// Is the command line open?
+    pub fn is_command_line_open(&self) -> bool {
+        self.open_prompt == Some(Prompt::Command)
+    }
+
+    /// Open an empty search prompt.
+    pub fn open_search(&mut self) {
+        self.search_line.clear();
+        self.open_prompt = Some(Prompt::Search);
+    }
+
+    /// Is the search prompt open?
+    pub fn is_search_open(&self) -> bool {
+        self.open_prompt == Some(Prompt::Search)
+    }
+
+    /// Handle `key` pressed by the user. Tab completes the command line from the `settings` and
+    /// `tags`.
+    ///
+    /// While no prompt is open, any key makes the message go away: the user has seen it by now.
+    pub fn handle_key(&mut self, key: Key, settings: &Settings, tags: &[String]) -> Input {
+        let prompt = match self.open_prompt {
+            Some(prompt) => prompt,
+            None => {
+                self.message = None;
+                return Input::Ignored(key);
+            }
+        };
+
+        if prompt == Prompt::Command && (key == Key::Char('\t') || key == Key::BackTab) {
+            self.command_line.complete(key == Key::BackTab, |line| {
+                completion::complete(line, settings, tags)
+            });
+            return Input::Consumed;
+        }
+
+        let line = match prompt {
+            Prompt::Command => &mut self.command_line,
+            Prompt::Search => &mut self.search_line,
+        };
+        match line.handle_key(&key) {
+            text_line::Outcome::Submitted => {
+                self.open_prompt = None;
+                let text = line.text().to_string();
+                match prompt {
+                    Prompt::Command => Input::Submitted(text),
+                    Prompt::Search => Input::Searched(text),
+                }
+            }
+
+            text_line::Outcome::Cancelled => {
+                self.open_prompt = None;
+                Input::Consumed
+            }
+
+            text_line::Outcome::Edited | text_line::Outcome::Ignored => Input::Consumed,
+        }
+    }
+
+    /// Draw the status bar into `area` of the `frame`, looking like `style`.
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect, style: Style) {
+        // On a tiny terminal, the dialog might not leave any room for the status bar.
+        if area.area() == 0 {
+            return;
+        }
+        frame.render_widget(Block::default().style(style), area);
+        let (line, prompt) = match self.open_prompt {
+            Some(Prompt::Command) => (&mut self.command_line, ":"),
+            Some(Prompt::Search) => (&mut self.search_line, "/"),
+            None => {
+                if let Some(message) = self.message() {
+                    frame.render_widget(Paragraph::new(Span::raw(message)), area);
+                }
+                return;
+            }
+        };
+
+        // While the history is searched, the query takes the place of the colon or the slash.
+        let prompt = line.search_prompt().unwrap_or_else(|| String::from(prompt));
+        let prompt_width = prompt.width().min(u16::MAX as usize) as u16;
+        let line_layout = Layout::default()
+            .constraints(
+                [
+                    Constraint::Length(prompt_width), // Prompt
+                    Constraint::Min(0),               // Text input
+                ]
+                .as_ref(),
+            )
+            .direction(Direction::Horizontal)
+            .split(area);
+
+        let paragraph = Paragraph::new(Span::raw(prompt));
+        frame.render_widget(paragraph, line_layout[0]);
+
+        frame.render_stateful_widget(text_line::TextLine::new(), line_layout[1], line);
+        frame.set_cursor(
+            line_layout[1]
+                // x+cursor_offset, with careful type conversions:
+                // - cursor_offset is usize, so we limit it to u16::MAX
+                // - u16+u16 won't fit into u16. Since we just want the cursor at the end of
+                //   the text, we're using saturating addition to put cursor as far right as we
+                //   possibly can
+                .x
+                .saturating_add(line.cursor_display_offset().min(u16::MAX as usize) as u16),
+            line_layout[1].y,
+        );
+    }
+}
+
+/// Let the status bar of `formaction` handle `key`, running the command if the user entered one,
+/// or the `search` command if they entered a search. This is what formactions do with keys unless
+/// they have their own use for them.
+///
+/// Returns the key if neither the status bar nor the command used it.
+pub fn handle_key<B, F>(formaction: &mut F, key: Key, app: &mut App<B>) -> Option<Key>
+where
+    B: Backend,
+    F: FormAction<B> + ?Sized,
+{
+    let tags = formaction.tags();
+    let config = app.config();
+    let had_message = formaction.status_bar().message().is_some();
+    let outcome = match formaction
+        .status_bar()
+        .handle_key(key, &config.settings, &tags)
+    {
+        Input::Ignored(key) => {
+            if had_message {
+                // The key made it go away.
+                app.mark_dirty();
+            }
+            return Some(key);
+        }
+        Input::Consumed => return None,
+        Input::Submitted(line) => command::run(&line, formaction, app),
+        // An empty search is just a change of mind.
+        Input::Searched(text) if text.is_empty() => return None,
+        Input::Searched(text) => {
+            let search = Command {
+                name: String::from("search"),
+                arguments: vec![text],
+            };
+            formaction.run_command(&search, app)
+        }
+    };
+
+    match outcome {
+        Ok(Some(message)) => formaction.status_bar().info(message),
+        Ok(None) => {}
+        Err(e) => formaction.status_bar().error(&e.to_string()),
+    }
+    None
+}
@@ -0,0 +1,244 @@
+//! Looks of the UI elements, configurable with `color ELEMENT FOREGROUND BACKGROUND [ATTRIBUTE]...`.
+
+use ratatui::style::{Color, Modifier, Style};
+use std::collections::HashMap;
+
+use crate::tokenizer::quote;
+
+/// A part of the UI whose looks can be configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Element {
+    /// Everything that doesn't have looks of its own.
+    Background,
+
+    /// Rows of the feed list and the item list.
+    ListNormal,
+
+    /// The selected row.
+    ListFocus,
+
+    /// Rows of feeds that have unread items, and of unread items. Looks like `ListNormal` unless
+    /// configured.
+    ListNormalUnread,
+
+    /// The selected row, if it's a feed with unread items, or an unread item. Looks like
+    /// `ListFocus` unless configured.
+    ListFocusUnread,
+
+    /// The title bar at the top. Looks like `Info` unless configured.
+    Title,
+
+    /// Bars at the top and the bottom of the screen.
+    Info,
+
+    /// The bar listing the keys for the most useful operations. Looks like `Info` unless
+    /// configured.
+    Hint,
+
+    /// Text of the article.
+    Article,
+
+    /// Lines past the end of the article. Looks like `Article` unless configured.
+    EndOfTextMarker,
+}
+
+/// Elements along with their names in the config file.
+const ELEMENTS: &[(Element, &str)] = &[
+    (Element::Background, "background"),
+    (Element::ListNormal, "listnormal"),
+    (Element::ListFocus, "listfocus"),
+    (Element::ListNormalUnread, "listnormal_unread"),
+    (Element::ListFocusUnread, "listfocus_unread"),
+    (Element::Title, "title"),
+    (Element::Info, "info"),
+    (Element::Hint, "hint"),
+    (Element::Article, "article"),
+    (Element::EndOfTextMarker, "end-of-text-marker"),
+];
+
+impl Element {
+    /// The element called `name` in the config file.
+    pub fn from_name(name: &str) -> Option<Element> {
+        ELEMENTS
+            .iter()
+            .find(|(_, element_name)| *element_name == name)
+            .map(|(element, _)| *element)
+    }
+
+    /// The element that this one looks like unless it's configured.
+    fn fallback(self) -> Option<Element> {
+        match self {
+            Element::ListNormalUnread => Some(Element::ListNormal),
+            Element::ListFocusUnread => Some(Element::ListFocus),
+            Element::Title | Element::Hint => Some(Element::Info),
+            Element::EndOfTextMarker => Some(Element::Article),
+            _ => None,
+        }
+    }
+}
+
+/// Looks of the UI elements.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Styles of elements that have their own, either by default or from the config.
+    styles: HashMap<Element, Style>,
+}
+
+impl Default for Theme {
+    /// Bold yellow bars on blue, and green lists with bold white selection.
+    fn default() -> Theme {
+        let mut theme = Theme {
+            styles: HashMap::new(),
+        };
+        theme.set(Element::Background, Style::default());
+        theme.set(Element::ListNormal, Style::default().fg(Color::Green));
+        theme.set(
+            Element::ListFocus,
+            Style::default()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::BOLD),
+        );
+        theme.set(
+            Element::Info,
+            Style::default()
+                .fg(Color::Yellow)
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        );
+        theme.set(Element::Article, Style::default());
+        theme
+    }
+}
+
+impl Theme {
+    /// Make `element` look like `style`.
+    pub fn set(&mut self, element: Element, style: Style) {
+        self.styles.insert(element, style);
+    }
+
+    /// How `element` looks.
+    pub fn style(&self, element: Element) -> Style {
+        match (self.styles.get(&element), element.fallback()) {
+            (Some(style), _) => *style,
+            (None, Some(fallback)) => self.style(fallback),
+            (None, None) => Style::default(),
+        }
+    }
+
+    /// `color` commands that make the default theme look like this one.
+    pub fn dump(&self) -> Vec<String> {
+        ELEMENTS
+            .iter()
+            .filter_map(|(element, name)| {
+                let style = self.styles.get(element)?;
+                let mut command = format!(
+                    "color {} {} {}",
+                    name,
+                    // True colors start with a hash, so they have to be quoted.
+                    quote(&color_name(style.fg.unwrap_or(Color::Reset))),
+                    quote(&color_name(style.bg.unwrap_or(Color::Reset))),
+                );
+                for (modifier, attribute) in ATTRIBUTES {
+                    if style.add_modifier.contains(*modifier) {
+                        command.push(' ');
+                        command.push_str(attribute);
+                    }
+                }
+                Some(command)
+            })
+            .collect()
+    }
+
+    /// How a row of a list looks, depending on whether it's `selected` and `unread`.
+    pub fn list_row(&self, selected: bool, unread: bool) -> Style {
+        self.style(match (selected, unread) {
+            (false, false) => Element::ListNormal,
+            (true, false) => Element::ListFocus,
+            (false, true) => Element::ListNormalUnread,
+            (true, true) => Element::ListFocusUnread,
+        })
+    }
+}
+
+/// Parse the `name` of a color: one of the eight basic ones, `colorN` for the Nth color of the
+/// 256-color palette, `#rrggbb` for a true color, or `default` for the terminal's own.
+pub fn parse_color(name: &str) -> Option<Color> {
+    let color = match name {
+        "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        // Like in Newsboat, this is the terminal's white, i.e. ANSI color 7, not the bright one.
+        "white" => Color::Gray,
+        _ => {
+            if let Some(index) = name.strip_prefix("color") {
+                return index.parse().ok().map(Color::Indexed);
+            }
+            let hex = name
+                .strip_prefix('#')
+                .filter(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))?;
+            let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            Color::Rgb(component(0)?, component(2)?, component(4)?)
+        }
+    };
+    Some(color)
+}
+
+/// The name of `color`; `parse_color` turns it back into the same color.
+pub fn color_name(color: Color) -> String {
+    let name = match color {
+        Color::Reset => "default",
+        Color::Black => "black",
+        Color::Red => "red",
+        Color::Green => "green",
+        Color::Yellow => "yellow",
+        Color::Blue => "blue",
+        Color::Magenta => "magenta",
+        Color::Cyan => "cyan",
+        Color::Gray => "white",
+        // The bright colors follow the basic ones in the 256-color palette.
+        Color::DarkGray => "color8",
+        Color::LightRed => "color9",
+        Color::LightGreen => "color10",
+        Color::LightYellow => "color11",
+        Color::LightBlue => "color12",
+        Color::LightMagenta => "color13",
+        Color::LightCyan => "color14",
+        Color::White => "color15",
+        Color::Indexed(index) => return format!("color{}", index),
+        Color::Rgb(red, green, blue) => return format!("#{:02x}{:02x}{:02x}", red, green, blue),
+    };
+    name.to_string()
+}
+
+/// Text attributes along with their names, for writing them out.
+const ATTRIBUTES: &[(Modifier, &str)] = &[
+    (Modifier::BOLD, "bold"),
+    (Modifier::DIM, "dim"),
+    (Modifier::UNDERLINED, "underline"),
+    (Modifier::SLOW_BLINK, "blink"),
+    (Modifier::REVERSED, "reverse"),
+    (Modifier::HIDDEN, "invis"),
+    (Modifier::ITALIC, "italic"),
+];
+
+/// Parse the `name` of a text attribute.
+pub fn parse_attribute(name: &str) -> Option<Modifier> {
+    let modifier = match name {
+        "bold" => Modifier::BOLD,
+        "dim" => Modifier::DIM,
+        "underline" => Modifier::UNDERLINED,
+        "blink" => Modifier::SLOW_BLINK,
+        "reverse" | "standout" => Modifier::REVERSED,
+        "invis" => Modifier::HIDDEN,
+        "italic" => Modifier::ITALIC,
+        // Curses' "protected" mode means nothing to terminals.
+        "protect" => Modifier::empty(),
+        _ => return None,
+    };
+    Some(modifier)
+}
@@ -0,0 +1,156 @@
+//! Timers: things that should happen at some point even if the user doesn't press anything.
+//!
+//! The event loop asks `Timers::next_deadline()` how long it may wait for events, and once that
+//! time comes without any, it sends `Event::Tick`. Then the timers that are due go off.
+
+use std::time::{Duration, Instant};
+
+/// What a timer is for.
+///
+/// The app's own timers have a variant each. Anything else that needs a timer, e.g. a formaction
+/// that wants to do something periodically, uses `Tick` with a number of its own choosing, so that
+/// it doesn't need a new variant here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timer {
+    /// The user took too long to type the rest of a key sequence.
+    KeySequence,
+
+    /// A message on the status bar timed out, so the screen should be redrawn without it.
+    StatusMessage,
+
+    /// Time to reload all the feeds, as `auto-reload` asks.
+    Reload,
+
+    /// A timer that someone else scheduled, told apart from others by its number.
+    Tick(u32),
+}
+
+/// Identifies a scheduled timer, so that it can be cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+/// A timer that hasn't gone off yet, or one that goes off periodically.
+#[derive(Debug, Clone)]
+struct Scheduled {
+    /// Identifies the timer.
+    id: TimerId,
+
+    /// What the timer is for.
+    timer: Timer,
+
+    /// When the timer goes off next.
+    deadline: Instant,
+
+    /// How long after going off the timer goes off again, if it's periodic.
+    period: Option<Duration>,
+}
+
+/// Timers that are scheduled to go off.
+#[derive(Debug, Clone, Default)]
+pub struct Timers {
+    /// The timers, in no particular order.
+    scheduled: Vec<Scheduled>,
+
+    /// The number that the next timer gets as its ID.
+    next_id: u64,
+}
+
+impl Timers {
+    /// Make `timer` go off at `deadline`. If there's a `period`, it then keeps going off once in
+    /// every period until it's cancelled.
+    pub fn add(&mut self, timer: Timer, deadline: Instant, period: Option<Duration>) -> TimerId {
+        let id = self.new_id();
+        self.scheduled.push(Scheduled {
+            id,
+            timer,
+            deadline,
+            period,
+        });
+        id
+    }
+
+    /// Make `timer` go off once, `delay` from now. A delay too long to be represented means
+    /// "never", so the timer isn't scheduled at all.
+    pub fn once(&mut self, timer: Timer, delay: Duration) -> TimerId {
+        match Instant::now().checked_add(delay) {
+            Some(deadline) => self.add(timer, deadline, None),
+            None => self.new_id(),
+        }
+    }
+
+    /// Make `timer` go off once in every `period`, starting a period from now. As with `once`, a
+    /// period too long to be represented means "never".
+    pub fn every(&mut self, timer: Timer, period: Duration) -> TimerId {
+        match Instant::now().checked_add(period) {
+            Some(deadline) => self.add(timer, deadline, Some(period)),
+            None => self.new_id(),
+        }
+    }
+
+    /// Hand out an ID that no other timer has.
+    fn new_id(&mut self) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Stop the timer with `id` from going off. Cancelling a timer that already went off, or was
+    /// cancelled before, does nothing.
+    pub fn cancel(&mut self, id: TimerId) {
+        self.scheduled.retain(|scheduled| scheduled.id != id);
+    }
+
+    /// Is the timer with `id` still going to go off?
+    pub fn is_scheduled(&self, id: TimerId) -> bool {
+        self.scheduled.iter().any(|scheduled| scheduled.id == id)
+    }
+
+    /// When the next timer goes off, if any is scheduled.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.scheduled
+            .iter()
+            .map(|scheduled| scheduled.deadline)
+            .min()
+    }
+
+    /// Take the timers that are due at `now`, in the order of their deadlines. One-shot timers are
+    /// forgotten; periodic ones are scheduled a period later.
+    ///
+    /// A periodic timer that missed several periods, e.g. because the computer was asleep, goes
+    /// off just once, and then a period after `now`.
+    pub fn expire(&mut self, now: Instant) -> Vec<(TimerId, Timer)> {
+        let mut due = self
+            .scheduled
+            .iter()
+            .filter(|scheduled| scheduled.deadline <= now)
+            .map(|scheduled| (scheduled.deadline, scheduled.id, scheduled.timer))
+            .collect::<Vec<_>>();
+        due.sort_by_key(|(deadline, _, _)| *deadline);
+
+        self.scheduled.retain_mut(|scheduled| {
+            if scheduled.deadline > now {
+                return true;
+            }
+            match scheduled.period {
+                Some(period) => {
+                    let next = scheduled
+                        .deadline
+                        .checked_add(period)
+                        .filter(|&next| next > now)
+                        .or_else(|| now.checked_add(period));
+                    match next {
+                        Some(next) => {
+                            scheduled.deadline = next;
+                            true
+                        }
+                        // The next time is too far off to be represented, i.e. never.
+                        None => false,
+                    }
+                }
+                None => false,
+            }
+        });
+
+        due.into_iter().map(|(_, id, timer)| (id, timer)).collect()
+    }
+}
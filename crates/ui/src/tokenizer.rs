@@ -0,0 +1,115 @@
+//! Splitting lines of the urls and config files into tokens.
+
+use std::process::Command;
+
+/// Split `line` into whitespace-separated tokens, stopping at a `#` that starts a token.
+///
+/// Double quotes group words into a single token; inside them, a backslash escapes the next
+/// character.
+pub fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    tokenize_impl(line, false)
+}
+
+/// Like `tokenize`, but text in backticks is replaced with the output of the shell command it
+/// contains, e.g. `` `pass show feeds` ``. Inside double quotes, backticks are left alone.
+pub fn tokenize_with_commands(line: &str) -> Result<Vec<String>, String> {
+    tokenize_impl(line, true)
+}
+
+/// Quote `token` so that `tokenize` turns it back into a single token, if it needs quoting.
+///
+/// Backticks are quoted too, so that `tokenize_with_commands` doesn't run what's inside them.
+pub fn quote(token: &str) -> String {
+    let needs_quotes = token.is_empty()
+        || token.starts_with('#')
+        || token
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '\\' || c == '`');
+    if !needs_quotes {
+        return token.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in token.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Split `line` into tokens, substituting commands in backticks if `substitute_commands` is set.
+fn tokenize_impl(line: &str, substitute_commands: bool) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut token = String::new();
+        match chars.peek() {
+            None | Some('#') => break,
+
+            Some('"') => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => token.push('\n'),
+                            Some('t') => token.push('\t'),
+                            Some(c) => token.push(c),
+                            None => return Err(String::from("unterminated quoted string")),
+                        },
+                        Some(c) => token.push(c),
+                        None => return Err(String::from("unterminated quoted string")),
+                    }
+                }
+            }
+
+            Some(_) => {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    if c == '`' && substitute_commands {
+                        let mut command = String::new();
+                        loop {
+                            match chars.next() {
+                                Some('`') => break,
+                                Some(c) => command.push(c),
+                                None => return Err(String::from("unterminated backtick")),
+                            }
+                        }
+                        token.push_str(&run_command(&command)?);
+                    } else {
+                        token.push(c);
+                    }
+                }
+            }
+        }
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Run `command` with the shell and return its output, without the trailing newlines.
+fn run_command(command: &str) -> Result<String, String> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .output()
+        .map_err(|e| format!("couldn't run `{}`: {}", command, e))?;
+    if !output.status.success() {
+        return Err(format!("`{}` failed ({})", command, output.status));
+    }
+
+    let mut output = String::from_utf8_lossy(&output.stdout).into_owned();
+    output.truncate(output.trim_end_matches('\n').len());
+    Ok(output)
+}
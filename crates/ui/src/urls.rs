@@ -0,0 +1,272 @@
+//! Parsing Newsboat's `urls` file, i.e. the list of subscriptions.
+//!
+//! Each line holds a feed URL followed by space-separated tags, e.g.:
+//!
+//! ```text
+//! # Comments start with a hash.
+//! https://example.com/feed.xml news "tag with spaces" "~Custom title"
+//! https://example.com/noisy.xml !
+//! "query:Unread news:unread = \"yes\" and tags # \"news\""
+//! ```
+//!
+//! A tag that starts with a tilde sets the feed's title, and a lone exclamation mark hides the
+//! feed from the feed list. Query feeds don't have a URL of their own; instead, they have a name
+//! and a filter expression that picks items from other feeds.
+
+use std::{cell::RefCell, error, fmt, fs, io, path::Path, path::PathBuf, rc::Rc};
+
+use crate::cache::Cache;
+use crate::feed::Feed;
+use crate::filter::{Filter, FilterError};
+use crate::tokenizer::{quote, tokenize};
+
+/// A single line of the urls file.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    /// URL of the feed. For query feeds, this is the whole `query:NAME:EXPRESSION` string.
+    pub url: String,
+
+    /// Tags of the feed, in the order they were listed.
+    pub tags: Vec<String>,
+
+    /// Title set with a `~` tag, if any.
+    pub custom_title: Option<String>,
+
+    /// Was the feed hidden with a `!` tag?
+    pub hidden: bool,
+
+    /// For query feeds, the filter that picks their items.
+    pub query: Option<Filter>,
+}
+
+impl Subscription {
+    /// A subscription to `url`, without tags.
+    pub fn new(url: String) -> Subscription {
+        Subscription {
+            url,
+            tags: vec![],
+            custom_title: None,
+            hidden: false,
+            query: None,
+        }
+    }
+
+    /// The subscription that `feed` was created from.
+    pub fn from_feed(feed: &Feed) -> Subscription {
+        Subscription {
+            url: feed.url.clone(),
+            tags: feed.tags.clone(),
+            custom_title: feed.custom_title.clone(),
+            hidden: feed.hidden,
+            query: feed.query.clone(),
+        }
+    }
+
+    /// Add `tags` that the subscription doesn't have yet.
+    pub fn add_tags<I: IntoIterator<Item = String>>(&mut self, tags: I) {
+        for tag in tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
+
+    /// The line of the urls file that describes this subscription.
+    pub fn to_line(&self) -> String {
+        let mut tokens = vec![quote(&self.url)];
+        tokens.extend(self.tags.iter().map(|tag| quote(tag)));
+        // Query feeds get their name from the URL, so only a title that differs needs a tag.
+        let query_name = self
+            .url
+            .strip_prefix("query:")
+            .and_then(|query| query.split(':').next());
+        if let Some(ref title) = self.custom_title {
+            if query_name != Some(title.as_str()) {
+                tokens.push(quote(&format!("~{}", title)));
+            }
+        }
+        if self.hidden {
+            tokens.push(String::from("!"));
+        }
+        tokens.join(" ")
+    }
+}
+
+/// Add `imported` subscriptions to `subscriptions`. Those that are already there only get the new
+/// tags. Returns the number of subscriptions that were added.
+pub fn merge(subscriptions: &mut Vec<Subscription>, imported: Vec<Subscription>) -> usize {
+    let mut added = 0;
+    for subscription in imported {
+        match subscriptions.iter_mut().find(|s| s.url == subscription.url) {
+            Some(existing) => existing.add_tags(subscription.tags),
+            None => {
+                subscriptions.push(subscription);
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+/// Write `subscriptions` to the urls file at `path`, replacing its contents.
+pub fn write_file(path: &Path, subscriptions: &[Subscription]) -> io::Result<()> {
+    let mut text = String::new();
+    for subscription in subscriptions {
+        text.push_str(&subscription.to_line());
+        text.push('\n');
+    }
+    fs::write(path, text)
+}
+
+/// Reasons why the urls file couldn't be loaded.
+#[derive(Debug)]
+pub enum UrlsError {
+    /// The file couldn't be read.
+    Io(PathBuf, io::Error),
+
+    /// A line of the file doesn't make sense.
+    Malformed {
+        /// The file in which the line is.
+        path: PathBuf,
+
+        /// Line number, starting from 1.
+        line: usize,
+
+        /// What's wrong with the line.
+        message: String,
+    },
+}
+
+impl fmt::Display for UrlsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UrlsError::Io(path, e) => {
+                write!(f, "{}: couldn't read the urls file: {}", path.display(), e)
+            }
+            UrlsError::Malformed {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl error::Error for UrlsError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            UrlsError::Io(_, e) => Some(e),
+            UrlsError::Malformed { .. } => None,
+        }
+    }
+}
+
+/// Parse the urls file at `path`.
+pub fn parse_file(path: &Path) -> Result<Vec<Subscription>, UrlsError> {
+    let text = fs::read_to_string(path).map_err(|e| UrlsError::Io(path.to_owned(), e))?;
+    parse_str(&text, path)
+}
+
+/// Parse the contents of a urls file. `path` is only used in error messages.
+pub fn parse_str(text: &str, path: &Path) -> Result<Vec<Subscription>, UrlsError> {
+    let mut subscriptions = vec![];
+    for (index, line) in text.lines().enumerate() {
+        let malformed = |message| UrlsError::Malformed {
+            path: path.to_owned(),
+            line: index + 1,
+            message,
+        };
+        if let Some(subscription) = parse_line(line).map_err(malformed)? {
+            subscriptions.push(subscription);
+        }
+    }
+    Ok(subscriptions)
+}
+
+/// Parse a single `line`. Returns `None` for empty lines and comments.
+fn parse_line(line: &str) -> Result<Option<Subscription>, String> {
+    let mut tokens = tokenize(line)?.into_iter();
+    let url = match tokens.next() {
+        Some(url) => url,
+        None => return Ok(None),
+    };
+
+    // Query feeds are named in the URL, but a `~` tag can still override that.
+    let mut custom_title = None;
+    let query = if let Some(query) = url.strip_prefix("query:") {
+        let (name, expression) = query
+            .split_once(':')
+            .ok_or_else(|| format!("query feed `{}` has no filter expression", url))?;
+        if name.is_empty() {
+            return Err(format!("query feed `{}` has no name", url));
+        }
+        custom_title = Some(name.to_string());
+        Some(Filter::parse(expression).map_err(|FilterError(e)| {
+            format!("query feed `{}`: invalid filter expression: {}", name, e)
+        })?)
+    } else if ["http://", "https://", "file://"]
+        .iter()
+        .any(|scheme| url.starts_with(scheme))
+    {
+        None
+    } else {
+        return Err(format!(
+            "`{}` is not a feed URL (it should start with http://, https://, file:// or query:)",
+            url
+        ));
+    };
+
+    let mut subscription = Subscription {
+        custom_title,
+        query,
+        ..Subscription::new(url)
+    };
+    for tag in tokens {
+        if tag == "!" {
+            subscription.hidden = true;
+        } else if let Some(title) = tag.strip_prefix('~') {
+            subscription.custom_title = Some(title.to_string());
+        } else {
+            subscription.add_tags(Some(tag));
+        }
+    }
+
+    Ok(Some(subscription))
+}
+
+/// Build the feed list out of `subscriptions`, in the same order.
+///
+/// Feeds start out with what's in the `cache`; fetching them is a separate step. Query feeds are
+/// filled with items of other feeds.
+pub fn load_feeds(
+    subscriptions: &[Subscription],
+    cache: &Cache,
+) -> rusqlite::Result<Vec<Rc<RefCell<Feed>>>> {
+    let mut feeds = vec![];
+    for subscription in subscriptions {
+        let mut feed = match subscription.query {
+            Some(_) => Feed::default(),
+            None => cache.load_feed(&subscription.url)?.unwrap_or_default(),
+        };
+        feed.url = subscription.url.clone();
+        feed.tags = subscription.tags.clone();
+        feed.custom_title = subscription.custom_title.clone();
+        feed.hidden = subscription.hidden;
+        feed.query = subscription.query.clone();
+        feeds.push(Rc::new(RefCell::new(feed)));
+    }
+
+    update_query_feeds(&feeds);
+    Ok(feeds)
+}
+
+/// Refill query feeds among `feeds` with matching items of all the other feeds.
+pub fn update_query_feeds(feeds: &[Rc<RefCell<Feed>>]) {
+    for feed in feeds {
+        let query = feed.borrow().query.clone();
+        if let Some(query) = query {
+            let items = query.select(feeds);
+            feed.borrow_mut().items = items;
+        }
+    }
+}
@@ -1,16 +1,111 @@
 //! An input field for a single line of plain text.
+//!
+//! The line is edited with the same keys as in Emacs and readline:
+//!
+//! | Key                     | Action                                             |
+//! |-------------------------|----------------------------------------------------|
+//! | Left, Ctrl-B            | move one character left                            |
+//! | Right, Ctrl-F           | move one character right                           |
+//! | Home, Ctrl-A            | move to the start of the line                      |
+//! | End, Ctrl-E             | move to the end of the line                        |
+//! | Alt-B, Ctrl-Left        | move to the start of the word                      |
+//! | Alt-F, Ctrl-Right       | move to the end of the word                        |
+//! | Backspace, Ctrl-H       | delete the character before the cursor             |
+//! | Delete, Ctrl-D          | delete the character under the cursor              |
+//! | Ctrl-U                  | kill everything before the cursor                  |
+//! | Ctrl-K                  | kill everything after the cursor                   |
+//! | Ctrl-W                  | kill the whitespace-delimited word before the cursor |
+//! | Alt-Backspace           | kill the word before the cursor                    |
+//! | Alt-D                   | kill the word after the cursor                     |
+//! | Ctrl-Y                  | yank (paste) the last killed text                  |
+//! | Alt-Y                   | replace the text just yanked with an earlier kill  |
+//! | Up, Ctrl-P              | replace the line with the previous one from history |
+//! | Down, Ctrl-N            | replace the line with the next one from history    |
+//! | Ctrl-R                  | search the history backwards as you type           |
+//! | Tab, Shift-Tab          | complete the word before the cursor (see `complete`) |
+//! | Enter                   | submit the line                                    |
+//! | Esc, Ctrl-G             | cancel                                             |
+//!
+//! Killed text goes to the kill ring, which lives as long as the state does, so it's shared by all
+//! the lines edited with the same state. Consecutive kills are joined into one entry.
+//!
+//! Submitted lines are added to the history the state was created with. During a Ctrl-R search,
+//! typed characters extend the query, Backspace shortens it, Ctrl-R looks for an older match,
+//! Ctrl-G goes back to the line as it was before the search, and any other key ends the search and
+//! then does what it always does.
 
 use ratatui::{buffer::Buffer, layout::Rect, style::Style, widgets::StatefulWidget};
+use std::{cell::RefCell, ops::Range, rc::Rc};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+use crate::event::Key;
+use crate::history::History;
+
+/// How many killed texts the kill ring remembers.
+const KILL_RING_SIZE: usize = 32;
+
+/// What a key did to the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The key edited the line or moved the cursor, or would have if there was anything to edit.
+    Edited,
+
+    /// The user is done with the line (pressed Enter).
+    Submitted,
+
+    /// The user gave up on the line (pressed Esc or Ctrl-G).
+    Cancelled,
+
+    /// The key doesn't mean anything to the line.
+    Ignored,
+}
+
+/// The editing command that was performed last, if it matters for the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LastAction {
+    /// Text was killed; another kill adds to the same kill ring entry.
+    Kill,
+
+    /// Kill ring entry number `index` was yanked; Alt-Y can replace it with an earlier one.
+    Yank { index: usize },
+}
+
+/// Ways to complete the word before the cursor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Completions {
+    /// Where the word starts, as a byte offset into the line.
+    pub start: usize,
+
+    /// Texts that could replace the word, in the order in which they're cycled through.
+    pub candidates: Vec<String>,
+}
+
+/// State of a reverse incremental search through the history (Ctrl-R).
 #[derive(Debug, Clone)]
+struct Search {
+    /// What the user is looking for.
+    query: String,
+
+    /// Which history entry matched the query last, if any did.
+    index: Option<usize>,
+
+    /// Did the last attempt to find a match fail?
+    failed: bool,
+
+    /// The line as it was before the search, and where the cursor was in it.
+    original: (String, usize),
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct TextLineState {
     text: String,
 
     // The screen might be too narrow to display the whole text, but the cursor should always be
     // visible. To achieve this, the widget keeps track of two values:
-    // 1. viewport_offset, which is the number of characters at the front of the string that are
-    //    outside the displayed area;
-    // 2. cursor_position, which is cursor's offset inside the text.
+    // 1. viewport_offset, which is where the first displayed grapheme cluster starts; everything
+    //    before it is outside the displayed area;
+    // 2. cursor_position, which is where the grapheme cluster under the cursor starts.
     //
     // Visually it looks something like this:
     //
@@ -23,81 +118,522 @@ pub struct TextLineState {
     // `--------------------------------'
     //            cursor_position
     //
-    // The widget always keeps the cursor in sight, i.e. cursor_position is in [viewport_offset;
-    // viewport_offset + viewport_width). When cursor_position reaches its viewport_width, each call to put_char
-    // increases viewport_offset, moving more text out of sight to make space for the new character.
+    // Both are byte offsets into `text`, and both are always at a grapheme cluster boundary, so
+    // that slicing the text with them is safe, and the cursor never ends up between a letter and
+    // its accent. How far from the left edge the cursor is drawn is the display width of the text
+    // between them: CJK characters take up two columns, combining marks take up none.
+    //
+    // The widget always keeps the cursor in sight, i.e. the text between viewport_offset and
+    // cursor_position fits into viewport_width, with a column to spare for the cursor itself. When
+    // it no longer does, each call to render moves viewport_offset forward, moving more text out of
+    // sight to make space for the new character.
     viewport_offset: usize,
     cursor_position: usize,
+
+    /// Texts that were killed, oldest first.
+    kill_ring: Vec<String>,
+
+    /// Where the text that was just yanked starts. It ends at the cursor.
+    yank_start: usize,
+
+    /// The editing command that was performed last.
+    last_action: Option<LastAction>,
+
+    /// Lines entered before. Submitted lines are added to it.
+    history: Rc<RefCell<History>>,
+
+    /// Which history entry is shown, if the user went back in history.
+    history_index: Option<usize>,
+
+    /// The line that was being edited before the user went back in history.
+    draft: String,
+
+    /// Reverse incremental search, if it's in progress.
+    search: Option<Search>,
+
+    /// Completions that Tab and Shift-Tab cycle through, and which of them is shown. There are
+    /// some only as long as the user presses nothing but these two keys.
+    completion: Option<(Completions, Option<usize>)>,
 }
 
-impl Default for TextLineState {
-    fn default() -> TextLineState {
+impl TextLineState {
+    /// Create a state that recalls lines from `history`, and adds the submitted ones to it.
+    pub fn with_history(history: Rc<RefCell<History>>) -> TextLineState {
         TextLineState {
-            text: String::new(),
-            viewport_offset: 0,
-            cursor_position: 0,
+            history,
+            ..TextLineState::default()
         }
     }
-}
-
-impl TextLineState {
-    // This API is incomplete, e.g. it lacks full cursor control. For this demo, we simply don't
-    // care; all of those functions are trivial to add and don't have an impact on our evaluation
-    // of tui-rs.
 
     /// Currently entered text.
     pub fn text(&self) -> &str {
         &self.text
     }
 
-    /// Current offset inside the display area (at the time of the last `render()` call).
+    /// Start editing a new, empty line. The kill ring stays.
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.viewport_offset = 0;
+        self.cursor_position = 0;
+        self.last_action = None;
+        self.history_index = None;
+        self.draft.clear();
+        self.search = None;
+        self.completion = None;
+    }
+
+    /// What to show in place of the usual prompt while searching the history, e.g.
+    /// ``(reverse-i-search)`feed': ``. Outside of search, there's nothing to show.
+    pub fn search_prompt(&self) -> Option<String> {
+        let search = self.search.as_ref()?;
+        let failed = if search.failed { "failed " } else { "" };
+        Some(format!("({}reverse-i-search)`{}': ", failed, search.query))
+    }
+
+    /// Edit the line according to `key`.
+    pub fn handle_key(&mut self, key: &Key) -> Outcome {
+        self.completion = None;
+        if self.search.is_some() {
+            if let Some(outcome) = self.handle_search_key(key) {
+                return outcome;
+            }
+        }
+
+        let last_action = self.last_action.take();
+        match key {
+            Key::Char('\n') => {
+                self.history.borrow_mut().add(&self.text);
+                return Outcome::Submitted;
+            }
+            Key::Esc => return Outcome::Cancelled,
+            Key::Char(c) if !c.is_control() => self.put_char(*c),
+            Key::Backspace => self.delete(self.previous_boundary(), self.cursor_position),
+            Key::Delete => self.delete(self.cursor_position, self.next_boundary()),
+            Key::Left => self.set_cursor_position(self.previous_boundary()),
+            Key::Right => self.set_cursor_position(self.next_boundary()),
+            Key::Home => self.set_cursor_position(0),
+            Key::End => self.set_cursor_position(self.text.len()),
+            Key::Up => self.history_previous(),
+            Key::Down => self.history_next(),
+
+            Key::Ctrl(key) => match **key {
+                Key::Char('g') => return Outcome::Cancelled,
+                Key::Char('h') => self.delete(self.previous_boundary(), self.cursor_position),
+                Key::Char('d') => self.delete(self.cursor_position, self.next_boundary()),
+                Key::Char('b') => self.set_cursor_position(self.previous_boundary()),
+                Key::Char('f') => self.set_cursor_position(self.next_boundary()),
+                Key::Char('a') => self.set_cursor_position(0),
+                Key::Char('e') => self.set_cursor_position(self.text.len()),
+                Key::Left => self.set_cursor_position(self.word_start(is_word)),
+                Key::Right => self.set_cursor_position(self.word_end(is_word)),
+                Key::Char('u') => self.kill(0..self.cursor_position, last_action),
+                Key::Char('k') => self.kill(self.cursor_position..self.text.len(), last_action),
+                Key::Char('w') => self.kill(
+                    self.word_start(is_not_space)..self.cursor_position,
+                    last_action,
+                ),
+                Key::Char('y') => self.yank(),
+                Key::Char('p') => self.history_previous(),
+                Key::Char('n') => self.history_next(),
+                Key::Char('r') => {
+                    self.search = Some(Search {
+                        query: String::new(),
+                        index: None,
+                        failed: false,
+                        original: (self.text.clone(), self.cursor_position),
+                    })
+                }
+                _ => return Outcome::Ignored,
+            },
+
+            Key::Alt(key) => match **key {
+                Key::Char('b') => self.set_cursor_position(self.word_start(is_word)),
+                Key::Char('f') => self.set_cursor_position(self.word_end(is_word)),
+                Key::Backspace => {
+                    self.kill(self.word_start(is_word)..self.cursor_position, last_action)
+                }
+                Key::Char('d') => {
+                    self.kill(self.cursor_position..self.word_end(is_word), last_action)
+                }
+                Key::Char('y') => self.yank_pop(last_action),
+                _ => return Outcome::Ignored,
+            },
+
+            _ => return Outcome::Ignored,
+        }
+        Outcome::Edited
+    }
+
+    /// Complete the word before the cursor with one of the `Completions` that `find` comes up with
+    /// for the text before the cursor. This is what Tab (and, with `backwards` set, Shift-Tab)
+    /// should do; `handle_key` leaves these keys to the caller, who knows what can be completed.
+    ///
+    /// A single candidate replaces the word right away. With several, the word is first extended
+    /// to their common prefix; if there's nothing to extend it with, or on the next press, the
+    /// word is replaced with each candidate in turn.
+    pub fn complete<F>(&mut self, backwards: bool, find: F)
+    where
+        F: FnOnce(&str) -> Completions,
+    {
+        self.last_action = None;
+        self.search = None;
+        if self.completion.is_none() {
+            let completions = find(&self.text[..self.cursor_position]);
+            let typed = &self.text[completions.start..self.cursor_position];
+            match completions.candidates.as_slice() {
+                [] => return,
+                [candidate] => {
+                    let candidate = candidate.clone();
+                    self.replace_word(completions.start, &candidate);
+                    return;
+                }
+                candidates => {
+                    let prefix = common_prefix(candidates);
+                    let extends = prefix.len() > typed.len();
+                    self.completion = Some((completions, None));
+                    if extends {
+                        self.replace_word(self.completion_start(), &prefix);
+                        return;
+                    }
+                }
+            }
+        }
+
+        if let Some((ref completions, ref mut index)) = self.completion {
+            let count = completions.candidates.len();
+            let next = match (*index, backwards) {
+                (None, false) => 0,
+                (None, true) => count - 1,
+                (Some(index), false) => (index + 1) % count,
+                (Some(index), true) => (index + count - 1) % count,
+            };
+            *index = Some(next);
+            let (start, candidate) = (completions.start, completions.candidates[next].clone());
+            self.replace_word(start, &candidate);
+        }
+    }
+
+    /// Where the word that's being completed starts.
+    fn completion_start(&self) -> usize {
+        self.completion
+            .as_ref()
+            .map_or(self.cursor_position, |(completions, _)| completions.start)
+    }
+
+    /// Replace the text between `start` and the cursor with `word`, and put the cursor after it.
+    fn replace_word(&mut self, start: usize, word: &str) {
+        self.text.replace_range(start..self.cursor_position, word);
+        self.set_cursor_position(start + word.len());
+    }
+
+    /// Handle `key` pressed during a history search. If the key ends the search and should then be
+    /// handled as usual, returns `None`.
+    fn handle_search_key(&mut self, key: &Key) -> Option<Outcome> {
+        let search = self.search.as_mut()?;
+        let newest = self.history.borrow().entries().len();
+        match key {
+            Key::Char(c) if !c.is_control() => {
+                search.query.push(*c);
+                // The current match might still match.
+                let before = search.index.map_or(newest, |index| index + 1);
+                self.search_history(before);
+            }
+
+            Key::Backspace => {
+                search.query.pop();
+                self.search_history(newest);
+            }
+
+            Key::Ctrl(key) if **key == Key::Char('h') => {
+                search.query.pop();
+                self.search_history(newest);
+            }
+
+            Key::Ctrl(key) if **key == Key::Char('r') => {
+                let before = search.index.unwrap_or(newest);
+                self.search_history(before);
+            }
+
+            Key::Ctrl(key) if **key == Key::Char('g') => {
+                let (text, cursor_position) = search.original.clone();
+                self.search = None;
+                self.replace_text(text);
+                self.set_cursor_position(cursor_position);
+            }
+
+            // Like in readline, Esc keeps the match and goes back to editing.
+            Key::Esc => self.search = None,
+
+            _ => {
+                self.search = None;
+                return None;
+            }
+        }
+        Some(Outcome::Edited)
+    }
+
+    /// Look for the newest history entry that's older than entry number `before` and contains the
+    /// search query. If there's one, show it with the cursor at the match.
+    fn search_history(&mut self, before: usize) {
+        let search = match self.search {
+            Some(ref mut search) => search,
+            None => return,
+        };
+        let found = self.history.borrow().entries()[..before]
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(index, entry)| Some((index, entry.clone(), entry.rfind(&search.query)?)));
+        search.failed = found.is_none();
+        if let Some((index, entry, position)) = found {
+            search.index = Some(index);
+            self.replace_text(entry);
+            self.set_cursor_position(position);
+        }
+    }
+
+    /// Show the history entry before the one that's shown now, or the newest one if it's the line
+    /// being edited.
+    fn history_previous(&mut self) {
+        let index = match self.history_index {
+            Some(index) => index.checked_sub(1),
+            None => self.history.borrow().entries().len().checked_sub(1),
+        };
+        let index = match index {
+            Some(index) => index,
+            None => return,
+        };
+        if self.history_index.is_none() {
+            self.draft = self.text.clone();
+        }
+        self.history_index = Some(index);
+        let entry = self.history.borrow().entries()[index].clone();
+        self.replace_text(entry);
+    }
+
+    /// Show the history entry after the one that's shown now, or the line that was being edited if
+    /// it's the newest one.
+    fn history_next(&mut self) {
+        let index = match self.history_index {
+            Some(index) => index + 1,
+            None => return,
+        };
+        let entry = self.history.borrow().entries().get(index).cloned();
+        match entry {
+            Some(entry) => {
+                self.history_index = Some(index);
+                self.replace_text(entry);
+            }
+            None => {
+                self.history_index = None;
+                let draft = std::mem::take(&mut self.draft);
+                self.replace_text(draft);
+            }
+        }
+    }
+
+    /// Replace the whole line with `text`, and put the cursor at its end.
+    fn replace_text(&mut self, text: String) {
+        self.text = text;
+        self.viewport_offset = 0;
+        self.set_cursor_position(self.text.len());
+    }
+
+    /// Current offset inside the display area, in columns (at the time of the last `render()`
+    /// call).
     pub fn cursor_display_offset(&self) -> usize {
-        self.cursor_position - self.viewport_offset
+        self.text[self.viewport_offset..self.cursor_position].width()
     }
 
-    /// Clip cursor position to interval of [0; text_length].
+    /// Clip cursor position to interval of [0; text_length], and move it forward to the nearest
+    /// grapheme cluster boundary.
     fn reestablish_cursor_position_invariants(&mut self) {
-        self.cursor_position = self.cursor_position.min(self.text.len()); // clip to text_length on the right
+        self.cursor_position = self
+            .text
+            .grapheme_indices(true)
+            .map(|(start, _)| start)
+            .find(|start| *start >= self.cursor_position)
+            .unwrap_or(self.text.len());
 
         if self.viewport_offset > self.cursor_position {
             self.viewport_offset = self.cursor_position;
         }
     }
 
-    /// Move cursor to the given position in text.
+    /// Move cursor to the given byte offset in text.
     ///
-    /// The position is clipped into the interval of [0; text_length].
+    /// The position is clipped into the interval of [0; text_length], and moved forward if it's
+    /// in the middle of a grapheme cluster.
     fn set_cursor_position(&mut self, new_position: usize) {
         self.cursor_position = new_position;
         self.reestablish_cursor_position_invariants();
     }
 
     /// Put given character at current cursor position and advance the cursor.
+    ///
+    /// A combining character joins the grapheme cluster before the cursor, and the cursor stays
+    /// after that cluster.
     pub fn put_char(&mut self, c: char) {
         self.text.insert(self.cursor_position, c);
-        self.move_right(1);
+        self.set_cursor_position(self.cursor_position + c.len_utf8());
+    }
+
+    /// Insert `text` at current cursor position and put the cursor after it.
+    fn insert(&mut self, text: &str) {
+        self.text.insert_str(self.cursor_position, text);
+        self.set_cursor_position(self.cursor_position + text.len());
+    }
+
+    /// Remove the text in `start..end` and put the cursor where it was.
+    fn delete(&mut self, start: usize, end: usize) {
+        self.text.replace_range(start..end, "");
+        self.set_cursor_position(start);
+    }
+
+    /// Delete the text in `range`, and put it into the kill ring. If `last_action` was a kill too,
+    /// the text is added to the same entry.
+    fn kill(&mut self, range: Range<usize>, last_action: Option<LastAction>) {
+        let killed = self.text[range.clone()].to_string();
+        let backwards = range.end == self.cursor_position;
+        self.delete(range.start, range.end);
+        self.last_action = Some(LastAction::Kill);
+
+        match self.kill_ring.last_mut() {
+            Some(entry) if last_action == Some(LastAction::Kill) => {
+                if backwards {
+                    entry.insert_str(0, &killed);
+                } else {
+                    entry.push_str(&killed);
+                }
+            }
+            // Nothing was killed, so there's nothing for the next kill to be joined with.
+            _ if killed.is_empty() => self.last_action = None,
+            _ => {
+                if self.kill_ring.len() == KILL_RING_SIZE {
+                    self.kill_ring.remove(0);
+                }
+                self.kill_ring.push(killed);
+            }
+        }
     }
 
-    /// Move cursor to the right by `offset` characters. Stop at the end of the text.
-    fn move_right(&mut self, offset: usize) {
-        self.set_cursor_position(self.cursor_position.saturating_add(offset));
+    /// Insert the last killed text at the cursor.
+    fn yank(&mut self) {
+        if let Some(index) = self.kill_ring.len().checked_sub(1) {
+            self.yank_entry(index);
+        }
+    }
+
+    /// If `last_action` was a yank, replace the text it inserted with the entry that was killed
+    /// before it, going around the ring.
+    fn yank_pop(&mut self, last_action: Option<LastAction>) {
+        if let Some(LastAction::Yank { index }) = last_action {
+            self.delete(self.yank_start, self.cursor_position);
+            self.yank_entry(index.checked_sub(1).unwrap_or(self.kill_ring.len() - 1));
+        }
+    }
+
+    /// Insert kill ring entry number `index` at the cursor.
+    fn yank_entry(&mut self, index: usize) {
+        self.yank_start = self.cursor_position;
+        let text = self.kill_ring[index].clone();
+        self.insert(&text);
+        self.last_action = Some(LastAction::Yank { index });
+    }
+
+    /// Where the grapheme cluster before the cursor starts.
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor_position]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(start, _)| start)
+    }
+
+    /// Where the grapheme cluster under the cursor ends.
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor_position..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor_position, |grapheme| {
+                self.cursor_position + grapheme.len()
+            })
+    }
+
+    /// Where the word before the cursor starts. Words are made of grapheme clusters for which
+    /// `in_word` is true.
+    fn word_start(&self, in_word: fn(&str) -> bool) -> usize {
+        let graphemes = self.text[..self.cursor_position]
+            .grapheme_indices(true)
+            .collect::<Vec<_>>();
+        let mut i = graphemes.len();
+        while i > 0 && !in_word(graphemes[i - 1].1) {
+            i -= 1;
+        }
+        while i > 0 && in_word(graphemes[i - 1].1) {
+            i -= 1;
+        }
+        graphemes
+            .get(i)
+            .map_or(self.cursor_position, |(start, _)| *start)
+    }
+
+    /// Where the word after the cursor ends. Words are made of grapheme clusters for which
+    /// `in_word` is true.
+    fn word_end(&self, in_word: fn(&str) -> bool) -> usize {
+        let mut end = self.cursor_position;
+        let mut graphemes = self.text[self.cursor_position..].graphemes(true).peekable();
+        while let Some(grapheme) = graphemes.next_if(|grapheme| !in_word(grapheme)) {
+            end += grapheme.len();
+        }
+        while let Some(grapheme) = graphemes.next_if(|grapheme| in_word(grapheme)) {
+            end += grapheme.len();
+        }
+        end
     }
 
     fn make_cursor_visible(&mut self, viewport_width: usize) {
-        let diff = self
-            .cursor_position
-            .saturating_sub(self.viewport_offset)
-            // We have to subtract 1 to reserve a space for cursor.
-            .saturating_sub(viewport_width.saturating_sub(1));
-        if diff > 0 {
-            // Cursor is too far to the right, out of the viewport. Shift viewport to put cursor
-            // at the end of the display area.
-            self.viewport_offset = self.viewport_offset + diff;
+        // We have to subtract 1 to reserve a space for cursor.
+        let available = viewport_width.saturating_sub(1);
+        while self.cursor_display_offset() > available {
+            // Cursor is too far to the right, out of the viewport. Shift viewport by a grapheme
+            // cluster at a time, until the cursor is at the end of the display area.
+            let first_grapheme = self.text[self.viewport_offset..]
+                .graphemes(true)
+                .next()
+                .map_or(0, str::len);
+            if first_grapheme == 0 {
+                break;
+            }
+            self.viewport_offset += first_grapheme;
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// The longest text that all of the `candidates` start with.
+fn common_prefix(candidates: &[String]) -> String {
+    let mut prefix = candidates.first().map_or("", String::as_str);
+    for candidate in candidates {
+        let common = prefix
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(prefix.len().min(candidate.len()), |((index, _), _)| index);
+        prefix = &prefix[..common];
+    }
+    prefix.to_string()
+}
+
+/// Is `grapheme` a part of a word, as far as Alt-B, Alt-F and friends are concerned?
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_alphanumeric)
+}
+
+/// Is `grapheme` a part of a word, as far as Ctrl-W is concerned?
+fn is_not_space(grapheme: &str) -> bool {
+    !grapheme.chars().all(char::is_whitespace)
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct TextLine {}
 
 impl TextLine {
@@ -110,11 +646,16 @@ impl StatefulWidget for TextLine {
     type State = TextLineState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        // On a tiny terminal, there might be no room for the line at all.
+        if area.area() == 0 {
+            return;
+        }
         state.make_cursor_visible(area.width as usize);
-        buf.set_string(
+        buf.set_stringn(
             area.left(),
             area.top(),
             &state.text[state.viewport_offset..],
+            area.width as usize,
             Style::default(),
         );
     }
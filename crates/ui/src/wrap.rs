@@ -0,0 +1,115 @@
+//! Word wrapping of styled text.
+
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Break `spans`, which make up a single line, into lines at most `width` columns wide.
+///
+/// Lines are broken between words; whitespace at the break is dropped. Words that are longer than
+/// a line are broken wherever they hit the edge. Indentation is kept, unless the first word
+/// doesn't fit after it. An empty line stays a single empty line.
+pub fn wrap(spans: &[Span], width: usize) -> Vec<Line<'static>> {
+    let mut wrapper = Wrapper {
+        width: width.max(1),
+        lines: vec![],
+        line: vec![],
+        used: 0,
+    };
+    for span in spans {
+        for piece in pieces(&span.content) {
+            wrapper.add(piece, span.style);
+        }
+    }
+    wrapper.finish_line();
+    wrapper.lines
+}
+
+/// Split `text` into alternating runs of whitespace and of everything else.
+fn pieces(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let end = rest
+            .find(|c: char| c.is_whitespace() != first.is_whitespace())
+            .unwrap_or(rest.len());
+        let (piece, after) = rest.split_at(end);
+        rest = after;
+        Some(piece)
+    })
+}
+
+/// State of `wrap`.
+struct Wrapper {
+    /// Maximum width of a line.
+    width: usize,
+
+    /// Lines that are done.
+    lines: Vec<Line<'static>>,
+
+    /// The line that's being filled.
+    line: Vec<Span<'static>>,
+
+    /// Width of that line so far.
+    used: usize,
+}
+
+impl Wrapper {
+    /// Add a run of whitespace or of non-whitespace to the text.
+    fn add(&mut self, piece: &str, style: Style) {
+        let piece_width = piece.width();
+        if piece.starts_with(char::is_whitespace) {
+            // Continuation lines don't start with the whitespace that the line was broken at.
+            if self.used > 0 || self.lines.is_empty() {
+                self.push(piece, style, piece_width);
+            }
+            return;
+        }
+
+        if self.used + piece_width > self.width && self.used > 0 {
+            if self.line.iter().all(|span| span.content.trim().is_empty()) {
+                // Indentation that the first word doesn't fit after is dropped.
+                self.line.clear();
+                self.used = 0;
+            } else {
+                self.finish_line();
+            }
+        }
+        if piece_width <= self.width {
+            self.push(piece, style, piece_width);
+            return;
+        }
+        for c in piece.chars() {
+            let c_width = c.width().unwrap_or(0);
+            if self.used + c_width > self.width && self.used > 0 {
+                self.finish_line();
+            }
+            self.push(c.encode_utf8(&mut [0; 4]), style, c_width);
+        }
+    }
+
+    /// Append `text` that's `width` columns wide to the current line.
+    fn push(&mut self, text: &str, style: Style, width: usize) {
+        match self.line.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push_str(text),
+            _ => self.line.push(Span::styled(text.to_string(), style)),
+        }
+        self.used += width;
+    }
+
+    /// Move the current line to the finished ones, without the whitespace at its end.
+    fn finish_line(&mut self) {
+        while let Some(span) = self.line.last_mut() {
+            let trimmed = span.content.trim_end().len();
+            if trimmed > 0 {
+                span.content.to_mut().truncate(trimmed);
+                break;
+            }
+            self.line.pop();
+        }
+        self.lines.push(Line::from(std::mem::take(&mut self.line)));
+        self.used = 0;
+    }
+}
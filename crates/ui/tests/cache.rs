@@ -0,0 +1,90 @@
+use chrono::{TimeZone, Utc};
+use std::{cell::RefCell, rc::Rc};
+
+use ui::cache::Cache;
+use ui::feed::{Enclosure, Feed, Item};
+
+fn item(guid: &str, second: u32) -> Rc<RefCell<Item>> {
+    Rc::new(RefCell::new(Item {
+        title: format!("Item {}", guid),
+        url: format!("https://example.com/{}", guid),
+        guid: guid.to_string(),
+        date: Utc.with_ymd_and_hms(2020, 4, 28, 12, 0, second).single(),
+        unread: true,
+        ..Item::default()
+    }))
+}
+
+fn feed(items: Vec<Rc<RefCell<Item>>>) -> Feed {
+    Feed {
+        title: String::from("Example"),
+        url: String::from("https://example.com/feed.xml"),
+        link: String::from("https://example.com/"),
+        items,
+        ..Feed::default()
+    }
+}
+
+#[test]
+fn saved_feeds_can_be_loaded_back() {
+    let cache = Cache::in_memory().unwrap();
+    let original = feed(vec![item("b", 2), item("a", 1)]);
+    {
+        let mut first = original.items[0].borrow_mut();
+        first.unread = false;
+        first.set_flags("x");
+        first.enclosure = Some(Enclosure {
+            url: String::from("https://example.com/b.mp3"),
+            mime_type: String::from("audio/mpeg"),
+            length: Some(42),
+        });
+    }
+    cache.save_feed(&original).unwrap();
+
+    let loaded = cache
+        .load_feed(&original.url)
+        .unwrap()
+        .expect("feed is cached");
+    assert_eq!(loaded.title, original.title);
+    assert_eq!(loaded.link, original.link);
+    assert_eq!(loaded.items.len(), 2);
+
+    let first = loaded.items[0].borrow();
+    let expected = original.items[0].borrow();
+    assert_eq!(first.guid, "b");
+    assert_eq!(first.date, expected.date);
+    assert!(!first.unread);
+    assert_eq!(first.flags, "x");
+    assert_eq!(first.enclosure, expected.enclosure);
+    assert!(loaded.items[1].borrow().unread);
+
+    assert!(cache
+        .load_feed("https://example.com/other.xml")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn merging_restores_read_state_and_keeps_old_items() {
+    let cache = Cache::in_memory().unwrap();
+    let old = feed(vec![item("b", 2), item("a", 1)]);
+    cache.save_feed(&old).unwrap();
+
+    // The user reads an item, which is saved on its own.
+    old.items[0].borrow_mut().unread = false;
+    cache.save_item(&old.url, &old.items[0].borrow()).unwrap();
+
+    // Next time, the feed has a new item, and the oldest one fell off.
+    let mut fresh = feed(vec![item("c", 3), item("b", 2)]);
+    cache.merge_into(&mut fresh).unwrap();
+
+    let guids = fresh
+        .items
+        .iter()
+        .map(|item| item.borrow().guid.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(guids, ["c", "b", "a"]);
+    assert!(fresh.items[0].borrow().unread);
+    assert!(!fresh.items[1].borrow().unread);
+    assert!(fresh.items[2].borrow().unread);
+}
@@ -0,0 +1,53 @@
+use ui::command::{parse, Command};
+
+fn command(name: &str, arguments: &[&str]) -> Option<Command> {
+    Some(Command {
+        name: name.to_string(),
+        arguments: arguments.iter().map(|a| a.to_string()).collect(),
+    })
+}
+
+#[test]
+fn arguments_are_split_like_config_lines() {
+    assert_eq!(
+        parse("set browser \"w3m %u\""),
+        Ok(command("set", &["browser", "w3m %u"]))
+    );
+    assert_eq!(
+        parse("  source a \"b c\"  "),
+        Ok(command("source", &["a", "b c"]))
+    );
+    assert_eq!(parse("quit"), Ok(command("quit", &[])));
+}
+
+#[test]
+fn empty_lines_are_not_commands() {
+    assert_eq!(parse(""), Ok(None));
+    assert_eq!(parse("   "), Ok(None));
+}
+
+#[test]
+fn number_is_short_for_goto() {
+    assert_eq!(parse("12"), Ok(command("goto", &["12"])));
+    assert_eq!(
+        parse("goto rust news"),
+        Ok(command("goto", &["rust", "news"]))
+    );
+    assert_eq!(parse("12 13"), Err(String::from("not a command: 12")));
+}
+
+#[test]
+fn mistakes_are_reported() {
+    assert_eq!(
+        parse("frobnicate"),
+        Err(String::from("not a command: frobnicate"))
+    );
+    assert_eq!(parse("set"), Err(String::from("usage: set NAME [VALUE]")));
+    assert_eq!(
+        parse("set a b c"),
+        Err(String::from("usage: set NAME [VALUE]"))
+    );
+    assert_eq!(parse("quit now"), Err(String::from("usage: quit")));
+    assert_eq!(parse("tag a b"), Err(String::from("usage: tag [TAG]")));
+    assert!(parse("source \"unterminated").is_err());
+}
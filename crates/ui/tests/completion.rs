@@ -0,0 +1,110 @@
+use std::{fs, process};
+
+use ui::completion::complete;
+use ui::config::Settings;
+
+/// Candidates for the last word of `line`, and where that word starts.
+fn candidates(line: &str) -> (usize, Vec<String>) {
+    let tags = [String::from("news"), String::from("nerd stuff")];
+    let completions = complete(line, &Settings::default(), &tags);
+    (completions.start, completions.candidates)
+}
+
+#[test]
+fn command_names_are_completed() {
+    assert_eq!(
+        candidates("s"),
+        (
+            0,
+            vec![
+                String::from("set"),
+                String::from("source"),
+                String::from("search"),
+                String::from("save")
+            ]
+        )
+    );
+    assert_eq!(candidates("dump").1, [String::from("dumpconfig")]);
+    assert_eq!(candidates("q"), (0, vec![String::from("quit")]));
+    assert_eq!(candidates("x").1, Vec::<String>::new());
+    // Arguments of unknown commands, and of commands that don't take any, aren't completed.
+    assert_eq!(candidates("quit ").1, Vec::<String>::new());
+    assert_eq!(candidates("frobnicate ").1, Vec::<String>::new());
+}
+
+#[test]
+fn setting_names_and_values_are_completed() {
+    assert_eq!(
+        candidates("set show-read-"),
+        (
+            4,
+            vec![
+                String::from("show-read-feeds"),
+                String::from("show-read-articles")
+            ]
+        )
+    );
+    assert_eq!(
+        candidates("set  show-read-feeds ").1,
+        [String::from("yes"), String::from("no")]
+    );
+    assert_eq!(candidates("set show-read-feeds n").1, [String::from("no")]);
+    // Other settings complete to their current value, quoted if necessary.
+    assert_eq!(
+        candidates("set feedlist-format ").1,
+        [String::from("\"%4i %n %9u %t\"")]
+    );
+    assert_eq!(candidates("set reload-threads 4 ").1, Vec::<String>::new());
+}
+
+#[test]
+fn tags_are_completed() {
+    assert_eq!(
+        candidates("tag ne").1,
+        [String::from("news"), String::from("nerd stuff")]
+    );
+    // `tag` takes just one.
+    assert_eq!(candidates("tag news nerd").1, Vec::<String>::new());
+}
+
+#[test]
+fn operations_are_completed() {
+    assert_eq!(
+        candidates("exec quit open-in-b").1,
+        [String::from("open-in-browser")]
+    );
+}
+
+#[test]
+fn paths_are_completed() {
+    let dir = std::env::temp_dir().join(format!("newsboat-tui-rs-completion-{}", process::id()));
+    fs::create_dir_all(dir.join("feeds")).unwrap();
+    fs::write(dir.join("feeds.opml"), "").unwrap();
+    fs::write(dir.join("config"), "").unwrap();
+    fs::write(dir.join(".hidden"), "").unwrap();
+    let dir_name = dir.display().to_string();
+
+    let line = format!("source {}/f", dir_name);
+    assert_eq!(
+        candidates(&line),
+        (
+            7,
+            vec![
+                format!("{}/feeds.opml", dir_name),
+                format!("{}/feeds/", dir_name)
+            ]
+        )
+    );
+    // Hidden files only turn up when asked for.
+    assert_eq!(candidates(&format!("source {}/", dir_name)).1.len(), 3);
+    assert_eq!(
+        candidates(&format!("source {}/.", dir_name)).1,
+        [format!("{}/.hidden", dir_name)]
+    );
+    assert_eq!(
+        candidates(&format!("source {}/nope/", dir_name)).1,
+        Vec::<String>::new()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,315 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::{fs, path::Path, process};
+
+use ui::config::{Config, ConfigError};
+use ui::event::Key;
+use ui::keymap::{Context, Dispatch, Lookup, Operation, PendingKeys};
+use ui::theme::Element;
+
+fn ctrl(c: char) -> Key {
+    Key::Ctrl(Box::new(Key::Char(c)))
+}
+
+#[test]
+fn settings_are_typed() {
+    let mut config = Config::default();
+    assert!(config.settings.show_read_feeds);
+    assert_eq!(config.settings.browser, None);
+
+    config
+        .load_str(
+            "set browser \"firefox --new-tab %u\"\n\
+             set show-read-feeds no\n\
+             set show-keymap-hint false\n\
+             set reload-threads 8\n\
+             set auto-reload yes\n\
+             set reload-time 15\n\
+             set history-limit 0\n",
+            Path::new("config"),
+        )
+        .unwrap();
+    let settings = &config.settings;
+    assert_eq!(settings.browser.as_deref(), Some("firefox --new-tab %u"));
+    assert!(!settings.show_read_feeds);
+    assert!(settings.show_read_articles);
+    assert!(!settings.show_keymap_hint);
+    assert_eq!(settings.reload_threads, 8);
+    assert!(settings.auto_reload);
+    assert_eq!(settings.reload_time, 15);
+    assert_eq!(settings.history_limit, 0);
+}
+
+#[test]
+fn backticks_and_backslashes_are_expanded() {
+    let mut config = Config::default();
+    config
+        .load_str(
+            "set reload-threads `echo 3`\n\
+             set browser \\\n\
+             \"w3m %u\"  # continued\n\
+             set show-read-articles `printf n`o\n",
+            Path::new("config"),
+        )
+        .unwrap();
+    assert_eq!(config.settings.reload_threads, 3);
+    assert_eq!(config.settings.browser.as_deref(), Some("w3m %u"));
+    assert!(!config.settings.show_read_articles);
+}
+
+#[test]
+fn colors_become_styles() {
+    let mut config = Config::default();
+    config
+        .load_str(
+            "color listfocus black yellow bold underline\n\
+             color background default color236\n\
+             color article \"#c0ffee\" default\n",
+            Path::new("config"),
+        )
+        .unwrap();
+    assert_eq!(
+        config.theme.style(Element::ListFocus),
+        Style::default()
+            .fg(Color::Black)
+            .bg(Color::Yellow)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+    );
+    assert_eq!(
+        config.theme.style(Element::Background),
+        Style::default().fg(Color::Reset).bg(Color::Indexed(236))
+    );
+    assert_eq!(
+        config.theme.style(Element::Article),
+        Style::default()
+            .fg(Color::Rgb(0xc0, 0xff, 0xee))
+            .bg(Color::Reset)
+    );
+}
+
+#[test]
+fn macros_run_after_the_macro_prefix() {
+    let mut config = Config::default();
+    config
+        .load_str(
+            "macro b open ; down ; open-in-browser\n",
+            Path::new("config"),
+        )
+        .unwrap();
+    let keymap = &config.keymap;
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char(',')]),
+        Lookup::Prefix(None)
+    );
+
+    let mut pending = PendingKeys::default();
+    assert_eq!(
+        pending.push(Key::Char(','), keymap, Context::FeedList),
+        Dispatch::Pending
+    );
+    assert_eq!(
+        pending.push(Key::Char('b'), keymap, Context::FeedList),
+        Dispatch::Macro(vec![
+            Operation::Open,
+            Operation::Down,
+            Operation::OpenInBrowser
+        ])
+    );
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char(','), Key::Char('x')]),
+        Lookup::Unbound
+    );
+}
+
+#[test]
+fn included_files_are_relative_to_the_including_one() {
+    let dir = std::env::temp_dir().join(format!("newsboat-tui-rs-config-{}", process::id()));
+    fs::create_dir_all(dir.join("conf.d")).unwrap();
+    fs::write(
+        dir.join("config"),
+        "include conf.d/keys\nset reload-threads 2\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("conf.d/keys"),
+        "bind-key j down\n\ninclude ../broken\n",
+    )
+    .unwrap();
+    fs::write(dir.join("broken"), "bind-key\n").unwrap();
+    fs::write(dir.join("loop"), "include loop\n").unwrap();
+
+    let mut config = Config::default();
+    let error = config
+        .load_file(&dir.join("config"))
+        .unwrap_err()
+        .to_string();
+    // The error points into the file that has it, not the one that included it.
+    assert!(
+        error.ends_with("broken:1: usage: bind-key KEY OPERATION [DIALOG]"),
+        "{}",
+        error
+    );
+    assert_eq!(
+        config.keymap.lookup(Context::Article, &[Key::Char('j')]),
+        Lookup::Bound(Operation::Down)
+    );
+
+    fs::write(dir.join("broken"), "").unwrap();
+    let mut config = Config::default();
+    config.load_file(&dir.join("config")).unwrap();
+    assert_eq!(config.settings.reload_threads, 2);
+
+    let error = config.load_file(&dir.join("loop")).unwrap_err().to_string();
+    assert!(
+        error.contains("loop:1:") && error.contains("includes itself"),
+        "{}",
+        error
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn config_binds_and_unbinds_keys() {
+    let mut config = Config::default();
+    config
+        .load_str(
+            "# Vim-like movement\n\
+             bind-key j down\n\
+             bind-key k up articlelist\n\
+             unbind-key R feedlist\n\
+             bind-key \"^N\" next-dialog global  # trailing comment\n",
+            Path::new("config"),
+        )
+        .unwrap();
+
+    let keymap = &config.keymap;
+    assert_eq!(
+        keymap.lookup(Context::Article, &[Key::Char('j')]),
+        Lookup::Bound(Operation::Down)
+    );
+    assert_eq!(
+        keymap.lookup(Context::ArticleList, &[Key::Char('k')]),
+        Lookup::Bound(Operation::Up)
+    );
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('k')]),
+        Lookup::Unbound
+    );
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('R')]),
+        Lookup::Unbound
+    );
+    assert_eq!(
+        keymap.lookup(Context::Article, &[ctrl('n')]),
+        Lookup::Bound(Operation::NextDialog)
+    );
+
+    config
+        .load_str("unbind-key -a\n", Path::new("config"))
+        .unwrap();
+    assert_eq!(
+        config.keymap.lookup(Context::FeedList, &[Key::Up]),
+        Lookup::Unbound
+    );
+}
+
+#[test]
+fn config_reports_malformed_lines_with_their_position() {
+    let cases = [
+        ("bind-key j down\nbind-key j\n", 2, "usage: bind-key"),
+        ("bind-key g<NOPE> down\n", 1, "`g<NOPE>` is not a key"),
+        ("\nbind-key j dance\n", 2, "unknown operation `dance`"),
+        (
+            "bind-key j down everywhere\n",
+            1,
+            "unknown dialog `everywhere`",
+        ),
+        ("unbind-key\n", 1, "usage: unbind-key"),
+        ("frobnicate\n", 1, "unknown command `frobnicate`"),
+        ("set reload-threads 0\n", 1, "should be a positive number"),
+        ("set reload-time 0\n", 1, "should be a positive number"),
+        ("set show-read-feeds maybe\n", 1, "should be yes or no"),
+        ("set history-limit -1\n", 1, "should be a number"),
+        ("set colour red\n", 1, "unknown setting `colour`"),
+        (
+            "set datetime-format \"%d %Q\"\n",
+            1,
+            "isn't a valid date format",
+        ),
+        ("color listfocus pink default\n", 1, "unknown color `pink`"),
+        (
+            "color listfocus \"#c0ffe\" default\n",
+            1,
+            "unknown color `#c0ffe`",
+        ),
+        (
+            "color sidebar red default\n",
+            1,
+            "unknown element `sidebar`",
+        ),
+        ("macro x open;down\n", 1, "unknown operation `open;down`"),
+        (
+            "set browser \\\n  w3m\n\nset browser `false`\n",
+            4,
+            "`false` failed",
+        ),
+    ];
+    for (text, expected_line, expected_message) in cases {
+        let mut config = Config::default();
+        match config.load_str(text, Path::new("/home/user/config")) {
+            Err(error @ ConfigError::Malformed { .. }) => {
+                let message = error.to_string();
+                let prefix = format!("/home/user/config:{}: ", expected_line);
+                assert!(message.starts_with(&prefix), "{}", message);
+                assert!(message.contains(expected_message), "{}", message);
+            }
+            other => panic!("expected an error for {:?}, got {:?}", text, other),
+        }
+    }
+}
+
+#[test]
+fn dumped_config_loads_back_the_same() {
+    let mut config = Config::default();
+    config
+        .load_str(
+            "set browser \"`true`w3m\"\n\
+             set show-read-feeds no\n\
+             set feedlist-format \"%4i %t\"\n\
+             color listfocus black yellow bold\n\
+             color article \"#ffd700\" default\n\
+             bind-key j down\n\
+             unbind-key R feedlist\n\
+             unbind-key q article\n\
+             macro b open ; open-in-browser\n",
+            Path::new("config"),
+        )
+        .unwrap();
+    let dump = config.dump();
+    // Backticks are quoted, so that loading the dump doesn't run the command in them.
+    assert!(dump.contains("set browser \"`true`w3m\"\n"));
+    assert!(dump.contains("set show-read-feeds no\n"));
+    assert!(dump.contains("color listfocus black yellow bold\n"));
+    assert!(dump.contains("macro b open ; open-in-browser\n"));
+
+    let mut reloaded = Config::default();
+    reloaded.load_str(&dump, Path::new("dump")).unwrap();
+    assert_eq!(reloaded.dump(), dump);
+    assert_eq!(reloaded.settings.browser.as_deref(), Some("`true`w3m"));
+    assert_eq!(
+        reloaded.keymap.lookup(Context::FeedList, &[Key::Char('j')]),
+        Lookup::Bound(Operation::Down)
+    );
+    assert_eq!(
+        reloaded.keymap.lookup(Context::FeedList, &[Key::Char('R')]),
+        Lookup::Unbound
+    );
+    assert_eq!(
+        reloaded.keymap.lookup(Context::Article, &[Key::Char('q')]),
+        Lookup::Unbound
+    );
+    assert_eq!(
+        reloaded.keymap.lookup(Context::FeedList, &[Key::Char('q')]),
+        Lookup::Bound(Operation::Quit)
+    );
+}
@@ -0,0 +1,98 @@
+use std::{cell::RefCell, rc::Rc};
+use unicode_width::UnicodeWidthStr;
+
+use ui::config::Settings;
+use ui::feed::{Feed, Item};
+use ui::format::{self, format};
+
+/// Expand `template` with specifiers `t` (a title), `a` (an author) and `e` (empty).
+fn expand(template: &str, width: usize) -> String {
+    format(template, width, &|specifier| match specifier {
+        't' => Some(String::from("Fabio Franchino’s blog")),
+        'a' => Some(String::from("千葉")),
+        'e' => Some(String::new()),
+        _ => None,
+    })
+}
+
+#[test]
+fn specifiers_are_padded_and_cut_to_width() {
+    let cases = [
+        ("%t", "Fabio Franchino’s blog"),
+        ("[%8t]", "[Fabio Fr]"),
+        ("[%-25t]", "[Fabio Franchino’s blog   ]"),
+        ("[%25t]", "[   Fabio Franchino’s blog]"),
+        // Wide characters take up two columns, and aren't split in half.
+        ("[%6a]", "[  千葉]"),
+        ("[%-3a]", "[千 ]"),
+        ("100%% %x%e!", "100% !"),
+    ];
+    for (template, expected) in cases {
+        assert_eq!(expand(template, 80), expected, "{}", template);
+    }
+}
+
+#[test]
+fn huge_widths_are_limited() {
+    let line = expand("%-99999999999999999999999t|", 80);
+    assert_eq!(line.width(), u16::MAX as usize + 1);
+    assert!(line.starts_with("Fabio Franchino’s blog "));
+    assert!(line.ends_with(" |"));
+}
+
+#[test]
+fn conditionals_depend_on_whether_the_value_is_empty() {
+    let cases = [
+        ("%?t?by %a&anonymous?.", "by 千葉."),
+        ("%?e?by %a&anonymous?.", "anonymous."),
+        ("%?x?unknown?.", "."),
+        ("%?a?[%-5a]?", "[千葉 ]"),
+    ];
+    for (template, expected) in cases {
+        assert_eq!(expand(template, 80), expected, "{}", template);
+    }
+}
+
+#[test]
+fn fill_pushes_the_rest_to_the_right_edge() {
+    assert_eq!(expand("%a%> %a", 10), "千葉  千葉");
+    assert_eq!(expand("%a%>-|", 8), "千葉---|");
+    // Too narrow: nothing to fill, but nothing is cut either.
+    assert_eq!(expand("%t%>.%a", 4), "Fabio Franchino’s blog千葉");
+}
+
+#[test]
+fn default_formats_look_like_newsboat() {
+    let settings = Settings::default();
+    let item = Rc::new(RefCell::new(Item {
+        title: String::from("Post"),
+        unread: true,
+        content: "x".repeat(4558),
+        ..Item::default()
+    }));
+    let mut feed = Feed {
+        title: String::from("Fabio Franchino’s blog"),
+        items: vec![item.clone()],
+        ..Feed::default()
+    };
+    for _ in 0..22 {
+        feed.items.push(Rc::new(RefCell::new(Item::default())));
+    }
+
+    assert_eq!(
+        format::feed_row(&settings, 80, 3, &feed),
+        "   3 N    (1/23) Fabio Franchino’s blog"
+    );
+    assert_eq!(
+        format::item_row(&settings, 80, 12, &item.borrow()),
+        "  12 N           4.6K  Post"
+    );
+    assert_eq!(
+        format::feedlist_title(&settings, 80, 1, 7),
+        "Newsboat 2.20 (ну, почти) - Your Feeds (1 unread, 7 total)"
+    );
+    assert_eq!(
+        format::itemview_title(&settings, 80, &feed, &item.borrow()),
+        "Newsboat 2.20 (ну, почти) - Article 'Post' (1 unread, 23 total)"
+    );
+}
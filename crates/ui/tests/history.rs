@@ -0,0 +1,108 @@
+use ratatui::backend::TestBackend;
+use std::{fs, process, rc::Rc};
+
+use ui::app::App;
+use ui::cache::Cache;
+use ui::config::Config;
+use ui::event::Key;
+use ui::example;
+use ui::history::History;
+
+#[test]
+fn blank_lines_and_repeats_are_not_remembered() {
+    let mut history = History::new(10);
+    for line in [
+        "quit",
+        "",
+        "  ",
+        "set browser w3m",
+        "set browser w3m",
+        "quit",
+    ] {
+        history.add(line);
+    }
+    assert_eq!(history.entries(), ["quit", "set browser w3m", "quit"]);
+}
+
+#[test]
+fn oldest_lines_are_forgotten_over_the_limit() {
+    let mut history = History::new(2);
+    for line in ["one", "two", "three"] {
+        history.add(line);
+    }
+    assert_eq!(history.entries(), ["two", "three"]);
+
+    let mut history = History::new(0);
+    history.add("one");
+    assert!(history.entries().is_empty());
+}
+
+#[test]
+fn changing_the_limit_applies_right_away() {
+    let mut history = History::new(3);
+    for line in ["one", "two", "three"] {
+        history.add(line);
+    }
+    history.set_limit(1);
+    assert_eq!(history.entries(), ["three"]);
+
+    let cache = Rc::new(Cache::in_memory().unwrap());
+    let mut app: App<TestBackend> =
+        App::new(example::example_feeds(), cache, None, Config::default());
+    for line in [
+        "set browser w3m\n",
+        "set history-limit 1\n",
+        "set browser lynx\n",
+    ] {
+        app.handle_key(Key::Char(':'));
+        for c in line.chars() {
+            app.handle_key(Key::Char(c));
+        }
+    }
+    assert_eq!(
+        app.cmdline_history().borrow().entries(),
+        ["set browser lynx"]
+    );
+}
+
+#[test]
+fn history_survives_a_round_trip_through_a_file() {
+    let path = std::env::temp_dir().join(format!("newsboat-tui-rs-history-{}", process::id()));
+
+    // A file that doesn't exist yet is an empty history.
+    let mut history = History::new(3);
+    history.load_file(&path).unwrap();
+    assert!(history.entries().is_empty());
+
+    for line in ["one", "two", "три"] {
+        history.add(line);
+    }
+    history.write_file(&path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\nтри\n");
+
+    // Lines entered before loading are newer than the ones in the file.
+    let mut loaded = History::new(3);
+    loaded.add("four");
+    loaded.load_file(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.entries(), ["two", "три", "four"]);
+}
+
+#[test]
+fn commands_and_searches_are_saved_separately() {
+    let dir = std::env::temp_dir().join(format!("newsboat-tui-rs-histories-{}", process::id()));
+    let cache = Rc::new(Cache::in_memory().unwrap());
+    let mut app: App<TestBackend> =
+        App::new(example::example_feeds(), cache, None, Config::default());
+    app.load_history(&dir).unwrap();
+    for c in ":set browser w3m\n/news\n".chars() {
+        app.handle_key(Key::Char(c));
+    }
+    app.save_state().unwrap();
+
+    let cmdline = fs::read_to_string(dir.join("history.cmdline")).unwrap();
+    let search = fs::read_to_string(dir.join("history.search")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(cmdline, "set browser w3m\n");
+    assert_eq!(search, "news\n");
+}
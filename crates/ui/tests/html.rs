@@ -0,0 +1,198 @@
+use ratatui::{style::Modifier, text::Line};
+
+use ui::html::{render, Link, LinkKind};
+
+/// Width of the lines in most tests; wide enough that nothing is wrapped.
+const WIDTH: usize = 80;
+
+/// Text of the `lines`, without styles.
+fn plain(lines: &[Line]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        })
+        .collect()
+}
+
+/// Render `html` into plain lines.
+fn render_plain(html: &str) -> Vec<String> {
+    plain(&render(html, WIDTH).lines)
+}
+
+#[test]
+fn paragraphs_are_separated_and_whitespace_collapsed() {
+    assert_eq!(
+        render_plain(
+            "<p>First\n   paragraph.</p><p>Second<br>line &amp; &lt;more&gt; &#8212; &hellip;\
+             &nbsp;&unknown; AT&T</p>\n<!-- <p>comment</p> --><script>alert('<p>')</script>"
+        ),
+        [
+            "First paragraph.",
+            "",
+            "Second",
+            "line & <more> — …\u{a0}&unknown; AT&T",
+        ]
+    );
+    assert_eq!(
+        render_plain("Plain text,\nno tags < 3"),
+        ["Plain text, no tags < 3"]
+    );
+    assert!(render("  <p></p>  ", WIDTH).lines.is_empty());
+}
+
+#[test]
+fn inline_elements_are_styled() {
+    let rendered = render(
+        "<h2>Title</h2>Some <b>bold, <i>both</i></b> and <u>under</u>.",
+        WIDTH,
+    );
+    assert_eq!(
+        plain(&rendered.lines),
+        ["Title", "", "Some bold, both and under."]
+    );
+
+    let spans = &rendered.lines[2].spans;
+    let styled = spans
+        .iter()
+        .map(|span| (span.content.as_ref(), span.style.add_modifier))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        styled,
+        [
+            ("Some ", Modifier::empty()),
+            ("bold, ", Modifier::BOLD),
+            ("both", Modifier::BOLD | Modifier::ITALIC),
+            (" and ", Modifier::empty()),
+            ("under", Modifier::UNDERLINED),
+            (".", Modifier::empty()),
+        ]
+    );
+    assert_eq!(
+        rendered.lines[0].spans[0].style.add_modifier,
+        Modifier::BOLD
+    );
+}
+
+#[test]
+fn pre_keeps_whitespace() {
+    assert_eq!(
+        render_plain("<p>Code:</p><pre>\nfn main() {\n    println!(\"&lt;hi>\");\n}\n</pre>After"),
+        [
+            "Code:",
+            "",
+            "fn main() {",
+            "    println!(\"<hi>\");",
+            "}",
+            "",
+            "After",
+        ]
+    );
+}
+
+#[test]
+fn lists_are_numbered_and_nested() {
+    assert_eq!(
+        render_plain(
+            "<ul><li>One<li>Two<ol start=\"3\"><li>Three</li><li>Four<br>more</li></ol></li>\
+             <li><ul><li>Deep</li></ul></li></ul>After"
+        ),
+        [
+            "* One",
+            "* Two",
+            "  3. Three",
+            "  4. Four",
+            "     more",
+            "*",
+            "  * Deep",
+            "",
+            "After",
+        ]
+    );
+}
+
+#[test]
+fn list_numbers_stop_at_the_largest_one() {
+    assert_eq!(
+        render_plain("<ol start=\"18446744073709551615\"><li>a<li>b</ol>"),
+        ["18446744073709551615. a", "18446744073709551615. b"]
+    );
+}
+
+#[test]
+fn quotes_are_marked() {
+    assert_eq!(
+        render_plain("<p>They said:</p><blockquote><p>One.</p><p>Two.</p></blockquote>Right."),
+        ["They said:", "", "> One.", ">", "> Two.", "", "Right."]
+    );
+}
+
+#[test]
+fn tables_are_lined_up() {
+    assert_eq!(
+        render_plain(
+            "<table><tr><th>Name</th><th>Size</th></tr>\
+             <tr><td>ну</td><td><b>1</b> KB</td></tr>\
+             <tr><td>longer name</td></tr></table>"
+        ),
+        [
+            "Name        | Size",
+            "------------+-----",
+            "ну          | 1 KB",
+            "longer name |",
+        ]
+    );
+}
+
+#[test]
+fn links_and_images_are_listed_below_the_text() {
+    let rendered = render(
+        "See <a href=\"https://example.com/\">this</a> and <a href='https://example.com/'>that</a>, \
+         <img src=https://example.com/a.png alt=\"a cat\"> <img src=\"/b.png\"> \
+         <a name=\"anchor\">nothing</a>",
+        WIDTH,
+    );
+    assert_eq!(
+        plain(&rendered.lines),
+        [
+            "See this[1] and that[1], [image 2 (a cat)] [image 3] nothing",
+            "",
+            "Links:",
+            "[1]: https://example.com/ (link)",
+            "[2]: https://example.com/a.png (image)",
+            "[3]: /b.png (image)",
+        ]
+    );
+    assert_eq!(
+        rendered.links[0],
+        Link {
+            url: String::from("https://example.com/"),
+            kind: LinkKind::Link,
+        }
+    );
+}
+
+#[test]
+fn lines_are_wrapped_under_their_prefixes() {
+    assert_eq!(
+        plain(
+            &render(
+                "<p>A paragraph that is too long.</p>\
+                 <blockquote><ol><li>Quoted item, also long</li></ol></blockquote>",
+                16,
+            )
+            .lines
+        ),
+        [
+            "A paragraph that",
+            "is too long.",
+            "",
+            "> 1. Quoted",
+            ">    item, also",
+            ">    long",
+        ]
+    );
+}
@@ -0,0 +1,277 @@
+use ui::event::Key;
+use ui::keymap::{
+    key_name, keys_name, parse_key, parse_keys, Context, Dispatch, KeyMap, Lookup, Operation,
+    PendingKeys, MAX_COUNT,
+};
+
+fn ctrl(c: char) -> Key {
+    Key::Ctrl(Box::new(Key::Char(c)))
+}
+
+#[test]
+fn key_names_parse_and_print_back() {
+    let cases = [
+        ("q", Key::Char('q')),
+        ("ENTER", Key::Char('\n')),
+        ("SPACE", Key::Char(' ')),
+        ("UP", Key::Up),
+        ("NPAGE", Key::PageDown),
+        ("F5", Key::F(5)),
+        ("^V", ctrl('v')),
+        ("M-x", Key::Alt(Box::new(Key::Char('x')))),
+        ("ф", Key::Char('ф')),
+    ];
+    for (name, key) in cases {
+        assert_eq!(parse_key(name), Some(key.clone()), "{}", name);
+        assert_eq!(key_name(&key), name);
+    }
+
+    // Ctrl combinations are case-insensitive.
+    assert_eq!(parse_key("^v"), Some(ctrl('v')));
+
+    for name in ["", "qq", "F13", "UPP"] {
+        assert_eq!(parse_key(name), None, "{}", name);
+    }
+}
+
+#[test]
+fn key_sequence_names_parse_and_print_back() {
+    let cases = [
+        ("UP", vec![Key::Up]),
+        ("gg", vec![Key::Char('g'), Key::Char('g')]),
+        ("g<ENTER>", vec![Key::Char('g'), Key::Char('\n')]),
+        ("^Wj", vec![ctrl('w'), Key::Char('j')]),
+        (
+            "<F1><M-x>",
+            vec![Key::F(1), Key::Alt(Box::new(Key::Char('x')))],
+        ),
+    ];
+    for (name, keys) in cases {
+        assert_eq!(parse_keys(name), Some(keys.clone()), "{}", name);
+        assert_eq!(keys_name(&keys), name);
+    }
+
+    assert_eq!(parse_keys(""), None);
+    assert_eq!(parse_keys("g<NOPE>"), None);
+}
+
+#[test]
+fn dialog_bindings_take_precedence_over_global_ones() {
+    let mut keymap = KeyMap::default();
+    assert_eq!(
+        keymap.lookup(Context::Article, &[Key::Char('q')]),
+        Lookup::Bound(Operation::Quit)
+    );
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('r')]),
+        Lookup::Bound(Operation::Reload)
+    );
+    assert_eq!(
+        keymap.lookup(Context::Article, &[Key::Char('r')]),
+        Lookup::Unbound
+    );
+
+    keymap.bind(
+        vec![Key::Char('q')],
+        Operation::Open,
+        Some(Context::Article),
+    );
+    assert_eq!(
+        keymap.lookup(Context::Article, &[Key::Char('q')]),
+        Lookup::Bound(Operation::Open)
+    );
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('q')]),
+        Lookup::Bound(Operation::Quit)
+    );
+
+    // Unbinding in a dialog doesn't reveal the global binding there.
+    keymap.unbind(&[Key::Char('q')], Some(Context::Article));
+    assert_eq!(
+        keymap.lookup(Context::Article, &[Key::Char('q')]),
+        Lookup::Unbound
+    );
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('q')]),
+        Lookup::Bound(Operation::Quit)
+    );
+
+    keymap.unbind(&[Key::Char('q')], None);
+    assert_eq!(
+        keymap.lookup(Context::Article, &[Key::Char('q')]),
+        Lookup::Unbound
+    );
+}
+
+#[test]
+fn hints_show_keys_that_actually_perform_the_operation() {
+    let mut keymap = KeyMap::default();
+    let operations = [(Operation::Quit, "Quit"), (Operation::Reload, "Reload")];
+    assert_eq!(
+        keymap.hints(Context::FeedList, &operations),
+        "q:Quit r:Reload"
+    );
+    assert_eq!(keymap.hints(Context::Article, &operations), "q:Quit");
+
+    // The global `q` no longer quits the feedlist, so it shouldn't be advertised there.
+    keymap.bind(
+        vec![Key::Char('q')],
+        Operation::Reload,
+        Some(Context::FeedList),
+    );
+    keymap.bind(vec![ctrl('x')], Operation::Quit, Some(Context::FeedList));
+    assert_eq!(
+        keymap.hints(Context::FeedList, &operations),
+        "^X:Quit q:Reload"
+    );
+}
+
+/// Feed `keys` to `pending` one by one, returning what the last one resulted in.
+fn push_all(pending: &mut PendingKeys, keymap: &KeyMap, keys: &str) -> Dispatch {
+    let mut dispatch = Dispatch::Unbound;
+    for key in parse_keys(keys).unwrap() {
+        dispatch = pending.push(key, keymap, Context::ArticleList);
+    }
+    dispatch
+}
+
+#[test]
+fn sequences_wait_for_the_rest_of_the_keys() {
+    let keymap = KeyMap::default();
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('g')]),
+        Lookup::Prefix(None)
+    );
+
+    let mut pending = PendingKeys::default();
+    assert_eq!(push_all(&mut pending, &keymap, "g"), Dispatch::Pending);
+    assert_eq!(pending.to_string(), "g");
+    assert_eq!(
+        push_all(&mut pending, &keymap, "g"),
+        Dispatch::Perform(Operation::Home, None)
+    );
+    assert!(pending.is_empty());
+
+    // A key that doesn't continue any sequence drops the ones typed so far.
+    assert_eq!(push_all(&mut pending, &keymap, "gx"), Dispatch::Unbound);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn counts_are_passed_along_with_the_operation() {
+    let keymap = KeyMap::default();
+    let mut pending = PendingKeys::default();
+
+    assert_eq!(push_all(&mut pending, &keymap, "12"), Dispatch::Pending);
+    assert_eq!(pending.to_string(), "12");
+    assert!(!pending.is_incomplete_sequence());
+    assert_eq!(
+        push_all(&mut pending, &keymap, "<DOWN>"),
+        Dispatch::Perform(Operation::Down, Some(12))
+    );
+
+    assert_eq!(
+        push_all(&mut pending, &keymap, "30G"),
+        Dispatch::Perform(Operation::End, Some(30))
+    );
+
+    // Zero doesn't start a count.
+    assert_eq!(push_all(&mut pending, &keymap, "0"), Dispatch::Unbound);
+
+    // Esc gives up on the count.
+    assert_eq!(push_all(&mut pending, &keymap, "5<ESC>"), Dispatch::Unbound);
+    assert!(pending.is_empty());
+
+    // Bound digits aren't counts.
+    let mut keymap = keymap;
+    keymap.bind(vec![Key::Char('5')], Operation::Open, None);
+    assert_eq!(
+        push_all(&mut pending, &keymap, "5"),
+        Dispatch::Perform(Operation::Open, None)
+    );
+}
+
+#[test]
+fn global_keys_can_be_unbound_in_one_dialog() {
+    let mut keymap = KeyMap::default();
+    keymap.unbind(&[Key::Char('q')], Some(Context::FeedList));
+    keymap.unbind(&parse_keys("gg").unwrap(), Some(Context::FeedList));
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('q')]),
+        Lookup::Unbound
+    );
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('g')]),
+        Lookup::Unbound
+    );
+    for context in [Context::Global, Context::ArticleList, Context::Article] {
+        assert_eq!(
+            keymap.lookup(context, &[Key::Char('q')]),
+            Lookup::Bound(Operation::Quit)
+        );
+        assert_eq!(
+            keymap.lookup(context, &parse_keys("gg").unwrap()),
+            Lookup::Bound(Operation::Home)
+        );
+    }
+
+    // Keys bound everywhere can be taken away from one dialog, too.
+    keymap.bind(vec![Key::Char('j')], Operation::Down, None);
+    keymap.unbind(&[Key::Char('j')], Some(Context::FeedList));
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('j')]),
+        Lookup::Unbound
+    );
+    assert_eq!(
+        keymap.lookup(Context::Article, &[Key::Char('j')]),
+        Lookup::Bound(Operation::Down)
+    );
+
+    // Binding the keys in the dialog again brings them back.
+    keymap.bind(
+        vec![Key::Char('q')],
+        Operation::Open,
+        Some(Context::FeedList),
+    );
+    assert_eq!(
+        keymap.lookup(Context::FeedList, &[Key::Char('q')]),
+        Lookup::Bound(Operation::Open)
+    );
+}
+
+#[test]
+fn huge_counts_are_capped() {
+    let keymap = KeyMap::default();
+    let mut pending = PendingKeys::default();
+
+    assert_eq!(
+        push_all(&mut pending, &keymap, "99999999999999999999999<DOWN>"),
+        Dispatch::Perform(Operation::Down, Some(MAX_COUNT))
+    );
+}
+
+#[test]
+fn ambiguous_sequences_are_resolved_by_a_timeout() {
+    let mut keymap = KeyMap::default();
+    keymap.bind(vec![Key::Char('z')], Operation::Open, None);
+    assert_eq!(
+        keymap.lookup(Context::ArticleList, &[Key::Char('z')]),
+        Lookup::Prefix(Some(Operation::Open))
+    );
+
+    let mut pending = PendingKeys::default();
+    assert_eq!(push_all(&mut pending, &keymap, "3z"), Dispatch::Pending);
+    assert!(pending.is_incomplete_sequence());
+    assert_eq!(
+        pending.time_out(&keymap, Context::ArticleList),
+        Dispatch::Perform(Operation::Open, Some(3))
+    );
+
+    // An incomplete sequence that means nothing on its own is dropped.
+    assert_eq!(push_all(&mut pending, &keymap, "g"), Dispatch::Pending);
+    assert_eq!(
+        pending.time_out(&keymap, Context::ArticleList),
+        Dispatch::Unbound
+    );
+    assert!(pending.is_empty());
+}
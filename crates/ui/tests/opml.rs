@@ -0,0 +1,83 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use ui::feed::Feed;
+use ui::opml::{export, import_file, import_str, OpmlError};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("data")
+        .join(name)
+}
+
+#[test]
+fn imports_nested_outlines_as_tags() {
+    let subscriptions = import_file(&fixture("opml10.xml")).unwrap();
+    let imported = subscriptions
+        .iter()
+        .map(|s| (s.url.as_str(), s.tags.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        imported,
+        [
+            (
+                "https://this-week-in-rust.org/rss.xml",
+                vec![String::from("Tech"), String::from("Rust")]
+            ),
+            // A feed listed in two folders gets the tags of both.
+            (
+                "https://lwn.net/headlines/rss",
+                vec![String::from("Tech"), String::from("News")]
+            ),
+            ("https://example.com/old.xml", vec![]),
+        ]
+    );
+}
+
+#[test]
+fn exported_subscriptions_can_be_imported_back() {
+    let feed = |url: &str, title: &str, tags: &[&str]| {
+        Rc::new(RefCell::new(Feed {
+            url: url.to_string(),
+            title: title.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Feed::default()
+        }))
+    };
+    let feeds = vec![
+        feed(
+            "https://example.com/a.xml?x=1&y=2",
+            "A & B",
+            &["news", "tag, with comma"],
+        ),
+        feed("https://example.com/b.xml", "\"Quoted\"", &[]),
+        Rc::new(RefCell::new(Feed {
+            url: String::from("query:Unread:unread = \"yes\""),
+            query: Some(ui::filter::Filter::parse("unread = \"yes\"").unwrap()),
+            ..Feed::default()
+        })),
+    ];
+
+    let opml = export(&feeds);
+    assert!(opml.contains(r#"title="A &amp; B""#), "{}", opml);
+
+    let subscriptions = import_str(&opml).unwrap();
+    // Query feeds aren't exported.
+    assert_eq!(subscriptions.len(), 2);
+    assert_eq!(subscriptions[0].url, "https://example.com/a.xml?x=1&y=2");
+    assert_eq!(subscriptions[0].tags, ["news", "tag  with comma"]);
+    assert_eq!(subscriptions[1].url, "https://example.com/b.xml");
+    assert!(subscriptions[1].tags.is_empty());
+}
+
+#[test]
+fn rejects_documents_that_are_not_opml() {
+    match import_file(&fixture("rss20.xml")) {
+        Err(OpmlError::NotOpml(root)) => assert_eq!(root, "rss"),
+        other => panic!("expected NotOpml, got {:?}", other),
+    }
+    assert!(matches!(
+        import_str("<opml><body><outline text=\"Unclosed\"></body></opml>"),
+        Err(OpmlError::Xml(_))
+    ));
+}
@@ -0,0 +1,173 @@
+use chrono::{TimeZone, Utc};
+use std::path::PathBuf;
+
+use ui::feed::{Enclosure, Feed};
+use ui::parser::{parse_file, ParseError};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("data")
+        .join(name)
+}
+
+fn parse_fixture(name: &str) -> Feed {
+    parse_file(&fixture(name)).expect("fixture should parse")
+}
+
+#[test]
+fn parses_rss_20() {
+    let feed = parse_fixture("rss20.xml");
+    assert_eq!(feed.title, "Example RSS 2.0 feed");
+    assert_eq!(feed.link, "https://example.com/");
+    assert_eq!(feed.items.len(), 2);
+
+    let first = feed.items[0].borrow();
+    assert_eq!(first.title, "First & foremost");
+    assert_eq!(first.url, "https://example.com/first");
+    assert_eq!(first.guid, "urn:example:first");
+    assert_eq!(first.author, "jane@example.com (Jane Doe)");
+    assert_eq!(
+        first.date,
+        Some(Utc.with_ymd_and_hms(2004, 3, 1, 21, 6, 7).unwrap())
+    );
+    // content:encoded wins over description, and CDATA is taken verbatim.
+    assert_eq!(
+        first.content,
+        "<p>Full text of the <b>first</b> item&nbsp;</p>"
+    );
+    assert_eq!(
+        first.enclosure,
+        Some(Enclosure {
+            url: String::from("https://example.com/first.mp3"),
+            mime_type: String::from("audio/mpeg"),
+            length: Some(123456),
+        })
+    );
+    assert!(first.unread);
+
+    let second = feed.items[1].borrow();
+    assert_eq!(second.author, "John Doe");
+    // Without a GUID, the link identifies the item.
+    assert_eq!(second.guid, "https://example.com/second");
+    assert_eq!(
+        second.date,
+        Some(Utc.with_ymd_and_hms(2004, 3, 3, 0, 0, 0).unwrap())
+    );
+    assert_eq!(
+        second.content,
+        "<p>Escaped <i>HTML</i>&nbsp;description</p>"
+    );
+    assert_eq!(second.enclosure, None);
+}
+
+#[test]
+fn parses_rss_091_with_html_entities() {
+    let feed = parse_fixture("rss091.xml");
+    assert_eq!(feed.title, "Example RSS 0.91 feed");
+    assert_eq!(feed.items.len(), 1);
+
+    let item = feed.items[0].borrow();
+    assert_eq!(item.title, "Only item");
+    assert_eq!(item.content, "Nothing but a description é");
+    assert_eq!(item.date, None);
+}
+
+#[test]
+fn parses_rss_10() {
+    let feed = parse_fixture("rss10.xml");
+    assert_eq!(feed.title, "Example RSS 1.0 feed");
+    assert_eq!(feed.items.len(), 2);
+
+    let item = feed.items[0].borrow();
+    assert_eq!(item.title, "RDF item");
+    assert_eq!(item.guid, "https://example.com/rdf-item");
+    assert_eq!(item.author, "Alice");
+    assert_eq!(item.content, "Described in RDF");
+    assert_eq!(
+        item.date,
+        Some(Utc.with_ymd_and_hms(2004, 3, 1, 21, 6, 7).unwrap())
+    );
+
+    // Dublin Core dates may lack the time.
+    let undated = feed.items[1].borrow();
+    assert_eq!(
+        undated.date,
+        Some(Utc.with_ymd_and_hms(2004, 3, 2, 0, 0, 0).unwrap())
+    );
+}
+
+#[test]
+fn parses_rss_090() {
+    let feed = parse_fixture("rss090.xml");
+    assert_eq!(feed.title, "Example RSS 0.90 feed");
+    assert_eq!(feed.items.len(), 1);
+    assert_eq!(feed.items[0].borrow().url, "https://example.com/ancient");
+}
+
+#[test]
+fn parses_atom_10() {
+    let feed = parse_fixture("atom10.xml");
+    // Titles are kept as-is, even if they're HTML.
+    assert_eq!(feed.title, "Example <b>Atom</b> feed");
+    // The "self" link is not the feed's web page.
+    assert_eq!(feed.link, "https://example.com/");
+    assert_eq!(feed.items.len(), 2);
+
+    let first = feed.items[0].borrow();
+    assert_eq!(first.url, "https://example.com/atom-first");
+    assert_eq!(first.guid, "urn:uuid:1225c695-cfb8-4ebb-aaaa-80da344efa6a");
+    assert_eq!(first.author, "Entry Author");
+    // Publication date wins over the date of the last update.
+    assert_eq!(
+        first.date,
+        Some(Utc.with_ymd_and_hms(2004, 3, 1, 8, 0, 0).unwrap())
+    );
+    // Content wins over summary; XHTML is serialized without the wrapping div.
+    assert_eq!(
+        first.content,
+        r#"<p>Some <a href="https://example.com/?a=1&amp;b=2">XHTML</a> content</p>"#
+    );
+    assert_eq!(
+        first.enclosure,
+        Some(Enclosure {
+            url: String::from("https://example.com/video.mp4"),
+            mime_type: String::from("video/mp4"),
+            length: Some(9876),
+        })
+    );
+
+    let second = feed.items[1].borrow();
+    // Entries without an author inherit the feed's.
+    assert_eq!(second.author, "Feed Author");
+    assert_eq!(second.url, "https://example.com/atom-second");
+    assert_eq!(second.content, "<p>HTML summary</p>");
+    assert_eq!(
+        second.date,
+        Some(Utc.with_ymd_and_hms(2004, 3, 3, 0, 0, 0).unwrap())
+    );
+}
+
+#[test]
+fn rejects_documents_that_are_not_feeds() {
+    match parse_file(&fixture("not_a_feed.xml")) {
+        Err(ParseError::UnknownFormat(root)) => assert_eq!(root, "html"),
+        other => panic!("expected UnknownFormat, got {:?}", other.map(|f| f.title)),
+    }
+}
+
+#[test]
+fn rejects_malformed_xml() {
+    assert!(matches!(
+        parse_file(&fixture("malformed.xml")),
+        Err(ParseError::Xml(_))
+    ));
+}
+
+#[test]
+fn reports_missing_files() {
+    assert!(matches!(
+        parse_file(&fixture("does-not-exist.xml")),
+        Err(ParseError::Io(_))
+    ));
+}
@@ -0,0 +1,115 @@
+use ratatui::{backend::TestBackend, Terminal};
+use std::{rc::Rc, time::Instant};
+
+use ui::app::App;
+use ui::cache::Cache;
+use ui::config::Config;
+use ui::event::{Event, Key};
+use ui::example;
+use ui::timer::Timer;
+
+fn app() -> App<TestBackend> {
+    let cache = Rc::new(Cache::in_memory().unwrap());
+    App::new(example::example_feeds(), cache, None, Config::default())
+}
+
+/// Draw `app` the way the event loop does: only if it needs redrawing.
+fn frame(app: &mut App<TestBackend>, terminal: &mut Terminal<TestBackend>) {
+    if app.needs_redraw() {
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+    }
+}
+
+fn terminal() -> Terminal<TestBackend> {
+    Terminal::new(TestBackend::new(60, 10)).unwrap()
+}
+
+#[test]
+fn first_frame_is_drawn() {
+    let mut app = app();
+    let mut terminal = terminal();
+    assert!(app.needs_redraw());
+    frame(&mut app, &mut terminal);
+    assert_eq!(app.redraw_count(), 1);
+    assert!(!app.needs_redraw());
+}
+
+#[test]
+fn ignored_keys_are_not_drawn() {
+    let mut app = app();
+    let mut terminal = terminal();
+    frame(&mut app, &mut terminal);
+
+    for _ in 0..100 {
+        app.handle_key(Key::F(12));
+        frame(&mut app, &mut terminal);
+    }
+    assert_eq!(app.redraw_count(), 1);
+}
+
+#[test]
+fn changes_are_drawn() {
+    let mut app = app();
+    let mut terminal = terminal();
+    frame(&mut app, &mut terminal);
+
+    app.handle_key(Key::Down);
+    assert!(app.needs_redraw());
+    frame(&mut app, &mut terminal);
+
+    // The pending key shows up in the corner, then the binding is performed.
+    app.handle_key(Key::Char('g'));
+    assert!(app.needs_redraw());
+    frame(&mut app, &mut terminal);
+    app.handle_key(Key::Char('g'));
+    assert!(app.needs_redraw());
+    frame(&mut app, &mut terminal);
+
+    // Each key typed into the command line is drawn.
+    for c in ":nope".chars() {
+        app.handle_key(Key::Char(c));
+        assert!(app.needs_redraw());
+        frame(&mut app, &mut terminal);
+    }
+    app.handle_key(Key::Char('\n'));
+    frame(&mut app, &mut terminal);
+    assert_eq!(app.redraw_count(), 10);
+
+    // An ignored key still dismisses the error, so that has to be drawn; the next one doesn't.
+    app.handle_key(Key::F(12));
+    assert!(app.needs_redraw());
+    frame(&mut app, &mut terminal);
+    app.handle_key(Key::F(12));
+    assert!(!app.needs_redraw());
+}
+
+#[test]
+fn events_handled_between_frames_are_drawn_once() {
+    let mut app = app();
+    let mut terminal = terminal();
+    frame(&mut app, &mut terminal);
+
+    // Which events arrive together is up to the event loop; however many it handles before
+    // drawing, they cost just one frame.
+    for _ in 0..20 {
+        app.handle_key(Key::Down);
+    }
+    frame(&mut app, &mut terminal);
+    assert_eq!(app.redraw_count(), 2);
+}
+
+#[test]
+fn background_changes_are_drawn() {
+    let mut app = app();
+    let mut terminal = terminal();
+    frame(&mut app, &mut terminal);
+
+    app.handle_event(&Event::ReloadFinished);
+    assert!(app.needs_redraw());
+    frame(&mut app, &mut terminal);
+
+    // Timers that nobody cares about don't need a frame.
+    app.add_timer(Timer::StatusMessage, Instant::now(), None);
+    app.handle_tick();
+    assert!(!app.needs_redraw());
+}
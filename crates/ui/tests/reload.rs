@@ -0,0 +1,168 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::PathBuf,
+    rc::Rc,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use ratatui::backend::TestBackend;
+
+use ui::app::App;
+use ui::cache::Cache;
+use ui::config::Config;
+use ui::event::Event;
+use ui::feed::Feed;
+use ui::reload::{apply, spawn};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("data")
+        .join(name)
+}
+
+/// Serve `/rss20.xml` and `/atom10.xml` fixtures over HTTP, answering anything else with 404.
+/// Returns the server's base URL.
+fn serve_fixtures() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // Skip the headers.
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+            let response = match fs::read_to_string(fixture(path.trim_start_matches('/'))) {
+                Ok(body) if path.ends_with(".xml") => format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/xml\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                ),
+                _ => String::from(
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                ),
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    format!("http://{}", address)
+}
+
+/// Collect events until `Event::ReloadFinished`, returning the fetch results by URL.
+fn collect_results(rx: &mpsc::Receiver<Event>) -> HashMap<String, Result<String, String>> {
+    let mut results = HashMap::new();
+    loop {
+        match rx.recv_timeout(Duration::from_secs(10)).unwrap() {
+            Event::FeedFetched { url, result } => {
+                assert!(results.insert(url, result).is_none(), "fetched twice");
+            }
+            Event::ReloadFinished => return results,
+            _ => panic!("unexpected event"),
+        }
+    }
+}
+
+#[test]
+fn fetches_feeds_on_worker_threads() {
+    let server = serve_fixtures();
+    let file_url = format!("file://{}", fixture("rss091.xml").display());
+    let urls = vec![
+        format!("{}/rss20.xml", server),
+        format!("{}/atom10.xml", server),
+        format!("{}/missing.xml", server),
+        file_url.clone(),
+    ];
+
+    let (tx, rx) = mpsc::channel();
+    spawn(urls.clone(), 2, tx);
+    let results = collect_results(&rx);
+
+    assert_eq!(results.len(), urls.len());
+    assert!(results[&urls[0]]
+        .as_ref()
+        .unwrap()
+        .contains("Example RSS 2.0 feed"));
+    assert!(results[&urls[1]].as_ref().unwrap().contains("<feed"));
+    assert!(results[&urls[2]].as_ref().unwrap_err().contains("404"));
+    assert!(results[&file_url].as_ref().unwrap().contains("RSS 0.91"));
+}
+
+#[test]
+fn finishes_even_without_feeds() {
+    let (tx, rx) = mpsc::channel();
+    spawn(vec![], 4, tx);
+    assert!(collect_results(&rx).is_empty());
+}
+
+#[test]
+fn applying_a_fetched_feed_keeps_read_state() {
+    let cache = Cache::in_memory().unwrap();
+    let document = fs::read_to_string(fixture("rss20.xml")).unwrap();
+    let feed = Rc::new(RefCell::new(Feed {
+        url: String::from("https://example.com/feed.xml"),
+        ..Feed::default()
+    }));
+
+    apply(&feed, &document, &cache).unwrap();
+    assert_eq!(feed.borrow().title, "Example RSS 2.0 feed");
+    assert_eq!(feed.borrow().unread_count(), 2);
+
+    // Read the first item, but don't save it; the next reload should take care of that.
+    feed.borrow().items[0].borrow_mut().unread = false;
+    apply(&feed, &document, &cache).unwrap();
+    assert_eq!(feed.borrow().total_count(), 2);
+    assert_eq!(feed.borrow().unread_count(), 1);
+    let cached = cache.load_feed(&feed.borrow().url).unwrap().unwrap();
+    assert_eq!(cached.unread_count(), 1);
+
+    // Broken documents leave the feed alone.
+    assert!(apply(&feed, "<rss><channel>", &cache).is_err());
+    assert_eq!(feed.borrow().total_count(), 2);
+}
+
+#[test]
+fn fetched_feeds_update_every_subscription_to_them() {
+    let url = "https://example.com/feed.xml";
+    let feeds = ["news", "tech"]
+        .iter()
+        .map(|tag| {
+            Rc::new(RefCell::new(Feed {
+                url: String::from(url),
+                tags: vec![tag.to_string()],
+                ..Feed::default()
+            }))
+        })
+        .collect::<Vec<_>>();
+    let cache = Rc::new(Cache::in_memory().unwrap());
+    let mut app: App<TestBackend> = App::new(feeds.clone(), cache, None, Config::default());
+
+    let document = fs::read_to_string(fixture("rss20.xml")).unwrap();
+    app.handle_event(&Event::FeedFetched {
+        url: String::from(url),
+        result: Ok(document),
+    });
+    for feed in &feeds {
+        assert_eq!(feed.borrow().title, "Example RSS 2.0 feed");
+        assert_eq!(feed.borrow().total_count(), 2);
+    }
+}
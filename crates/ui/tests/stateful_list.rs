@@ -0,0 +1,50 @@
+use ui::stateful_list::StatefulList;
+
+#[test]
+fn visible_range_follows_the_selection() {
+    let mut list = StatefulList::with_items((0..10).collect::<Vec<_>>());
+    assert_eq!(list.visible_range(4), 0..4);
+
+    list.select_nearest(5);
+    assert_eq!(list.visible_range(4), 2..6);
+    assert_eq!(list.state.offset(), 2);
+
+    // Moving within the visible rows doesn't scroll.
+    list.select_nearest(3);
+    assert_eq!(list.visible_range(4), 2..6);
+
+    list.select_nearest(0);
+    assert_eq!(list.visible_range(4), 0..4);
+
+    list.select_nearest(9);
+    assert_eq!(list.visible_range(20), 0..10);
+}
+
+#[test]
+fn visible_range_of_nothing_is_empty() {
+    let mut list = StatefulList::<u32>::new();
+    assert_eq!(list.visible_range(4), 0..0);
+
+    let mut list = StatefulList::with_items(vec![1, 2, 3]);
+    assert_eq!(list.visible_range(0), 0..0);
+}
+
+#[test]
+fn moving_by_many_items_stops_at_the_ends() {
+    let mut list = StatefulList::with_items((0..10).collect::<Vec<_>>());
+    list.next_by(3);
+    assert_eq!(list.selected(), Some(&3));
+
+    list.next_by(usize::MAX);
+    assert_eq!(list.selected(), Some(&9));
+
+    list.previous_by(2);
+    assert_eq!(list.selected(), Some(&7));
+
+    list.previous_by(usize::MAX);
+    assert_eq!(list.selected(), Some(&0));
+
+    let mut list = StatefulList::<u32>::new();
+    list.next_by(5);
+    assert_eq!(list.selected(), None);
+}
@@ -0,0 +1,220 @@
+use ratatui::{backend::TestBackend, Terminal};
+use std::{rc::Rc, time::Duration};
+
+use ui::app::App;
+use ui::cache::Cache;
+use ui::config::{Config, Settings};
+use ui::event::Key;
+use ui::example;
+use ui::history::Histories;
+use ui::status_bar::{Input, StatusBar};
+use ui::theme::Element;
+
+fn status_bar() -> StatusBar {
+    StatusBar::new(Histories::default())
+}
+
+/// Press `keys` one after another, returning what the status bar did with the last one.
+fn press(status_bar: &mut StatusBar, keys: &str) -> Input {
+    let mut input = Input::Consumed;
+    for c in keys.chars() {
+        input = status_bar.handle_key(Key::Char(c), &Settings::default(), &[]);
+    }
+    input
+}
+
+#[test]
+fn messages_stay_until_they_time_out() {
+    let mut status_bar = status_bar();
+    assert_eq!(status_bar.message(), None);
+    assert_eq!(status_bar.deadline(), None);
+
+    status_bar.info(String::from("Saved"));
+    assert_eq!(status_bar.message(), Some("Saved"));
+    assert!(status_bar.deadline().is_some());
+
+    status_bar.error("no such file");
+    assert_eq!(status_bar.message(), Some("Error: no such file"));
+
+    status_bar.show(String::from("Gone already"), Duration::ZERO);
+    assert_eq!(status_bar.message(), None);
+    assert_eq!(status_bar.deadline(), None);
+}
+
+#[test]
+fn keys_dismiss_the_message() {
+    let mut status_bar = status_bar();
+    status_bar.info(String::from("Saved"));
+    assert_eq!(press(&mut status_bar, "j"), Input::Ignored(Key::Char('j')));
+    assert_eq!(status_bar.message(), None);
+}
+
+#[test]
+fn command_line_takes_keys_until_it_is_closed() {
+    let mut status_bar = status_bar();
+    status_bar.open_command_line();
+    assert!(status_bar.is_command_line_open());
+    assert_eq!(press(&mut status_bar, "qu"), Input::Consumed);
+    assert_eq!(
+        press(&mut status_bar, "it\n"),
+        Input::Submitted(String::from("quit"))
+    );
+    assert!(!status_bar.is_command_line_open());
+
+    status_bar.open_command_line();
+    assert_eq!(
+        status_bar.handle_key(Key::Esc, &Settings::default(), &[]),
+        Input::Consumed
+    );
+    assert!(!status_bar.is_command_line_open());
+    assert_eq!(press(&mut status_bar, "q"), Input::Ignored(Key::Char('q')));
+}
+
+#[test]
+fn search_prompt_has_its_own_history() {
+    let histories = Histories::default();
+    let mut status_bar = StatusBar::new(histories.clone());
+    status_bar.open_search();
+    assert!(status_bar.is_search_open());
+    assert!(!status_bar.is_command_line_open());
+    assert_eq!(
+        press(&mut status_bar, "rust\n"),
+        Input::Searched(String::from("rust"))
+    );
+    assert!(!status_bar.is_search_open());
+
+    status_bar.open_command_line();
+    press(&mut status_bar, "quit\n");
+    assert_eq!(histories.search.borrow().entries(), ["rust"]);
+    assert_eq!(histories.cmdline.borrow().entries(), ["quit"]);
+
+    // Up recalls the previous search, not the command.
+    status_bar.open_search();
+    status_bar.handle_key(Key::Up, &Settings::default(), &[]);
+    assert_eq!(
+        press(&mut status_bar, "\n"),
+        Input::Searched(String::from("rust"))
+    );
+}
+
+#[test]
+fn command_line_completes_tags() {
+    let mut status_bar = status_bar();
+    status_bar.open_command_line();
+    press(&mut status_bar, "tag n");
+    let tags = [String::from("news")];
+    status_bar.handle_key(Key::Char('\t'), &Settings::default(), &tags);
+    assert_eq!(
+        press(&mut status_bar, "\n"),
+        Input::Submitted(String::from("tag news"))
+    );
+}
+
+/// The bottom row of what `app` draws.
+fn bottom_row(app: &mut App<TestBackend>) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+    terminal.draw(|frame| app.draw(frame)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let y = buffer.area.height - 1;
+    (0..buffer.area.width)
+        .map(|x| buffer.get(x, y).symbol.as_str())
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+fn type_in(app: &mut App<TestBackend>, text: &str) {
+    for c in text.chars() {
+        app.handle_key(Key::Char(c));
+    }
+}
+
+#[test]
+fn every_dialog_has_a_command_line() {
+    let cache = Rc::new(Cache::in_memory().unwrap());
+    let mut app = App::new(example::example_feeds(), cache, None, Config::default());
+
+    type_in(&mut app, ":frob");
+    assert_eq!(bottom_row(&mut app), ":frob");
+    type_in(&mut app, "nicate\n");
+    assert_eq!(bottom_row(&mut app), "Error: not a command: frobnicate");
+
+    // In the itemlist...
+    app.handle_key(Key::Char('\n'));
+    assert_eq!(bottom_row(&mut app), "");
+    type_in(&mut app, ":tag\n");
+    assert_eq!(bottom_row(&mut app), "Error: `tag` can't be used here");
+
+    // ...and in the article.
+    app.handle_key(Key::Char('\n'));
+    type_in(&mut app, ":save\n");
+    assert_eq!(bottom_row(&mut app), "Error: usage: save FILE");
+    type_in(&mut app, ":set show-keymap-hint\n");
+    assert_eq!(bottom_row(&mut app), "show-keymap-hint = yes");
+}
+
+#[test]
+fn status_bar_looks_like_info() {
+    let cache = Rc::new(Cache::in_memory().unwrap());
+    let config = Config::default();
+    let info = config.theme.style(Element::Info);
+    let mut app = App::new(example::example_feeds(), cache, None, config);
+    type_in(&mut app, ":frob");
+
+    let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+    terminal.draw(|frame| app.draw(frame)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let y = buffer.area.height - 1;
+    for x in [0, 1, 59] {
+        let cell = buffer.get(x, y);
+        assert_eq!(cell.fg, info.fg.unwrap(), "column {}", x);
+        assert_eq!(cell.bg, info.bg.unwrap(), "column {}", x);
+    }
+}
+
+#[test]
+fn tiny_terminals_leave_no_room_for_the_command_line() {
+    let cache = Rc::new(Cache::in_memory().unwrap());
+    let mut app = App::new(example::example_feeds(), cache, None, Config::default());
+    type_in(&mut app, ":frob");
+
+    for (width, height) in [(3, 2), (3, 1), (1, 3)] {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+    }
+}
+
+#[test]
+fn searches_select_the_next_match() {
+    let cache = Rc::new(Cache::in_memory().unwrap());
+    let mut app = App::new(example::example_feeds(), cache, None, Config::default());
+    let titles = example::example_feeds()
+        .iter()
+        .map(|feed| feed.borrow().display_title().to_string())
+        .collect::<Vec<_>>();
+    let wanted = titles.last().unwrap();
+
+    type_in(&mut app, "/");
+    assert_eq!(bottom_row(&mut app), "/");
+    type_in(&mut app, &format!("{}\n", wanted.to_uppercase()));
+    assert_eq!(bottom_row(&mut app), "");
+    assert_eq!(
+        app.search_history().borrow().entries(),
+        [wanted.to_uppercase()]
+    );
+
+    type_in(&mut app, "/no such feed\n");
+    assert_eq!(
+        bottom_row(&mut app),
+        "Error: no feed matches `no such feed`"
+    );
+    // The feed that matched is the one that opens.
+    app.handle_key(Key::Char('\n'));
+    let mut terminal = Terminal::new(TestBackend::new(200, 10)).unwrap();
+    terminal.draw(|frame| app.draw(frame)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let top_row = (0..buffer.area.width)
+        .map(|x| buffer.get(x, 0).symbol.as_str())
+        .collect::<String>();
+    assert!(top_row.contains(wanted.as_str()), "{}", top_row);
+}
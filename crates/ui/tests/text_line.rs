@@ -0,0 +1,356 @@
+use ratatui::{buffer::Buffer, layout::Rect, widgets::StatefulWidget};
+use std::{cell::RefCell, rc::Rc};
+use unicode_width::UnicodeWidthStr;
+
+use ui::event::Key;
+use ui::history::History;
+use ui::widgets::text_line::{Completions, Outcome, TextLine, TextLineState};
+
+/// Type `text` into `state`.
+fn type_in(state: &mut TextLineState, text: &str) {
+    for c in text.chars() {
+        state.handle_key(&Key::Char(c));
+    }
+}
+
+/// Press `keys` one after another, returning what the last one did.
+fn press(state: &mut TextLineState, keys: &[Key]) -> Outcome {
+    let mut outcome = Outcome::Ignored;
+    for key in keys {
+        outcome = state.handle_key(key);
+    }
+    outcome
+}
+
+fn ctrl(c: char) -> Key {
+    Key::Ctrl(Box::new(Key::Char(c)))
+}
+
+fn alt(key: Key) -> Key {
+    Key::Alt(Box::new(key))
+}
+
+/// Text of the line, with a `|` where the cursor is.
+fn with_cursor(state: &TextLineState) -> String {
+    // Everything after the cursor is what Ctrl-K would kill.
+    let mut probe = state.clone();
+    press(&mut probe, &[ctrl('k')]);
+    let mut text = state.text().to_string();
+    text.insert(probe.text().len(), '|');
+    text
+}
+
+/// Render `state` into a line `width` columns wide, returning what's displayed.
+fn render(state: &mut TextLineState, width: u16) -> String {
+    let area = Rect::new(0, 0, width, 1);
+    let mut buffer = Buffer::empty(area);
+    TextLine::new().render(area, &mut buffer, state);
+    let mut displayed = String::new();
+    let mut cells = buffer.content.iter();
+    while let Some(cell) = cells.next() {
+        displayed.push_str(&cell.symbol);
+        // Cells covered by a wide character are blank.
+        for _ in 1..cell.symbol.width() {
+            cells.next();
+        }
+    }
+    displayed
+}
+
+#[test]
+fn non_ascii_text_can_be_typed() {
+    let mut state = TextLineState::default();
+    type_in(&mut state, "ну, почти");
+    assert_eq!(state.text(), "ну, почти");
+    assert_eq!(render(&mut state, 20), "ну, почти           ");
+    assert_eq!(state.cursor_display_offset(), 9);
+}
+
+#[test]
+fn wide_characters_take_up_two_columns() {
+    let mut state = TextLineState::default();
+    type_in(&mut state, "日本語");
+    assert_eq!(render(&mut state, 10), "日本語    ");
+    assert_eq!(state.cursor_display_offset(), 6);
+
+    // The viewport scrolls by whole characters, keeping a column for the cursor.
+    type_in(&mut state, "テキスト");
+    assert_eq!(render(&mut state, 10), "テキスト  ");
+    assert_eq!(state.cursor_display_offset(), 8);
+    type_in(&mut state, "!");
+    assert_eq!(render(&mut state, 10), "テキスト! ");
+    assert_eq!(state.cursor_display_offset(), 9);
+    type_in(&mut state, "?");
+    assert_eq!(render(&mut state, 10), "キスト!?  ");
+    assert_eq!(state.cursor_display_offset(), 8);
+}
+
+#[test]
+fn combining_marks_stay_with_their_letter() {
+    let mut state = TextLineState::default();
+    type_in(&mut state, "cafe\u{301}");
+    assert_eq!(state.text(), "cafe\u{301}");
+    assert_eq!(state.cursor_display_offset(), 4);
+    assert_eq!(render(&mut state, 6), "cafe\u{301}  ");
+    assert_eq!(state.cursor_display_offset(), 4);
+}
+
+#[test]
+fn enter_submits_and_esc_or_ctrl_g_cancel() {
+    let mut state = TextLineState::default();
+    assert_eq!(press(&mut state, &[Key::Char('q')]), Outcome::Edited);
+    assert_eq!(press(&mut state, &[Key::Char('\n')]), Outcome::Submitted);
+    assert_eq!(press(&mut state, &[Key::Esc]), Outcome::Cancelled);
+    assert_eq!(press(&mut state, &[ctrl('g')]), Outcome::Cancelled);
+    assert_eq!(press(&mut state, &[Key::F(1)]), Outcome::Ignored);
+    assert_eq!(state.text(), "q");
+
+    state.clear();
+    assert_eq!(state.text(), "");
+}
+
+#[test]
+fn cursor_moves_by_characters_words_and_lines() {
+    let mut state = TextLineState::default();
+    type_in(&mut state, "set browser-cmd lynx");
+    assert_eq!(with_cursor(&state), "set browser-cmd lynx|");
+
+    press(&mut state, &[alt(Key::Char('b'))]);
+    assert_eq!(with_cursor(&state), "set browser-cmd |lynx");
+    press(&mut state, &[Key::Ctrl(Box::new(Key::Left)), ctrl('b')]);
+    assert_eq!(with_cursor(&state), "set browser|-cmd lynx");
+    press(&mut state, &[ctrl('a'), alt(Key::Char('f'))]);
+    assert_eq!(with_cursor(&state), "set| browser-cmd lynx");
+    press(&mut state, &[Key::Ctrl(Box::new(Key::Right)), ctrl('f')]);
+    assert_eq!(with_cursor(&state), "set browser-|cmd lynx");
+    press(&mut state, &[Key::Home, Key::Right]);
+    assert_eq!(with_cursor(&state), "s|et browser-cmd lynx");
+    press(&mut state, &[ctrl('e'), Key::Left]);
+    assert_eq!(with_cursor(&state), "set browser-cmd lyn|x");
+    press(&mut state, &[Key::End]);
+    assert_eq!(with_cursor(&state), "set browser-cmd lynx|");
+}
+
+#[test]
+fn characters_are_deleted_whole() {
+    let mut state = TextLineState::default();
+    type_in(&mut state, "cafe\u{301}s");
+    press(&mut state, &[Key::Left, Key::Backspace]);
+    assert_eq!(with_cursor(&state), "caf|s");
+    press(&mut state, &[ctrl('h'), Key::Delete]);
+    assert_eq!(with_cursor(&state), "ca|");
+    press(&mut state, &[ctrl('a'), ctrl('d')]);
+    assert_eq!(with_cursor(&state), "|a");
+    // There's nothing before the cursor.
+    assert_eq!(press(&mut state, &[Key::Backspace]), Outcome::Edited);
+    assert_eq!(with_cursor(&state), "|a");
+}
+
+#[test]
+fn killed_text_can_be_yanked_back() {
+    let mut state = TextLineState::default();
+    type_in(&mut state, "open http://example.com/feed now");
+    press(&mut state, &[alt(Key::Char('b')), ctrl('w')]);
+    assert_eq!(with_cursor(&state), "open |now");
+    press(&mut state, &[Key::End, ctrl('y')]);
+    assert_eq!(with_cursor(&state), "open nowhttp://example.com/feed |");
+
+    press(&mut state, &[ctrl('u')]);
+    assert_eq!(with_cursor(&state), "|");
+    press(&mut state, &[ctrl('y')]);
+    assert_eq!(with_cursor(&state), "open nowhttp://example.com/feed |");
+}
+
+#[test]
+fn consecutive_kills_are_joined() {
+    let mut state = TextLineState::default();
+    type_in(&mut state, "one two three four");
+    press(&mut state, &[alt(Key::Backspace), alt(Key::Backspace)]);
+    assert_eq!(with_cursor(&state), "one two |");
+    press(
+        &mut state,
+        &[ctrl('a'), alt(Key::Char('d')), alt(Key::Char('d'))],
+    );
+    assert_eq!(with_cursor(&state), "| ");
+    press(&mut state, &[ctrl('y')]);
+    assert_eq!(with_cursor(&state), "one two| ");
+
+    // Moving the cursor in between starts a new kill.
+    press(
+        &mut state,
+        &[ctrl('a'), ctrl('k'), Key::Char('x'), ctrl('a'), ctrl('k')],
+    );
+    press(&mut state, &[ctrl('y'), alt(Key::Char('y'))]);
+    assert_eq!(with_cursor(&state), "one two |");
+}
+
+#[test]
+fn yank_pop_cycles_through_the_kill_ring() {
+    let mut state = TextLineState::default();
+    for word in ["first", "second", "third"] {
+        type_in(&mut state, word);
+        press(&mut state, &[ctrl('u'), Key::Left]);
+    }
+
+    // Alt-Y does nothing unless it follows a yank.
+    press(&mut state, &[alt(Key::Char('y'))]);
+    assert_eq!(with_cursor(&state), "|");
+
+    press(&mut state, &[ctrl('y')]);
+    assert_eq!(with_cursor(&state), "third|");
+    press(&mut state, &[alt(Key::Char('y'))]);
+    assert_eq!(with_cursor(&state), "second|");
+    press(&mut state, &[alt(Key::Char('y')), alt(Key::Char('y'))]);
+    assert_eq!(with_cursor(&state), "third|");
+
+    // The kill ring survives clearing the line.
+    state.clear();
+    press(&mut state, &[ctrl('y')]);
+    assert_eq!(with_cursor(&state), "third|");
+}
+
+/// A text line with `lines` in its history.
+fn with_history(lines: &[&str]) -> (TextLineState, Rc<RefCell<History>>) {
+    let history = Rc::new(RefCell::new(History::new(10)));
+    for line in lines {
+        history.borrow_mut().add(line);
+    }
+    (TextLineState::with_history(history.clone()), history)
+}
+
+#[test]
+fn submitted_lines_are_added_to_history() {
+    let (mut state, history) = with_history(&[]);
+    type_in(&mut state, "set browser w3m");
+    press(&mut state, &[Key::Char('\n')]);
+    state.clear();
+    type_in(&mut state, "quit");
+    // A cancelled line isn't remembered.
+    press(&mut state, &[Key::Esc]);
+    assert_eq!(history.borrow().entries(), ["set browser w3m"]);
+}
+
+#[test]
+fn up_and_down_walk_through_history() {
+    let (mut state, _history) = with_history(&["first", "second"]);
+    type_in(&mut state, "dra");
+
+    press(&mut state, &[Key::Up]);
+    assert_eq!(with_cursor(&state), "second|");
+    press(&mut state, &[ctrl('p')]);
+    assert_eq!(with_cursor(&state), "first|");
+    // There's nothing older.
+    press(&mut state, &[Key::Up]);
+    assert_eq!(with_cursor(&state), "first|");
+
+    press(&mut state, &[ctrl('n')]);
+    assert_eq!(with_cursor(&state), "second|");
+    // Past the newest entry is the line that was being typed.
+    press(&mut state, &[Key::Down]);
+    assert_eq!(with_cursor(&state), "dra|");
+    press(&mut state, &[Key::Down]);
+    assert_eq!(with_cursor(&state), "dra|");
+}
+
+#[test]
+fn ctrl_r_searches_history_backwards() {
+    let (mut state, _history) = with_history(&["set browser w3m", "quit", "set browser lynx"]);
+    type_in(&mut state, "typed");
+
+    press(&mut state, &[ctrl('r')]);
+    assert_eq!(
+        state.search_prompt().as_deref(),
+        Some("(reverse-i-search)`': ")
+    );
+    type_in(&mut state, "bro");
+    assert_eq!(with_cursor(&state), "set |browser lynx");
+    assert_eq!(
+        state.search_prompt().as_deref(),
+        Some("(reverse-i-search)`bro': ")
+    );
+
+    // Ctrl-R looks further back, and stays at the last match when there's nothing more.
+    press(&mut state, &[ctrl('r')]);
+    assert_eq!(with_cursor(&state), "set |browser w3m");
+    press(&mut state, &[ctrl('r')]);
+    assert_eq!(with_cursor(&state), "set |browser w3m");
+    assert_eq!(
+        state.search_prompt().as_deref(),
+        Some("(failed reverse-i-search)`bro': ")
+    );
+
+    // Ctrl-G goes back to what was typed before the search.
+    press(&mut state, &[ctrl('g')]);
+    assert_eq!(state.search_prompt(), None);
+    assert_eq!(with_cursor(&state), "typed|");
+
+    // Other keys end the search and then do their usual thing.
+    press(&mut state, &[ctrl('r'), Key::Char('q'), Key::End]);
+    assert_eq!(state.search_prompt(), None);
+    assert_eq!(with_cursor(&state), "quit|");
+    press(&mut state, &[ctrl('r')]);
+    type_in(&mut state, "w3");
+    assert_eq!(press(&mut state, &[Key::Char('\n')]), Outcome::Submitted);
+    assert_eq!(state.text(), "set browser w3m");
+}
+
+/// Completions of the last word of `line` from `words`.
+fn complete_from(words: &[&str], line: &str) -> Completions {
+    let start = line.rfind(' ').map_or(0, |space| space + 1);
+    Completions {
+        start,
+        candidates: words
+            .iter()
+            .filter(|word| word.starts_with(&line[start..]))
+            .map(|word| word.to_string())
+            .collect(),
+    }
+}
+
+#[test]
+fn tab_completes_a_single_candidate_right_away() {
+    let mut state = TextLineState::default();
+    type_in(&mut state, "qu to");
+    press(&mut state, &[ctrl('a'), ctrl('f'), ctrl('f')]);
+    state.complete(false, |line| complete_from(&["quit", "set"], line));
+    assert_eq!(with_cursor(&state), "quit| to");
+
+    // Without candidates, nothing happens.
+    state.complete(false, |line| complete_from(&["set"], line));
+    assert_eq!(with_cursor(&state), "quit| to");
+}
+
+#[test]
+fn tab_extends_to_the_common_prefix_then_cycles() {
+    let words = ["show-read-feeds", "show-read-articles", "set"];
+    let mut state = TextLineState::default();
+    type_in(&mut state, "set sh");
+    state.complete(false, |line| complete_from(&words, line));
+    assert_eq!(with_cursor(&state), "set show-read-|");
+
+    state.complete(false, |_| {
+        unreachable!("cycling doesn't look for new completions")
+    });
+    assert_eq!(with_cursor(&state), "set show-read-feeds|");
+    state.complete(false, |_| unreachable!());
+    assert_eq!(with_cursor(&state), "set show-read-articles|");
+    state.complete(false, |_| unreachable!());
+    assert_eq!(with_cursor(&state), "set show-read-feeds|");
+    state.complete(true, |_| unreachable!());
+    assert_eq!(with_cursor(&state), "set show-read-articles|");
+
+    // Any other key accepts the completion.
+    type_in(&mut state, " ");
+    state.complete(false, |line| complete_from(&["yes", "no"], line));
+    assert_eq!(with_cursor(&state), "set show-read-articles yes|");
+}
+
+#[test]
+fn shift_tab_cycles_backwards_from_the_last_candidate() {
+    let mut state = TextLineState::default();
+    type_in(&mut state, "s");
+    state.complete(true, |line| complete_from(&["set", "source"], line));
+    assert_eq!(with_cursor(&state), "source|");
+    state.complete(true, |_| unreachable!());
+    assert_eq!(with_cursor(&state), "set|");
+}
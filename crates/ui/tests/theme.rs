@@ -0,0 +1,69 @@
+use ratatui::style::{Color, Modifier, Style};
+
+use ui::theme::{color_name, parse_color, Element, Theme};
+
+#[test]
+fn colors_parse() {
+    let cases = [
+        ("default", Some(Color::Reset)),
+        ("magenta", Some(Color::Magenta)),
+        // The terminal's white, not the bright one.
+        ("white", Some(Color::Gray)),
+        ("color15", Some(Color::Indexed(15))),
+        ("color0", Some(Color::Indexed(0))),
+        ("color255", Some(Color::Indexed(255))),
+        ("#ffd700", Some(Color::Rgb(0xff, 0xd7, 0x00))),
+        ("#FFD700", Some(Color::Rgb(0xff, 0xd7, 0x00))),
+        ("color256", None),
+        ("#ffd70", None),
+        ("#ffd7000", None),
+        ("#ffd7zz", None),
+        ("#ффф", None),
+        ("pink", None),
+    ];
+    for (name, color) in cases {
+        assert_eq!(parse_color(name), color, "{}", name);
+    }
+}
+
+#[test]
+fn color_names_parse_back_into_the_same_colors() {
+    let colors = [
+        Color::Reset,
+        Color::Black,
+        Color::Red,
+        Color::Gray,
+        Color::Indexed(42),
+        Color::Rgb(0xff, 0xd7, 0x00),
+    ];
+    for color in colors {
+        assert_eq!(parse_color(&color_name(color)), Some(color), "{:?}", color);
+    }
+
+    // The bright colors only have numbers, and each has its own.
+    assert_eq!(color_name(Color::LightRed), "color9");
+    assert_eq!(color_name(Color::White), "color15");
+}
+
+#[test]
+fn unconfigured_elements_look_like_their_relatives() {
+    let mut theme = Theme::default();
+    let info = theme.style(Element::Info);
+    assert_eq!(theme.style(Element::Title), info);
+    assert_eq!(theme.style(Element::Hint), info);
+    assert_eq!(theme.list_row(true, true), theme.list_row(true, false));
+
+    let unread = Style::default()
+        .fg(Color::Yellow)
+        .bg(Color::Reset)
+        .add_modifier(Modifier::BOLD);
+    theme.set(Element::ListNormalUnread, unread);
+    theme.set(Element::Info, Style::default().bg(Color::Red));
+    assert_eq!(theme.list_row(false, true), unread);
+    assert_ne!(theme.list_row(false, false), unread);
+    assert_eq!(theme.style(Element::Title), Style::default().bg(Color::Red));
+
+    // A configured element no longer follows its relative.
+    theme.set(Element::Hint, Style::default());
+    assert_eq!(theme.style(Element::Hint), Style::default());
+}
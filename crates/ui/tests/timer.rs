@@ -0,0 +1,168 @@
+use ratatui::backend::TestBackend;
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use ui::app::{App, KEY_SEQUENCE_TIMEOUT};
+use ui::cache::Cache;
+use ui::config::Config;
+use ui::event::Key;
+use ui::example;
+use ui::status_bar::ERROR_TIMEOUT;
+use ui::timer::{Timer, Timers};
+
+const SECOND: Duration = Duration::from_secs(1);
+
+#[test]
+fn one_shot_timers_go_off_once() {
+    let start = Instant::now();
+    let mut timers = Timers::default();
+    assert_eq!(timers.next_deadline(), None);
+
+    let later = timers.add(Timer::StatusMessage, start + 2 * SECOND, None);
+    let sooner = timers.add(Timer::KeySequence, start + SECOND, None);
+    assert_eq!(timers.next_deadline(), Some(start + SECOND));
+
+    assert_eq!(timers.expire(start), vec![]);
+    assert_eq!(
+        timers.expire(start + 3 * SECOND),
+        vec![(sooner, Timer::KeySequence), (later, Timer::StatusMessage)]
+    );
+    assert!(!timers.is_scheduled(sooner));
+    assert_eq!(timers.next_deadline(), None);
+    assert_eq!(timers.expire(start + 4 * SECOND), vec![]);
+}
+
+#[test]
+fn periodic_timers_keep_going_off() {
+    let start = Instant::now();
+    let mut timers = Timers::default();
+    let id = timers.add(Timer::Reload, start + SECOND, Some(SECOND));
+
+    assert_eq!(timers.expire(start + SECOND), vec![(id, Timer::Reload)]);
+    assert_eq!(timers.next_deadline(), Some(start + 2 * SECOND));
+
+    // Missed periods don't pile up.
+    assert_eq!(
+        timers.expire(start + 10 * SECOND),
+        vec![(id, Timer::Reload)]
+    );
+    assert_eq!(timers.next_deadline(), Some(start + 11 * SECOND));
+}
+
+#[test]
+fn cancelled_timers_do_not_go_off() {
+    let start = Instant::now();
+    let mut timers = Timers::default();
+    let id = timers.add(Timer::Reload, start, Some(SECOND));
+    assert!(timers.is_scheduled(id));
+    timers.cancel(id);
+    assert!(!timers.is_scheduled(id));
+    assert_eq!(timers.expire(start + SECOND), vec![]);
+    // Once is enough.
+    timers.cancel(id);
+}
+
+fn app(config: Config) -> App<TestBackend> {
+    let cache = Rc::new(Cache::in_memory().unwrap());
+    App::new(example::example_feeds(), cache, None, config)
+}
+
+#[test]
+fn incomplete_key_sequences_time_out() {
+    let mut app = app(Config::default());
+    assert_eq!(app.next_deadline(), None);
+
+    app.handle_key(Key::Char('g'));
+    let deadline = app.next_deadline().unwrap();
+    assert!(deadline <= Instant::now() + KEY_SEQUENCE_TIMEOUT);
+
+    app.handle_key(Key::Char('g'));
+    assert_eq!(app.next_deadline(), None);
+}
+
+#[test]
+fn status_messages_time_out() {
+    let mut app = app(Config::default());
+    for c in ":nope\n".chars() {
+        app.handle_key(Key::Char(c));
+    }
+    let deadline = app.next_deadline().unwrap();
+    assert!(deadline > Instant::now() + ERROR_TIMEOUT - SECOND);
+    assert!(deadline <= Instant::now() + ERROR_TIMEOUT);
+
+    // Any key dismisses the message, and then there's nothing to wait for.
+    app.handle_key(Key::Char('j'));
+    assert_eq!(app.next_deadline(), None);
+}
+
+#[test]
+fn feeds_are_reloaded_when_asked() {
+    let mut config = Config::default();
+    config.settings.auto_reload = true;
+    config.settings.reload_time = 5;
+    let mut app = app(config);
+    let deadline = app.next_deadline().unwrap();
+    assert!(deadline > Instant::now() + Duration::from_secs(4 * 60));
+    assert_eq!(app.take_reload_request(), None);
+
+    app.add_timer(Timer::Reload, Instant::now(), None);
+    app.handle_tick();
+    let urls = app.take_reload_request().unwrap();
+    assert_eq!(urls.len(), example::example_feeds().len());
+}
+
+#[test]
+fn ticks_are_told_apart_by_their_number() {
+    let start = Instant::now();
+    let mut timers = Timers::default();
+    let first = timers.add(Timer::Tick(1), start, Some(SECOND));
+    let second = timers.add(Timer::Tick(2), start + SECOND, None);
+
+    assert_eq!(timers.expire(start), vec![(first, Timer::Tick(1))]);
+    assert_eq!(
+        timers.expire(start + SECOND),
+        vec![(first, Timer::Tick(1)), (second, Timer::Tick(2))]
+    );
+}
+
+#[test]
+fn apps_schedule_periodic_ticks() {
+    let mut app = app(Config::default());
+    assert_eq!(app.next_deadline(), None);
+
+    let before = Instant::now();
+    let id = app.every(60 * SECOND, 7);
+    let deadline = app.next_deadline().unwrap();
+    assert!(deadline >= before + 60 * SECOND);
+    assert!(deadline <= Instant::now() + 60 * SECOND);
+
+    app.cancel_timer(id);
+    assert_eq!(app.next_deadline(), None);
+}
+
+#[test]
+fn timers_too_far_off_never_go_off() {
+    let mut timers = Timers::default();
+    let once = timers.once(Timer::StatusMessage, Duration::MAX);
+    let every = timers.every(Timer::Reload, Duration::MAX);
+    assert!(!timers.is_scheduled(once));
+    assert!(!timers.is_scheduled(every));
+    assert_eq!(timers.next_deadline(), None);
+
+    // A periodic timer whose next deadline can't be represented goes off one last time.
+    let start = Instant::now();
+    let id = timers.add(Timer::Reload, start, Some(Duration::MAX));
+    assert_eq!(timers.expire(start), vec![(id, Timer::Reload)]);
+    assert!(!timers.is_scheduled(id));
+}
+
+#[test]
+fn huge_reload_times_mean_never() {
+    let mut config = Config::default();
+    config.settings.auto_reload = true;
+    config.settings.reload_time = u64::MAX;
+    let mut app = app(config);
+    assert_eq!(app.next_deadline(), None);
+}
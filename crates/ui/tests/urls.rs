@@ -0,0 +1,125 @@
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+use ui::cache::Cache;
+use ui::feed::{Feed, Item};
+use ui::urls::{load_feeds, parse_str, UrlsError};
+
+const URLS: &str = r#"
+# Comments and empty lines are skipped.
+
+https://example.com/news.xml news "tech news" "~My news"  # trailing comment
+https://example.com/noisy.xml news ! news
+file:///tmp/local.xml
+"query:Unread news:unread = \"yes\" and tags # \"news\"" virtual
+"#;
+
+#[test]
+fn parses_urls_tags_titles_and_queries() {
+    let subscriptions = parse_str(URLS, Path::new("urls")).unwrap();
+    assert_eq!(subscriptions.len(), 4);
+
+    let news = &subscriptions[0];
+    assert_eq!(news.url, "https://example.com/news.xml");
+    assert_eq!(news.tags, ["news", "tech news"]);
+    assert_eq!(news.custom_title.as_deref(), Some("My news"));
+    assert!(!news.hidden);
+    assert!(news.query.is_none());
+
+    let noisy = &subscriptions[1];
+    assert!(noisy.hidden);
+    // Duplicate tags are dropped.
+    assert_eq!(noisy.tags, ["news"]);
+
+    assert_eq!(subscriptions[2].url, "file:///tmp/local.xml");
+    assert!(subscriptions[2].tags.is_empty());
+
+    let query = &subscriptions[3];
+    assert!(query.query.is_some());
+    assert_eq!(query.custom_title.as_deref(), Some("Unread news"));
+    assert_eq!(query.tags, ["virtual"]);
+}
+
+#[test]
+fn reports_malformed_lines_with_their_position() {
+    let cases = [
+        (
+            "https://example.com/ok.xml\nnot-a-url\n",
+            2,
+            "not a feed URL",
+        ),
+        (
+            "\"https://example.com/unterminated.xml\n",
+            1,
+            "unterminated",
+        ),
+        (
+            "\n\n\"query:Broken:unread = \"\n",
+            3,
+            "invalid filter expression",
+        ),
+        ("\"query::unread = \\\"yes\\\"\"\n", 1, "has no name"),
+    ];
+    for (text, expected_line, expected_message) in cases {
+        match parse_str(text, Path::new("/home/user/urls")) {
+            Err(error @ UrlsError::Malformed { .. }) => {
+                let message = error.to_string();
+                let prefix = format!("/home/user/urls:{}: ", expected_line);
+                assert!(message.starts_with(&prefix), "{}", message);
+                assert!(message.contains(expected_message), "{}", message);
+            }
+            other => panic!("expected an error for {:?}, got {:?}", text, other),
+        }
+    }
+}
+
+#[test]
+fn query_feeds_collect_matching_items_of_other_feeds() {
+    let cache = Cache::in_memory().unwrap();
+    let mut cached = Feed {
+        url: String::from("https://example.com/news.xml"),
+        title: String::from("News"),
+        ..Feed::default()
+    };
+    for (guid, unread) in [("read", false), ("unread", true)] {
+        cached.items.push(Rc::new(RefCell::new(Item {
+            guid: guid.to_string(),
+            unread,
+            ..Item::default()
+        })));
+    }
+    cache.save_feed(&cached).unwrap();
+
+    let subscriptions = parse_str(URLS, Path::new("urls")).unwrap();
+    let feeds = load_feeds(&subscriptions, &cache).unwrap();
+    assert_eq!(feeds.len(), 4);
+
+    let news = feeds[0].borrow();
+    assert_eq!(news.display_title(), "My news");
+    assert_eq!(news.tags, ["news", "tech news"]);
+    assert_eq!(news.total_count(), 2);
+    assert!(feeds[1].borrow().hidden);
+
+    let query = feeds[3].borrow();
+    assert_eq!(query.display_title(), "Unread news");
+    assert_eq!(query.items.len(), 1);
+    assert_eq!(query.items[0].borrow().guid, "unread");
+}
+
+#[test]
+fn written_subscriptions_parse_back_the_same() {
+    let subscriptions = parse_str(URLS, Path::new("urls")).unwrap();
+    let text = subscriptions
+        .iter()
+        .map(|subscription| subscription.to_line() + "\n")
+        .collect::<String>();
+    let reparsed = parse_str(&text, Path::new("urls")).unwrap();
+
+    assert_eq!(reparsed.len(), subscriptions.len());
+    for (original, reparsed) in subscriptions.iter().zip(&reparsed) {
+        assert_eq!(reparsed.url, original.url);
+        assert_eq!(reparsed.tags, original.tags);
+        assert_eq!(reparsed.custom_title, original.custom_title);
+        assert_eq!(reparsed.hidden, original.hidden);
+        assert_eq!(reparsed.query.is_some(), original.query.is_some());
+    }
+}
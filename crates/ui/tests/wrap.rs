@@ -0,0 +1,61 @@
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+use ui::wrap::wrap;
+
+/// Text of the `lines`, without styles.
+fn plain(lines: &[Line]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn lines_are_broken_between_words() {
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let spans = [
+        Span::raw("The quick "),
+        Span::styled("brown fox", bold),
+        Span::raw(" jumps over   the lazy dog"),
+    ];
+    let lines = wrap(&spans, 10);
+    assert_eq!(
+        plain(&lines),
+        ["The quick", "brown fox", "jumps over", "the lazy", "dog"]
+    );
+    assert_eq!(lines[1].spans, [Span::styled("brown fox", bold)]);
+
+    assert_eq!(
+        plain(&wrap(&spans, 100)),
+        ["The quick brown fox jumps over   the lazy dog"]
+    );
+}
+
+#[test]
+fn long_words_are_broken_at_the_edge() {
+    assert_eq!(
+        plain(&wrap(&[Span::raw("see https://example.com/long")], 8)),
+        ["see", "https://", "example.", "com/long"]
+    );
+    // Wide characters aren't split in half.
+    assert_eq!(
+        plain(&wrap(&[Span::raw("日本語のテキスト")], 5)),
+        ["日本", "語の", "テキ", "スト"]
+    );
+}
+
+#[test]
+fn leading_whitespace_and_empty_lines_stay() {
+    let indented = [Span::raw("    indented code")];
+    assert_eq!(plain(&wrap(&indented, 12)), ["    indented", "code"]);
+    assert_eq!(plain(&wrap(&indented, 10)), ["indented", "code"]);
+    assert_eq!(plain(&wrap(&[], 10)), [""]);
+}